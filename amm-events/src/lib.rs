@@ -0,0 +1,242 @@
+//! Decodes the events the amm program logs with `sol_log_data`.
+//!
+//! Events are read from the `Program data:` lines of transaction logs, only
+//! the ones logged while the amm program is the innermost invoked program
+//! being kept. The layouts mirror `amm::events`.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+pub const SWAP_EVENT: u8 = 0;
+pub const DEPOSIT_EVENT: u8 = 1;
+pub const WITHDRAW_EVENT: u8 = 2;
+
+const SWAP_EVENT_LEN: usize = 1 + 32 + 32 + 1 + 8 * 5;
+const LIQUIDITY_EVENT_LEN: usize = 1 + 32 + 32 + 8 * 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapEvent {
+    pub pool: [u8; 32],
+    pub user: [u8; 32],
+    /// Whether X went in and Y out
+    pub in_x: bool,
+    /// Amount received by the vault, net of transfer fees
+    pub amount_in: u64,
+    /// Amount sent by the vault, before transfer fees
+    pub amount_out: u64,
+    /// Swap fee, paid in the token going out
+    pub fee: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+/// Deposit or withdrawal of both tokens of a pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiquidityEvent {
+    pub pool: [u8; 32],
+    pub user: [u8; 32],
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmmEvent {
+    Swap(SwapEvent),
+    Deposit(LiquidityEvent),
+    Withdraw(LiquidityEvent),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data field is not valid base64
+    InvalidBase64,
+    /// The discriminator is not one of an amm event
+    UnknownEvent(u8),
+    /// The event is shorter than its layout
+    InvalidLength,
+}
+
+impl AmmEvent {
+    /// Decodes the raw bytes of a single logged data field
+    pub fn decode(data: &[u8]) -> Result<Self, DecodeError> {
+        let (&discriminator, _) = data.split_first().ok_or(DecodeError::InvalidLength)?;
+        let len = match discriminator {
+            SWAP_EVENT => SWAP_EVENT_LEN,
+            DEPOSIT_EVENT | WITHDRAW_EVENT => LIQUIDITY_EVENT_LEN,
+            _ => return Err(DecodeError::UnknownEvent(discriminator)),
+        };
+        if data.len() < len {
+            return Err(DecodeError::InvalidLength);
+        }
+
+        let pubkey = |offset: usize| -> [u8; 32] { data[offset..offset + 32].try_into().unwrap() };
+        let u64_at =
+            |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+
+        Ok(match discriminator {
+            SWAP_EVENT => Self::Swap(SwapEvent {
+                pool: pubkey(1),
+                user: pubkey(33),
+                in_x: data[65] != 0,
+                amount_in: u64_at(66),
+                amount_out: u64_at(74),
+                fee: u64_at(82),
+                reserve_x: u64_at(90),
+                reserve_y: u64_at(98),
+            }),
+            _ => {
+                let event = LiquidityEvent {
+                    pool: pubkey(1),
+                    user: pubkey(33),
+                    amount_x: u64_at(65),
+                    amount_y: u64_at(73),
+                    lp: u64_at(81),
+                    reserve_x: u64_at(89),
+                    reserve_y: u64_at(97),
+                };
+                if discriminator == DEPOSIT_EVENT {
+                    Self::Deposit(event)
+                } else {
+                    Self::Withdraw(event)
+                }
+            }
+        })
+    }
+
+    /// Pool config the event was logged for
+    pub fn pool(&self) -> &[u8; 32] {
+        match self {
+            Self::Swap(event) => &event.pool,
+            Self::Deposit(event) | Self::Withdraw(event) => &event.pool,
+        }
+    }
+}
+
+/// Decodes every event logged by `program_id`, given in base58, from the log
+/// messages of a transaction in order
+pub fn decode_logs<'a>(
+    program_id: &str,
+    logs: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<AmmEvent>, DecodeError> {
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        if let Some(data) = log.strip_prefix("Program data: ") {
+            if stack.last() != Some(&program_id) {
+                continue;
+            }
+            // sol_log_data logs one base64 string per field, events have one
+            for field in data.split(' ') {
+                let field = STANDARD
+                    .decode(field)
+                    .map_err(|_| DecodeError::InvalidBase64)?;
+                events.push(AmmEvent::decode(&field)?);
+            }
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            let (Some(program), Some(status)) = (words.next(), words.next()) else {
+                continue;
+            };
+            match status {
+                "invoke" => stack.push(program),
+                "success" | "failed:" => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    use crate::{decode_logs, AmmEvent, DecodeError, LiquidityEvent, SwapEvent};
+
+    const AMM: &str = "22222222222222222222222222222222222222222222";
+
+    fn swap_data() -> Vec<u8> {
+        [
+            vec![0],
+            [1u8; 32].to_vec(),
+            [2u8; 32].to_vec(),
+            vec![1],
+            1_000_000u64.to_le_bytes().to_vec(),
+            899_100u64.to_le_bytes().to_vec(),
+            99_901u64.to_le_bytes().to_vec(),
+            1_001_000_000u64.to_le_bytes().to_vec(),
+            999_100_900u64.to_le_bytes().to_vec(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(
+            AmmEvent::decode(&swap_data()),
+            Ok(AmmEvent::Swap(SwapEvent {
+                pool: [1; 32],
+                user: [2; 32],
+                in_x: true,
+                amount_in: 1_000_000,
+                amount_out: 899_100,
+                fee: 99_901,
+                reserve_x: 1_001_000_000,
+                reserve_y: 999_100_900,
+            }))
+        );
+
+        let data = [
+            vec![2],
+            [1u8; 32].to_vec(),
+            [2u8; 32].to_vec(),
+            [10u64, 20, 30, 40, 50]
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect(),
+        ]
+        .concat();
+        assert_eq!(
+            AmmEvent::decode(&data),
+            Ok(AmmEvent::Withdraw(LiquidityEvent {
+                pool: [1; 32],
+                user: [2; 32],
+                amount_x: 10,
+                amount_y: 20,
+                lp: 30,
+                reserve_x: 40,
+                reserve_y: 50,
+            }))
+        );
+
+        assert_eq!(AmmEvent::decode(&[3]), Err(DecodeError::UnknownEvent(3)));
+        assert_eq!(
+            AmmEvent::decode(&swap_data()[..105]),
+            Err(DecodeError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn decode_logs_of_the_program_only() {
+        let data = format!("Program data: {}", STANDARD.encode(swap_data()));
+        let logs = [
+            format!("Program {AMM} invoke [1]"),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [2]".to_string(),
+            // Logged by the token program, not an amm event
+            "Program data: AwAA".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA success".to_string(),
+            data,
+            format!("Program {AMM} consumed 20000 of 200000 compute units"),
+            format!("Program {AMM} success"),
+        ];
+
+        let events = decode_logs(AMM, logs.iter().map(String::as_str)).unwrap();
+        assert_eq!(events, vec![AmmEvent::decode(&swap_data()).unwrap()]);
+    }
+}
@@ -0,0 +1,64 @@
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const MINT_LP_SEED: &[u8] = b"mint_lp";
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const OBSERVATIONS_SEED: &[u8] = b"observations";
+pub const FACTORY_SEED: &[u8] = b"factory";
+pub const REGISTRY_SEED: &[u8] = b"registry";
+pub const FARM_SEED: &[u8] = b"farm";
+pub const STAKE_SEED: &[u8] = b"stake";
+pub const TWAMM_SEED: &[u8] = b"twamm";
+pub const ORDER_SEED: &[u8] = b"order";
+pub const NATIVE_SEED: &[u8] = b"native";
+pub const BATCH_SEED: &[u8] = b"batch";
+pub const INTENT_SEED: &[u8] = b"intent";
+
+/// Status flags, combined in the Config status byte
+pub const PAUSE_SWAPS: u8 = 1 << 0;
+pub const PAUSE_DEPOSITS: u8 = 1 << 1;
+pub const PAUSE_WITHDRAWALS: u8 = 1 << 2;
+/// Pauses everything but withdrawals, which cannot be paused at the same time
+pub const EMERGENCY_MODE: u8 = 1 << 3;
+
+pub const LP_DECIMALS: u8 = 6;
+/// LP supply locked by the first deposit, never minted to anyone
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+pub const MAX_FEE: u16 = 10_000;
+/// Largest swap fee a pool can charge, in bps of MAX_FEE
+pub const MAX_SWAP_FEE: u16 = 1_000;
+pub const MAX_FEE_TIERS: usize = 16;
+/// Scale of the reward per staked LP token accumulated by farms
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+/// Scale of the proceeds per unit of sale rate accumulated by long-term orders
+pub const PROCEEDS_PRECISION: u128 = 1_000_000_000_000;
+
+/// Slots between two long-term order expiries, orders ending on a multiple
+pub const TWAMM_INTERVAL: u64 = 9_000;
+/// Expiries a TWAMM tracks at once, bounding orders to about a week
+pub const TWAMM_BUCKETS: usize = 168;
+
+/// Slots after its reveal window a batch round can still be settled, its
+/// revealed swaps being refunded by ClaimBatch once they are over
+pub const BATCH_TIMEOUT_SLOTS: u64 = 1_500;
+
+pub const CURVE_CONSTANT_PRODUCT: u8 = 0;
+pub const CURVE_STABLE: u8 = 1;
+/// Weighted product curve, its weights moving along a schedule for liquidity
+/// bootstrapping
+pub const CURVE_WEIGHTED: u8 = 2;
+pub const MAX_AMP: u64 = 1_000_000;
+/// Sum of the weights of X and Y in a weighted pool
+pub const WEIGHT_SCALE: u16 = 10_000;
+
+pub const FLASH_LOAN_NONE: u8 = 0;
+pub const FLASH_LOAN_X: u8 = 1;
+pub const FLASH_LOAN_Y: u8 = 2;
+
+pub const INSTRUCTIONS_SYSVAR_ID: [u8; 32] =
+    five8_const::decode_32_const("Sysvar1nstructions1111111111111111111111111");
+
+pub const TOKEN_2022_PROGRAM_ID: [u8; 32] =
+    five8_const::decode_32_const("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Wrapped SOL mint of the Token program
+pub const NATIVE_MINT: [u8; 32] =
+    five8_const::decode_32_const("So11111111111111111111111111111111111111112");
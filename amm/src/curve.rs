@@ -0,0 +1,418 @@
+//! Dispatches the pool math on the curve type stored in the config.
+//!
+//! Quotes are expressed from the swapper side, `reserve_in` being the vault
+//! receiving tokens and `reserve_out` the one paying them out. Deposits after
+//! the first and withdrawals are proportional to the reserves on every curve,
+//! rounded in favour of the pool.
+//! Weighted pools quote with the weights of their schedule at `now`, `in_x`
+//! telling which one belongs to `reserve_in`.
+
+use constant_product_curve::{stable, weighted};
+use pinocchio::program_error::ProgramError;
+
+use crate::{
+    constants::{CURVE_STABLE, CURVE_WEIGHTED, MAX_FEE, WEIGHT_SCALE},
+    error::AmmError,
+    oracle,
+    state::{Config, PriceFeed},
+};
+
+/// Amount out and fee, paid in the out token, for swapping `amount_in`
+pub fn swap_amount_out(
+    config: &Config,
+    in_x: bool,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    now: i64,
+) -> Result<(u64, u64), ProgramError> {
+    match config.curve() {
+        CURVE_WEIGHTED => {
+            let (weight_in, weight_out) = weights(config, in_x, now);
+            weighted::delta_y_from_x_swap_amount_with_fee(
+                reserve_in,
+                reserve_out,
+                amount_in,
+                weight_in,
+                weight_out,
+                config.fee(),
+            )
+        }
+        CURVE_STABLE => stable::delta_y_from_x_swap_amount_with_fee(
+            reserve_in,
+            reserve_out,
+            amount_in,
+            config.amp(),
+            config.fee(),
+        ),
+        _ => constant_product_curve::delta_y_from_x_swap_amount_with_fee(
+            reserve_in,
+            reserve_out,
+            amount_in,
+            config.fee(),
+        ),
+    }
+    .map_err(|_| AmmError::CurveOverflow.into())
+}
+
+/// Amount in and fee, paid in the out token, for receiving `amount_out`
+pub fn swap_amount_in(
+    config: &Config,
+    in_x: bool,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    now: i64,
+) -> Result<(u64, u64), ProgramError> {
+    match config.curve() {
+        CURVE_WEIGHTED => {
+            let (weight_in, weight_out) = weights(config, in_x, now);
+            weighted::x_swap_amount_from_delta_y_with_fee(
+                reserve_in,
+                reserve_out,
+                amount_out,
+                weight_in,
+                weight_out,
+                config.fee(),
+            )
+        }
+        CURVE_STABLE => stable::x_swap_amount_from_delta_y_with_fee(
+            reserve_in,
+            reserve_out,
+            amount_out,
+            config.amp(),
+            config.fee(),
+        ),
+        _ => constant_product_curve::x_swap_amount_from_delta_y_with_fee(
+            reserve_in,
+            reserve_out,
+            amount_out,
+            config.fee(),
+        ),
+    }
+    .map_err(|_| AmmError::CurveOverflow.into())
+}
+
+/// Part of `amount_in` to swap so that the rest and the swap output are in the
+/// ratio of the reserves after the swap. Constant product pools solve it in
+/// closed form, the other curves search the smallest amount for which
+/// (amount_in - swapped) / (reserve_in + swapped) <= out / (reserve_out - out)
+pub fn single_deposit_swap_amount(
+    config: &Config,
+    in_x: bool,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    now: i64,
+) -> Result<u64, ProgramError> {
+    if config.curve() != CURVE_STABLE && config.curve() != CURVE_WEIGHTED {
+        return constant_product_curve::x_swap_amount_from_single_deposit(
+            reserve_in,
+            amount_in,
+            config.fee(),
+        )
+        .map_err(|_| AmmError::CurveOverflow.into());
+    }
+
+    let (mut low, mut high) = (0, amount_in);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let (amount_out, _) = swap_amount_out(config, in_x, reserve_in, reserve_out, mid, now)?;
+        if (amount_in - mid) as u128 * (reserve_out - amount_out) as u128
+            <= amount_out as u128 * (reserve_in as u128 + mid as u128)
+        {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Ok(low)
+}
+
+/// Weights of the in and out tokens at `now`
+fn weights(config: &Config, in_x: bool, now: i64) -> (u64, u64) {
+    let weight_x = config.weight_x(now) as u64;
+    let weight_y = WEIGHT_SCALE as u64 - weight_x;
+    if in_x {
+        (weight_x, weight_y)
+    } else {
+        (weight_y, weight_x)
+    }
+}
+
+/// LP supply minted by the first deposit of `x` and `y`, weighted pools
+/// minting the constant product one as only its scale matters
+pub fn initial_liquidity(config: &Config, x: u64, y: u64) -> Result<u64, ProgramError> {
+    match config.curve() {
+        CURVE_STABLE => stable::l_from_xy(x, y, config.amp()),
+        _ => constant_product_curve::l_from_xy(x, y),
+    }
+    .map_err(|_| AmmError::CurveOverflow.into())
+}
+
+/// Q64.64 spot prices of X in Y and of Y in X at the given reserves. They are
+/// the marginal prices of the curve, which only match the ratio of the
+/// reserves on constant product pools.
+pub fn spot_prices(
+    config: &Config,
+    reserve_x: u64,
+    reserve_y: u64,
+    now: i64,
+) -> Result<(u128, u128), ProgramError> {
+    let (numerator, denominator) = match config.curve() {
+        // Y·Wx / (X·Wy)
+        CURVE_WEIGHTED => {
+            let weight_x = config.weight_x(now) as u128;
+            let weight_y = WEIGHT_SCALE as u128 - weight_x;
+            (reserve_y as u128 * weight_x, reserve_x as u128 * weight_y)
+        }
+        CURVE_STABLE if reserve_x != 0 && reserve_y != 0 => {
+            stable_price(reserve_x, reserve_y, config.amp())?
+        }
+        _ => (reserve_y as u128, reserve_x as u128),
+    };
+    Ok((
+        oracle::ratio_q64(numerator, denominator),
+        oracle::ratio_q64(denominator, numerator),
+    ))
+}
+
+/// Marginal price of X in Y of the StableSwap invariant as a ratio, the
+/// quotient of its partial derivatives in X and Y
+/// (Ann·X·Y + D³ / 4X) / (Ann·X·Y + D³ / 4Y)
+/// The reserves and D are scaled down to 40 bits first so that D³ fits.
+fn stable_price(reserve_x: u64, reserve_y: u64, amp: u64) -> Result<(u128, u128), ProgramError> {
+    let d = stable::d_from_xy(reserve_x, reserve_y, amp).map_err(|_| AmmError::CurveOverflow)?;
+    let shift = (128 - d.max(reserve_x.max(reserve_y) as u128).leading_zeros()).saturating_sub(40);
+    let (x, y, d) = (
+        (reserve_x as u128 >> shift).max(1),
+        (reserve_y as u128 >> shift).max(1),
+        d >> shift,
+    );
+    let ann_xy = 4 * amp as u128 * x * y;
+    let d_cubed = d * d * d;
+    Ok((ann_xy + d_cubed / (4 * x), ann_xy + d_cubed / (4 * y)))
+}
+
+/// Amounts of X and Y a deposit minting `lp` out of `supply` brings, rounded
+/// up. Deposits costing nothing on either side are refused, as they would
+/// mint LP for free.
+pub fn deposit_amounts(
+    reserve_x: u64,
+    reserve_y: u64,
+    supply: u64,
+    lp: u64,
+) -> Result<(u64, u64), ProgramError> {
+    let (x, y) = (
+        share(reserve_x, lp, supply, true)?,
+        share(reserve_y, lp, supply, true)?,
+    );
+    if x == 0 || y == 0 {
+        return Err(AmmError::SlippageExceeded.into());
+    }
+    Ok((x, y))
+}
+
+/// Amounts of X and Y paid out for burning `lp` out of `supply`, rounded down
+pub fn withdraw_amounts(
+    reserve_x: u64,
+    reserve_y: u64,
+    supply: u64,
+    lp: u64,
+) -> Result<(u64, u64), ProgramError> {
+    if lp > supply {
+        return Err(AmmError::InsufficientLiquidity.into());
+    }
+    Ok((
+        share(reserve_x, lp, supply, false)?,
+        share(reserve_y, lp, supply, false)?,
+    ))
+}
+
+/// `reserve` · `lp` / `supply`, rounded up or down
+fn share(reserve: u64, lp: u64, supply: u64, round_up: bool) -> Result<u64, ProgramError> {
+    if supply == 0 {
+        return Err(AmmError::InsufficientLiquidity.into());
+    }
+    let product = reserve as u128 * lp as u128;
+    let share = if round_up {
+        product.div_ceil(supply as u128)
+    } else {
+        product / supply as u128
+    };
+    u64::try_from(share).map_err(|_| AmmError::CurveOverflow.into())
+}
+
+/// Checks the spot price of the curve at the reserves of the config did not
+/// move by more than its MaxPriceImpact from the one at the reserves before
+/// the swap. The price is taken in the token with the larger reserve.
+pub fn check_price_impact(
+    config: &Config,
+    reserve_x: u64,
+    reserve_y: u64,
+    now: i64,
+) -> Result<(), ProgramError> {
+    let max_price_impact = config.max_price_impact();
+    let (after_x, after_y) = (config.reserve_x(), config.reserve_y());
+    if max_price_impact == 0 || reserve_x == 0 || reserve_y == 0 || after_x == 0 || after_y == 0 {
+        return Ok(());
+    }
+
+    let before = spot_prices(config, reserve_x, reserve_y, now)?;
+    let after = spot_prices(config, after_x, after_y, now)?;
+    let (before, after) = if reserve_x >= reserve_y {
+        (before.1, after.1)
+    } else {
+        (before.0, after.0)
+    };
+
+    let impact = before.abs_diff(after).saturating_mul(MAX_FEE as u128) / before.max(1);
+    if impact > max_price_impact as u128 {
+        return Err(AmmError::PriceImpactExceeded.into());
+    }
+
+    Ok(())
+}
+
+/// Checks the spot price of the curve at the reserves of the config is within
+/// the MaxOracleDeviation of the price feed. A swap leaving it further only goes
+/// through if it moves the price closer to the feed than the reserves before
+/// the swap had it, so that a pool drifting away can be brought back. The
+/// feed is not older than MaxOracleStaleness slots and its confidence is
+/// within the deviation allowed.
+pub fn check_oracle_price(
+    config: &Config,
+    price_feed: &PriceFeed,
+    reserve_x: u64,
+    reserve_y: u64,
+    slot: u64,
+    now: i64,
+) -> Result<(), ProgramError> {
+    if slot.saturating_sub(price_feed.publish_slot()) > config.max_oracle_staleness() {
+        return Err(AmmError::StaleOracle.into());
+    }
+    let exponent = price_feed.exponent();
+    let price = u64::try_from(price_feed.price())
+        .ok()
+        .filter(|price| *price > 0)
+        .and_then(|price| oracle::scale_q64(price, exponent))
+        .ok_or(AmmError::InvalidOracle)?;
+    let band = oracle::bps_of(price, config.max_oracle_deviation());
+    let confidence =
+        oracle::scale_q64(price_feed.confidence(), exponent).ok_or(AmmError::InvalidOracle)?;
+    if confidence > band {
+        return Err(AmmError::InvalidOracle.into());
+    }
+
+    let (after_x, after_y) = (config.reserve_x(), config.reserve_y());
+    if after_x == 0 || after_y == 0 {
+        return Ok(());
+    }
+    let after = spot_prices(config, after_x, after_y, now)?
+        .0
+        .abs_diff(price);
+    let before = match reserve_x == 0 || reserve_y == 0 {
+        true => u128::MAX,
+        false => spot_prices(config, reserve_x, reserve_y, now)?
+            .0
+            .abs_diff(price),
+    };
+    if after > band && after >= before {
+        return Err(AmmError::OracleDeviation.into());
+    }
+
+    Ok(())
+}
+
+/// Net swap clearing a batch selling `total_x` of X and `total_y` of Y at a
+/// single price. Returns whether X goes in, the amount in, the amount out and
+/// the fee.
+///
+/// The side in excess at the spot price swaps its imbalance through the
+/// curve and the opposite orders cross at the average price of that swap:
+/// the net amount in is the smallest `d` for which
+/// out(d) · (total_in - d) <= total_other · d, the other side then buying
+/// the remaining total_in - d at a price no better than out(d) / d. The
+/// condition has a single crossing past the amounts the curve rounds to zero,
+/// which the search, starting from the whole imbalance, does not probe first.
+pub fn batch_swap(
+    config: &Config,
+    total_x: u64,
+    total_y: u64,
+    now: i64,
+) -> Result<(bool, u64, u64, u64), ProgramError> {
+    let (reserve_x, reserve_y) = (config.reserve_x(), config.reserve_y());
+    let in_x = total_x as u128 * reserve_y as u128 >= total_y as u128 * reserve_x as u128;
+    let (total_in, total_other, reserve_in, reserve_out) = if in_x {
+        (total_x, total_y, reserve_x, reserve_y)
+    } else {
+        (total_y, total_x, reserve_y, reserve_x)
+    };
+    if total_in == 0 {
+        return Ok((in_x, 0, 0, 0));
+    }
+
+    let (mut low, mut high) = (1, total_in);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let (amount_out, _) = swap_amount_out(config, in_x, reserve_in, reserve_out, mid, now)?;
+        if amount_out as u128 * (total_in - mid) as u128 <= total_other as u128 * mid as u128 {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    let (amount_out, fee) = swap_amount_out(config, in_x, reserve_in, reserve_out, low, now)?;
+    Ok((in_x, low, amount_out, fee))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::curve::{deposit_amounts, stable_price, withdraw_amounts};
+    use crate::oracle::ratio_q64;
+
+    #[test]
+    fn stable_marginal_price() {
+        // Balanced pools trade at par
+        let (numerator, denominator) = stable_price(1_000_000, 1_000_000, 100).unwrap();
+        assert_eq!(ratio_q64(numerator, denominator), 1 << 64);
+
+        // With twice as much Y, X trades close to par where constant product
+        // would price it at 2 Y
+        let (numerator, denominator) =
+            stable_price(1_000_000_000_000, 2_000_000_000_000, 100).unwrap();
+        let price = ratio_q64(numerator, denominator);
+        assert!(price > 1 << 64 && price < (11 << 64) / 10);
+
+        // Swapping the reserves inverts the price
+        let (inverse_numerator, inverse_denominator) =
+            stable_price(2_000_000_000_000, 1_000_000_000_000, 100).unwrap();
+        assert_eq!(
+            (inverse_numerator, inverse_denominator),
+            (denominator, numerator)
+        );
+    }
+
+    #[test]
+    fn liquidity_rounds_in_favour_of_the_pool() {
+        // 999 LP out of 1e12 is worth 999 of each reserve
+        assert_eq!(
+            deposit_amounts(1_000_000_000_000, 1_000_000_000_000, 1_000_000_000_000, 999),
+            Ok((999, 999))
+        );
+        assert_eq!(
+            withdraw_amounts(1_000_000_000_000, 1_000_000_000_000, 1_000_000_000_000, 999),
+            Ok((999, 999))
+        );
+
+        // A third of an LP costs a whole token and pays nothing out
+        assert_eq!(deposit_amounts(1_000, 3_000, 3_000, 1), Ok((1, 1)));
+        assert_eq!(withdraw_amounts(1_000, 3_000, 3_000, 1), Ok((0, 1)));
+    }
+
+    #[test]
+    fn free_deposits_and_overdrawn_withdrawals_are_refused() {
+        assert!(deposit_amounts(0, 3_000, 3_000, 1).is_err());
+        assert!(withdraw_amounts(1_000, 1_000, 1_000, 1_001).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use pinocchio::program_error::ProgramError;
+
+/// # Errors
+///
+/// Every failure path of the AMM returns one of these as `ProgramError::Custom(code)`,
+/// where `code` is the position of the variant in the enum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmmError {
+    /// The instruction is paused on the pool
+    Locked,
+    /// The instruction was submitted after its expiration timestamp
+    Expired,
+    /// The resulting amount is worse than the user bound
+    SlippageExceeded,
+    /// A vault account does not match the one stored in the config
+    InvalidVault,
+    /// A mint account does not match the one stored in the config
+    InvalidMint,
+    /// The signer is not the authority of the pool
+    InvalidAuthority,
+    /// The curve math overflowed
+    CurveOverflow,
+    /// The config account has the wrong size or owner
+    InvalidConfig,
+    /// The fee is above the maximum allowed
+    InvalidFee,
+    /// The observations account has the wrong size, owner or pool
+    InvalidObservations,
+    /// A flash loan is outstanding on the pool
+    FlashLoanActive,
+    /// No FlashRepay for the pool follows the FlashLoan in the transaction
+    FlashLoanNotRepaid,
+    /// FlashRepay was called without an outstanding flash loan
+    NoFlashLoan,
+    /// The curve type is unknown or its amplification is out of bounds
+    InvalidCurve,
+    /// The first deposit does not mint more than MINIMUM_LIQUIDITY
+    InsufficientLiquidity,
+    /// The status has unknown flags or pauses withdrawals in emergency mode
+    InvalidStatus,
+    /// The token program is not the owner of the mint
+    InvalidTokenProgram,
+    /// The factory account has the wrong address, size or owner
+    InvalidFactory,
+    /// The fee is not a tier whitelisted by the factory, or no slot is left for it
+    InvalidFeeTier,
+    /// The registry address does not match the sorted mints and fee tier, or
+    /// the registry is not the one of the pool
+    InvalidRegistry,
+    /// The farm account has the wrong address, size, owner or pool
+    InvalidFarm,
+    /// The stake account has the wrong address, size, owner or farm
+    InvalidStake,
+    /// The pool still has LP tokens, reserves or fees
+    PoolNotEmpty,
+    /// The swap moves the spot price by more than MaxPriceImpact
+    PriceImpactExceeded,
+    /// The TWAMM account has the wrong address, size, owner or pool, or is missing
+    InvalidTwamm,
+    /// The long-term order has the wrong address, size, owner or TWAMM, or
+    /// its expiry cannot be tracked
+    InvalidOrder,
+    /// The pool has an allowlist and the user is not proven to be on it
+    NotAllowlisted,
+    /// The account unwrapping native SOL is not at its derived address
+    InvalidNativeAccount,
+    /// The batch round has the wrong address, size, owner or pool, or is not
+    /// in the window the operation needs
+    InvalidBatch,
+    /// The swap intent has the wrong address, size, owner or round, or does
+    /// not match its commitment
+    InvalidIntent,
+    /// The pool swaps in batch rounds only
+    BatchOnly,
+    /// The price feed is missing, too short, not positive, out of scale or
+    /// less precise than the deviation allowed
+    InvalidOracle,
+    /// The price feed was published too many slots ago
+    StaleOracle,
+    /// The swap leaves the spot price too far from the price feed
+    OracleDeviation,
+    /// The protocol fees must be collected before the authority is renounced
+    ProtocolFeesNotCollected,
+    /// The farm still emits rewards or has LP tokens staked
+    FarmActive,
+}
+
+impl From<AmmError> for ProgramError {
+    fn from(error: AmmError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
@@ -0,0 +1,88 @@
+//! Binary events logged with `sol_log_data` for indexers, decoded off-chain
+//! by the `amm-events` crate.
+//!
+//! Every event is logged as a single data field: its discriminator, the pool
+//! config and the user, followed by the fields of the event, integers being
+//! little endian. Amounts are the ones moving the reserves, net of transfer
+//! fees on the way in and before them on the way out.
+
+use pinocchio::{log::sol_log_data, pubkey::Pubkey};
+
+pub const SWAP_EVENT: u8 = 0;
+pub const DEPOSIT_EVENT: u8 = 1;
+pub const WITHDRAW_EVENT: u8 = 2;
+
+/// -- SwapEvent --
+/// > Pool: Pubkey
+/// > User: Pubkey
+/// > InX: bool
+/// > AmountIn: u64
+/// > AmountOut: u64
+/// > Fee: u64
+/// > ReserveX: u64
+/// > ReserveY: u64
+pub struct SwapEvent<'a> {
+    pub pool: &'a Pubkey,
+    pub user: &'a Pubkey,
+    pub in_x: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+impl SwapEvent<'_> {
+    pub const LEN: usize = 1 + 32 + 32 + 1 + 8 * 5;
+
+    pub fn emit(&self) {
+        let mut data = [0u8; Self::LEN];
+        data[0] = SWAP_EVENT;
+        data[1..33].copy_from_slice(self.pool);
+        data[33..65].copy_from_slice(self.user);
+        data[65] = self.in_x as u8;
+        data[66..74].copy_from_slice(&self.amount_in.to_le_bytes());
+        data[74..82].copy_from_slice(&self.amount_out.to_le_bytes());
+        data[82..90].copy_from_slice(&self.fee.to_le_bytes());
+        data[90..98].copy_from_slice(&self.reserve_x.to_le_bytes());
+        data[98..106].copy_from_slice(&self.reserve_y.to_le_bytes());
+        sol_log_data(&[&data]);
+    }
+}
+
+/// -- LiquidityEvent --
+/// > Pool: Pubkey
+/// > User: Pubkey
+/// > AmountX: u64
+/// > AmountY: u64
+/// > Lp: u64
+/// > ReserveX: u64
+/// > ReserveY: u64
+///
+/// Logged by deposits under DEPOSIT_EVENT and withdrawals under WITHDRAW_EVENT.
+pub struct LiquidityEvent<'a> {
+    pub pool: &'a Pubkey,
+    pub user: &'a Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+impl LiquidityEvent<'_> {
+    pub const LEN: usize = 1 + 32 + 32 + 8 * 5;
+
+    pub fn emit(&self, discriminator: u8) {
+        let mut data = [0u8; Self::LEN];
+        data[0] = discriminator;
+        data[1..33].copy_from_slice(self.pool);
+        data[33..65].copy_from_slice(self.user);
+        data[65..73].copy_from_slice(&self.amount_x.to_le_bytes());
+        data[73..81].copy_from_slice(&self.amount_y.to_le_bytes());
+        data[81..89].copy_from_slice(&self.lp.to_le_bytes());
+        data[89..97].copy_from_slice(&self.reserve_x.to_le_bytes());
+        data[97..105].copy_from_slice(&self.reserve_y.to_le_bytes());
+        sol_log_data(&[&data]);
+    }
+}
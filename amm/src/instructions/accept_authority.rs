@@ -0,0 +1,37 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{error::AmmError, state::Config};
+
+/// # AcceptAuthority
+///
+/// -- Instruction Logic --
+/// Makes the pending authority the update authority and clears it.
+///
+/// -- Client Side Logic --
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The pending authority signs.
+pub fn accept_authority(accounts: &[AccountInfo]) -> ProgramResult {
+    let [pending_authority, config] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !pending_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if pending_authority.key() != &config_account.pending_authority() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+
+    config_account.set_update_authority(pending_authority.key());
+    config_account.set_pending_authority(&Pubkey::default());
+
+    Ok(())
+}
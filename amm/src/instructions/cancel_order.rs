@@ -0,0 +1,100 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::AmmError,
+    state::{Config, Order, Twamm},
+    token::{self, TransferChecked},
+};
+
+/// # CancelOrder
+///
+/// -- Instruction Logic --
+/// Executes the TWAMM, stops the order and closes it, paying the Y it earned
+/// since its last claim and returning the X it has not sold yet. The rent of
+/// the order goes back to the user. Transfer fees are paid out of the amounts.
+///
+/// -- Client Side Logic --
+/// Accounts are [user, authority, config, twamm, order, user_x, user_y,
+/// vault_x, vault_y, mint_x, mint_y, token_program_x, token_program_y].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The user signs and owns the order, which belongs to the TWAMM of the pool.
+/// Never paused, like Withdraw in emergency mode.
+pub fn cancel_order(accounts: &[AccountInfo]) -> ProgramResult {
+    let [user, authority, config, twamm, order, user_x, user_y, vault_x, vault_y, mint_x, mint_y, token_program_x, token_program_y] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if twamm.key() != &config_account.twamm() {
+        return Err(AmmError::InvalidTwamm.into());
+    }
+    let twamm_account = Twamm::from_account_info(twamm)?;
+    let order_account = Order::from_account_info(order)?;
+    if order_account.twamm() != *twamm.key() || order_account.owner() != *user.key() {
+        return Err(AmmError::InvalidOrder.into());
+    }
+    if vault_x.key() != &config_account.vault_x() || vault_y.key() != &config_account.vault_y() {
+        return Err(AmmError::InvalidVault.into());
+    }
+    token::check_mint(mint_x, &config_account.mint_x(), token_program_x)?;
+    token::check_mint(mint_y, &config_account.mint_y(), token_program_y)?;
+
+    // Settle the proceeds earned so far, the rest of the X is not sold
+    let clock = Clock::get()?;
+    twamm_account.execute(&config_account, clock.slot, clock.unix_timestamp)?;
+    let proceeds = order_account.settle(&twamm_account)?;
+    let (sale_rate, end_slot) = (order_account.sale_rate(), order_account.end_slot());
+    let unsold = sale_rate * end_slot.saturating_sub(twamm_account.last_slot());
+    twamm_account.remove_order(end_slot, sale_rate);
+
+    // Close Order
+    unsafe {
+        *user.borrow_mut_lamports_unchecked() += order.lamports();
+        *order.borrow_mut_lamports_unchecked() = 0;
+    }
+    order.realloc(0, false)?;
+    order.assign(&pinocchio_system::ID);
+
+    // Derive the signer
+    let binding = [config_account.authority_bump()];
+    let seeds = [Seed::from(config.key().as_ref()), Seed::from(&binding)];
+    let signer = [Signer::from(&seeds)];
+
+    // Pay Proceeds and Return the unsold X
+    for (amount, vault, mint, user_token, token_program) in [
+        (proceeds, vault_y, mint_y, user_y, token_program_y),
+        (unsold, vault_x, mint_x, user_x, token_program_x),
+    ] {
+        if amount == 0 {
+            continue;
+        }
+        TransferChecked {
+            from: vault,
+            mint,
+            to: user_token,
+            authority,
+            token_program,
+            amount,
+            decimals: token::decimals(mint)?,
+        }
+        .invoke_signed(&signer)?;
+    }
+
+    Ok(())
+}
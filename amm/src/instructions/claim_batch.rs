@@ -0,0 +1,148 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::AmmError,
+    state::{Config, Intent, Round},
+    token::{self, TransferChecked},
+};
+
+/// # ClaimBatch
+///
+/// -- Instruction Logic --
+/// Pays the share of the settled round owed to a revealed intent out of
+/// vault_to, in the token it bought, and closes the intent, its rent going
+/// back to the user. A revealed intent of a round that timed out unsettled
+/// is refunded the amount it sold instead. Intents never revealed are closed
+/// once the reveal window is over without paying anything. Transfer fees are
+/// paid out of the share. Along with its last intent the round is closed,
+/// its rent going back to its opener and the rounding dust it still holds
+/// to the reserves.
+///
+/// -- Client Side Logic --
+/// Accounts are [user, authority, config, round, opener, intent, user_to,
+/// vault_to, mint_to, token_program_to], the token accounts being the ones
+/// of the token bought, of the token sold for a refund, and ignored for an
+/// unrevealed intent.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The user signs and owns the intent, which belongs to the round of the
+/// pool, and opener is the opener of the round. A revealed intent needs the
+/// round settled or timed out, an unrevealed one its reveal window over.
+/// Never paused, like Withdraw in emergency mode.
+pub fn claim_batch(accounts: &[AccountInfo]) -> ProgramResult {
+    let [user, authority, config, round, opener, intent, user_to, vault_to, mint_to, token_program_to] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    let round_account = Round::from_account_info(round)?;
+    if round_account.config() != *config.key() || opener.key() != &round_account.opener() {
+        return Err(AmmError::InvalidBatch.into());
+    }
+    let intent_account = Intent::from_account_info(intent)?;
+    if intent_account.round() != *round.key() || intent_account.owner() != *user.key() {
+        return Err(AmmError::InvalidIntent.into());
+    }
+
+    let (in_x, amount) = (intent_account.in_x(), intent_account.amount());
+    let revealed = amount != 0;
+    let slot = Clock::get()?.slot;
+    let refunded = revealed && round_account.timed_out(slot);
+    if revealed && !round_account.settled() && !refunded
+        || !revealed && slot < round_account.reveal_end()
+    {
+        return Err(AmmError::InvalidBatch.into());
+    }
+
+    // Settled swaps are paid in the token bought, refunds in the token sold
+    let paid_x = if refunded { in_x } else { !in_x };
+    let payout = match revealed {
+        true => {
+            let (vault, mint) = if paid_x {
+                (config_account.vault_x(), config_account.mint_x())
+            } else {
+                (config_account.vault_y(), config_account.mint_y())
+            };
+            if vault_to.key() != &vault {
+                return Err(AmmError::InvalidVault.into());
+            }
+            token::check_mint(mint_to, &mint, token_program_to)?;
+            match refunded {
+                true => amount,
+                false => round_account.payout(in_x, amount),
+            }
+        }
+        false => 0,
+    };
+    let intents = round_account.close_intent(paid_x, payout)?;
+    if paid_x {
+        config_account.release_batch(payout, 0)?;
+    } else {
+        config_account.release_batch(0, payout)?;
+    }
+
+    // Close Intent
+    unsafe {
+        *user.borrow_mut_lamports_unchecked() += intent.lamports();
+        *intent.borrow_mut_lamports_unchecked() = 0;
+    }
+    intent.realloc(0, false)?;
+    intent.assign(&pinocchio_system::ID);
+
+    // Close Round, the dust of the payouts going to the LPs
+    if intents == 0 {
+        let (left_x, left_y) = (round_account.left_x(), round_account.left_y());
+        config_account.release_batch(left_x, left_y)?;
+        let reserve_x = config_account.reserve_x().checked_add(left_x);
+        let reserve_y = config_account.reserve_y().checked_add(left_y);
+        let (Some(reserve_x), Some(reserve_y)) = (reserve_x, reserve_y) else {
+            return Err(ProgramError::ArithmeticOverflow);
+        };
+        config_account.set_reserves(reserve_x, reserve_y);
+
+        unsafe {
+            *opener.borrow_mut_lamports_unchecked() += round.lamports();
+            *round.borrow_mut_lamports_unchecked() = 0;
+        }
+        round.realloc(0, false)?;
+        round.assign(&pinocchio_system::ID);
+    }
+
+    if payout == 0 {
+        return Ok(());
+    }
+
+    // Derive the signer
+    let binding = [config_account.authority_bump()];
+    let seeds = [Seed::from(config.key().as_ref()), Seed::from(&binding)];
+    let signer = [Signer::from(&seeds)];
+
+    // Pay Share
+    TransferChecked {
+        from: vault_to,
+        mint: mint_to,
+        to: user_to,
+        authority,
+        token_program: token_program_to,
+        amount: payout,
+        decimals: token::decimals(mint_to)?,
+    }
+    .invoke_signed(&signer)?;
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::AmmError,
+    state::{Config, Order, Twamm},
+    token::{self, TransferChecked},
+};
+
+/// # ClaimOrder
+///
+/// -- Instruction Logic --
+/// Executes the TWAMM and pays the Y the order earned since its last claim
+/// out of vault_y. Once the order has sold everything it is closed and its
+/// rent returned to the user. Transfer fees are paid out of the proceeds.
+///
+/// -- Client Side Logic --
+/// Accounts are [user, authority, config, twamm, order, user_y, vault_y,
+/// mint_y, token_program_y].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The user signs and owns the order, which belongs to the TWAMM of the pool.
+/// Never paused, like Withdraw in emergency mode.
+pub fn claim_order(accounts: &[AccountInfo]) -> ProgramResult {
+    let [user, authority, config, twamm, order, user_y, vault_y, mint_y, token_program_y] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if twamm.key() != &config_account.twamm() {
+        return Err(AmmError::InvalidTwamm.into());
+    }
+    let twamm_account = Twamm::from_account_info(twamm)?;
+    let order_account = Order::from_account_info(order)?;
+    if order_account.twamm() != *twamm.key() || order_account.owner() != *user.key() {
+        return Err(AmmError::InvalidOrder.into());
+    }
+    if vault_y.key() != &config_account.vault_y() {
+        return Err(AmmError::InvalidVault.into());
+    }
+    token::check_mint(mint_y, &config_account.mint_y(), token_program_y)?;
+
+    // Settle the proceeds earned so far
+    let clock = Clock::get()?;
+    twamm_account.execute(&config_account, clock.slot, clock.unix_timestamp)?;
+    let proceeds = order_account.settle(&twamm_account)?;
+
+    // Close the Order once it sold everything
+    if twamm_account.last_slot() >= order_account.end_slot() {
+        twamm_account.remove_order(order_account.end_slot(), order_account.sale_rate());
+        unsafe {
+            *user.borrow_mut_lamports_unchecked() += order.lamports();
+            *order.borrow_mut_lamports_unchecked() = 0;
+        }
+        order.realloc(0, false)?;
+        order.assign(&pinocchio_system::ID);
+    }
+
+    if proceeds == 0 {
+        return Ok(());
+    }
+
+    // Derive the signer
+    let binding = [config_account.authority_bump()];
+    let seeds = [Seed::from(config.key().as_ref()), Seed::from(&binding)];
+    let signer = [Signer::from(&seeds)];
+
+    // Pay Proceeds
+    TransferChecked {
+        from: vault_y,
+        mint: mint_y,
+        to: user_y,
+        authority,
+        token_program: token_program_y,
+        amount: proceeds,
+        decimals: token::decimals(mint_y)?,
+    }
+    .invoke_signed(&signer)?;
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::AmmError,
+    state::{Config, Farm, Stake},
+    token::{self, TransferChecked},
+};
+
+/// # ClaimRewards
+///
+/// -- Instruction Logic --
+/// Settles the rewards earned by the stake and pays every pending reward out
+/// of the reward vault. Transfer fees are paid out of the rewards.
+///
+/// -- Client Side Logic --
+/// Accounts are [user, authority, stake, farm, config, user_reward,
+/// reward_vault, reward_mint, reward_token_program].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The user signs and owns the stake and the farm belongs to the pool.
+pub fn claim_rewards(accounts: &[AccountInfo]) -> ProgramResult {
+    let [user, authority, stake, farm, config, user_reward, reward_vault, reward_mint, reward_token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    let farm_account = Farm::from_account_info(farm)?;
+    if farm_account.config() != *config.key() {
+        return Err(AmmError::InvalidFarm.into());
+    }
+    if reward_vault.key() != &farm_account.reward_vault() {
+        return Err(AmmError::InvalidVault.into());
+    }
+    token::check_mint(
+        reward_mint,
+        &farm_account.reward_mint(),
+        reward_token_program,
+    )?;
+    let stake_account = Stake::from_account_info(stake)?;
+    if stake_account.farm() != *farm.key() || stake_account.owner() != *user.key() {
+        return Err(AmmError::InvalidStake.into());
+    }
+
+    // Settle the rewards earned so far
+    farm_account.update(Clock::get()?.unix_timestamp)?;
+    stake_account.settle(&farm_account)?;
+
+    let rewards = stake_account.pending_rewards();
+    stake_account.set_pending_rewards(0);
+    farm_account.set_owed_rewards(farm_account.owed_rewards() - rewards);
+
+    if rewards == 0 {
+        return Ok(());
+    }
+
+    // Derive the signer
+    let binding = [config_account.authority_bump()];
+    let seeds = [Seed::from(config.key().as_ref()), Seed::from(&binding)];
+    let signer = [Signer::from(&seeds)];
+
+    // Pay Rewards
+    TransferChecked {
+        from: reward_vault,
+        mint: reward_mint,
+        to: user_reward,
+        authority,
+        token_program: reward_token_program,
+        amount: rewards,
+        decimals: token::decimals(reward_mint)?,
+    }
+    .invoke_signed(&signer)?;
+
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    ProgramResult,
+};
+use pinocchio_token::state::Mint;
+
+use crate::{
+    constants::{FARM_SEED, FLASH_LOAN_NONE},
+    error::AmmError,
+    state::{Config, Farm, Registry, Twamm},
+    token::{self, CloseAccount, TransferChecked},
+};
+
+/// # ClosePool
+///
+/// -- Instruction Logic --
+/// Decommissions a pool with no LP token left: sweeps what the vaults still
+/// hold, the MINIMUM_LIQUIDITY locked by the first deposit and any donation,
+/// to token accounts of the update authority, closes both vaults through
+/// their token program, then the stake and reward vaults of the farm if
+/// any, the farm, the TWAMM if any, the registry and the Config, every rent
+/// going to the update authority. The LP mint cannot be closed by the Token
+/// program and is left behind with no supply and an authority that can no
+/// longer sign.
+///
+/// -- Client Side Logic --
+/// Accounts are [update_authority, authority, config, mint_lp, mint_x,
+/// mint_y, vault_x, vault_y, update_authority_x, update_authority_y,
+/// registry, farm, token_program_x, token_program_y], followed by the stake
+/// and reward vaults of the farm once it is initialized, with their token
+/// programs when not passed already, and by the TWAMM once the pool has one.
+/// Protocol fees are collected beforehand with CollectProtocolFees, the
+/// reward vault is swept with SweepRewards, and the fees withheld in
+/// Token-2022 vaults are harvested to their mint.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The update authority signs, no LP token is left, no flash loan is
+/// outstanding, the protocol fees are zero, the pool is not in batch mode and
+/// its batch rounds hold no revealed swap or unclaimed share. The mints and
+/// vaults are the ones of the pool and owned by their token program, and the
+/// registry points at the pool. The farm matches its derivation and both of
+/// its vaults are empty, the TWAMM has no open order.
+pub fn close_pool(accounts: &[AccountInfo]) -> ProgramResult {
+    let [update_authority, authority, config, mint_lp, mint_x, mint_y, vault_x, vault_y, update_authority_x, update_authority_y, registry, farm, token_program_x, token_program_y, remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !update_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if update_authority.key() != &config_account.update_authority() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+    if config_account.flash_loan() != FLASH_LOAN_NONE {
+        return Err(AmmError::FlashLoanActive.into());
+    }
+    if mint_lp.key() != &config_account.mint_lp() {
+        return Err(AmmError::InvalidMint.into());
+    }
+    token::check_mint(mint_x, &config_account.mint_x(), token_program_x)?;
+    token::check_mint(mint_y, &config_account.mint_y(), token_program_y)?;
+    if vault_x.key() != &config_account.vault_x() || vault_y.key() != &config_account.vault_y() {
+        return Err(AmmError::InvalidVault.into());
+    }
+    if vault_x.owner() != token_program_x.key() || vault_y.owner() != token_program_y.key() {
+        return Err(AmmError::InvalidTokenProgram.into());
+    }
+    if Registry::from_account_info(registry)?.config() != *config.key() {
+        return Err(AmmError::InvalidRegistry.into());
+    }
+
+    let supply = unsafe { Mint::from_account_info_unchecked(mint_lp)?.supply() };
+    if supply != 0
+        || config_account.protocol_fees_x() != 0
+        || config_account.protocol_fees_y() != 0
+        || config_account.is_batched()
+        || config_account.batch_x() != 0
+        || config_account.batch_y() != 0
+    {
+        return Err(AmmError::PoolNotEmpty.into());
+    }
+
+    let (farm_key, _) = find_program_address(&[FARM_SEED, config.key()], &crate::ID);
+    if farm.key() != &farm_key {
+        return Err(AmmError::InvalidFarm.into());
+    }
+    let mut farm_vaults = [None, None];
+    if farm.data_len() != 0 {
+        let farm_account = Farm::from_account_info(farm)?;
+        for (farm_vault, key) in farm_vaults
+            .iter_mut()
+            .zip([farm_account.stake_vault(), farm_account.reward_vault()])
+        {
+            let vault = remaining
+                .iter()
+                .find(|account| account.key() == &key)
+                .ok_or(AmmError::InvalidVault)?;
+            if token::amount(vault)? != 0 {
+                return Err(AmmError::PoolNotEmpty.into());
+            }
+            let token_program = accounts
+                .iter()
+                .find(|account| account.key() == vault.owner())
+                .ok_or(AmmError::InvalidTokenProgram)?;
+            *farm_vault = Some((vault, token_program));
+        }
+    }
+
+    let twamm = match Twamm::from_config(&config_account, remaining)? {
+        Some(twamm) if twamm.open_orders() != 0 => return Err(AmmError::PoolNotEmpty.into()),
+        Some(_) => remaining
+            .iter()
+            .find(|account| account.key() == &config_account.twamm()),
+        None => None,
+    };
+
+    // Derive the signer
+    let binding = [config_account.authority_bump()];
+    let seeds = [Seed::from(config.key().as_ref()), Seed::from(&binding)];
+    let signer = [Signer::from(&seeds)];
+
+    // Sweep and close Vaults
+    for (vault, mint, destination, token_program) in [
+        (vault_x, mint_x, update_authority_x, token_program_x),
+        (vault_y, mint_y, update_authority_y, token_program_y),
+    ] {
+        let amount = token::amount(vault)?;
+        if amount != 0 {
+            TransferChecked {
+                from: vault,
+                mint,
+                to: destination,
+                authority,
+                token_program,
+                amount,
+                decimals: token::decimals(mint)?,
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        CloseAccount {
+            account: vault,
+            destination: update_authority,
+            authority,
+            token_program,
+        }
+        .invoke_signed(&signer)?;
+    }
+
+    // Close Farm Vaults
+    for (vault, token_program) in farm_vaults.into_iter().flatten() {
+        CloseAccount {
+            account: vault,
+            destination: update_authority,
+            authority,
+            token_program,
+        }
+        .invoke_signed(&signer)?;
+    }
+
+    // Close Farm, TWAMM, Registry and Config
+    let farm = (farm.data_len() != 0).then_some(farm);
+    for account in farm.into_iter().chain(twamm).chain([registry, config]) {
+        unsafe {
+            *update_authority.borrow_mut_lamports_unchecked() += account.lamports();
+            *account.borrow_mut_lamports_unchecked() = 0;
+        }
+        account.realloc(0, false)?;
+        account.assign(&pinocchio_system::ID);
+    }
+
+    Ok(())
+}
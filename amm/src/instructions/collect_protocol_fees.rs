@@ -0,0 +1,88 @@
+use crate::{
+    error::AmmError,
+    state::Config,
+    token::{self, TransferChecked},
+};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    ProgramResult,
+};
+
+/// # CollectProtocolFees
+///
+/// -- Instruction Logic --
+/// Sweeps the protocol fees accrued in both vaults to the treasury token
+/// accounts and resets the accrued amounts. Transfer fees are paid out of
+/// the swept amounts.
+///
+/// -- Client Side Logic --
+/// Accounts are [update_authority, authority, vault_x, vault_y, treasury_x,
+/// treasury_y, config, mint_x, mint_y, token_program_x, token_program_y].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The update authority signs and the vaults are the ones of the pool.
+pub fn collect_protocol_fees(accounts: &[AccountInfo]) -> ProgramResult {
+    let [update_authority, authority, vault_x, vault_y, treasury_x, treasury_y, config, mint_x, mint_y, token_program_x, token_program_y] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !update_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if update_authority.key() != &config_account.update_authority() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+    if vault_x.key() != &config_account.vault_x() || vault_y.key() != &config_account.vault_y() {
+        return Err(AmmError::InvalidVault.into());
+    }
+    token::check_mint(mint_x, &config_account.mint_x(), token_program_x)?;
+    token::check_mint(mint_y, &config_account.mint_y(), token_program_y)?;
+
+    let protocol_fees_x = config_account.protocol_fees_x();
+    let protocol_fees_y = config_account.protocol_fees_y();
+    config_account.set_protocol_fees_x(0);
+    config_account.set_protocol_fees_y(0);
+
+    // Derive the signer
+    let binding = [config_account.authority_bump()];
+    let seeds = [Seed::from(config.key().as_ref()), Seed::from(&binding)];
+    let signer = [Signer::from(&seeds)];
+
+    // Sweep Fees
+    if protocol_fees_x > 0 {
+        TransferChecked {
+            from: vault_x,
+            mint: mint_x,
+            to: treasury_x,
+            authority,
+            token_program: token_program_x,
+            amount: protocol_fees_x,
+            decimals: token::decimals(mint_x)?,
+        }
+        .invoke_signed(&signer)?;
+    }
+
+    if protocol_fees_y > 0 {
+        TransferChecked {
+            from: vault_y,
+            mint: mint_y,
+            to: treasury_y,
+            authority,
+            token_program: token_program_y,
+            amount: protocol_fees_y,
+            decimals: token::decimals(mint_y)?,
+        }
+        .invoke_signed(&signer)?;
+    }
+
+    Ok(())
+}
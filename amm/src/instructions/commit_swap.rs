@@ -0,0 +1,150 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    constants::{BATCH_SEED, INTENT_SEED, PAUSE_SWAPS},
+    error::AmmError,
+    state::{Config, Intent, Round},
+    system::CreatePda,
+};
+
+/// # CommitSwap
+///
+/// -- Data scheme --
+/// > Commitment: [u8; 32]
+/// > Proof: [[u8; 32]]
+///
+/// -- Instruction Logic --
+/// Commits a hidden swap to the current batch round of the pool and opens
+/// the intent PDA [INTENT_SEED, round, user]. Once the last round opened is
+/// over, the first commitment opens the next one at the round PDA
+/// [BATCH_SEED, config, index], index being one more than the BatchRound of
+/// the pool, with a commit window of CommitSlots from the current slot
+/// followed by a reveal window of RevealSlots. The commitment is
+/// `Intent::hash` of the swap, revealed with RevealSwap once the commit
+/// window is over. Allowlisted pools take the merkle proof of the user key
+/// after the commitment.
+///
+/// -- Client Side Logic --
+/// Accounts are [user, config, round, intent, system_program], round being
+/// the round BatchRound of the pool during its commit window and the next
+/// one after its reveal window. A user has one intent per round, the salt
+/// being kept secret until the reveal.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// Swaps are not paused and the pool is in batch mode. The last round opened
+/// is not in its reveal window. The round and intent addresses match their
+/// derivation and the intent is not open. The user is on the allowlist of
+/// the pool, if it has one.
+pub fn commit_swap(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [user, config, round, intent, _system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 32 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let commitment = unsafe { &*(data.as_ptr() as *const [u8; 32]) };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if config_account.is_paused(PAUSE_SWAPS) {
+        return Err(AmmError::Locked.into());
+    }
+    if !config_account.is_batched() {
+        return Err(AmmError::InvalidBatch.into());
+    }
+    config_account.check_allowlist(user.key(), &data[32..])?;
+
+    // Join the last round opened in its commit window, or open the next one
+    // once it is revealed
+    let slot = Clock::get()?.slot;
+    let open = slot >= config_account.batch_commit_end();
+    if open && slot < config_account.batch_reveal_end() {
+        return Err(AmmError::InvalidBatch.into());
+    }
+    let index = config_account.batch_round() + open as u64;
+
+    let index_bytes = index.to_le_bytes();
+    let (round_key, round_bump) =
+        find_program_address(&[BATCH_SEED, config.key(), &index_bytes], &crate::ID);
+    if round.key() != &round_key {
+        return Err(AmmError::InvalidBatch.into());
+    }
+    let (intent_key, intent_bump) =
+        find_program_address(&[INTENT_SEED, round.key(), user.key()], &crate::ID);
+    if intent.key() != &intent_key || intent.data_len() != 0 {
+        return Err(AmmError::InvalidIntent.into());
+    }
+
+    let rent = Rent::get()?;
+
+    // Create Round, the windows of a round staying the ones it opened with
+    if open {
+        let commit_end = slot
+            .checked_add(config_account.commit_slots())
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let reveal_end = commit_end
+            .checked_add(config_account.reveal_slots())
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        let binding = [round_bump];
+        let seeds = [
+            Seed::from(BATCH_SEED),
+            Seed::from(config.key()),
+            Seed::from(&index_bytes),
+            Seed::from(&binding),
+        ];
+        CreatePda {
+            from: user,
+            to: round,
+            lamports: rent.minimum_balance(Round::LEN),
+            space: Round::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&seeds)])?;
+
+        Round::from_account_info_unchecked(round).init(
+            config.key(),
+            user.key(),
+            commit_end,
+            reveal_end,
+        );
+        config_account.open_round(index, commit_end, reveal_end);
+    }
+    Round::from_account_info(round)?.add_intent()?;
+
+    // Create Intent
+    let binding = [intent_bump];
+    let seeds = [
+        Seed::from(INTENT_SEED),
+        Seed::from(round.key()),
+        Seed::from(user.key()),
+        Seed::from(&binding),
+    ];
+    CreatePda {
+        from: user,
+        to: intent,
+        lamports: rent.minimum_balance(Intent::LEN),
+        space: Intent::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&[Signer::from(&seeds)])?;
+
+    Intent::from_account_info_unchecked(intent).init(round.key(), user.key(), commitment);
+
+    Ok(())
+}
@@ -1,4 +1,3 @@
-use constant_product_curve::xy_deposit_amounts_from_l;
 use pinocchio::{
     account_info::AccountInfo,
     instruction::{Seed, Signer},
@@ -6,12 +5,16 @@ use pinocchio::{
     sysvars::{clock::Clock, Sysvar},
     ProgramResult,
 };
-use pinocchio_token::{
-    instructions::{MintTo, Transfer},
-    state::{Mint, TokenAccount},
-};
+use pinocchio_token::{instructions::MintTo, state::Mint};
 
-use crate::state::Config;
+use crate::{
+    constants::{FLASH_LOAN_NONE, MINIMUM_LIQUIDITY, PAUSE_DEPOSITS},
+    curve,
+    error::AmmError,
+    events::{LiquidityEvent, DEPOSIT_EVENT},
+    state::{Config, Twamm},
+    token::{self, TransferChecked, TransferFee},
+};
 
 /// # Deposit
 ///
@@ -20,72 +23,144 @@ use crate::state::Config;
 /// MaxX: u64
 /// MaxY: u64
 /// Expiration: i64
+/// Proof: [[u8; 32]]
 ///
 /// -- Instruction Logic --
+/// Executes the long-term orders of the pool, then mints Amount of LP for a
+/// proportional share of the reserves. The first deposit takes MaxX and MaxY
+/// as is and mints the curve liquidity minus MINIMUM_LIQUIDITY, Amount then
+/// being the minimum accepted. Reserves grow by the amounts received by the
+/// vaults, net of transfer fees. SOL is wrapped into the vault of the
+/// wrapped SOL mint when the user passes its wallet in place of its token
+/// account. Logs a LiquidityEvent under DEPOSIT_EVENT.
+/// Allowlisted pools take the merkle proof of the user key after the other
+/// fields.
 ///
 /// -- Client Side Logic --
+/// Accounts are [user, authority, mint_lp, user_x, user_y, user_lp, vault_x,
+/// vault_y, config, token_program, mint_x, mint_y, token_program_x,
+/// token_program_y], token_program being the one of the LP mint, followed by
+/// the system program when user_x or user_y is the user wallet and by the
+/// TWAMM of the pool once it has one.
 ///
 /// -- Account Optimization Logic --
 ///
 /// -- Checks --
-///
+/// MaxX and MaxY are checked against the amounts sent by the user, transfer
+/// fees included. The user is on the allowlist of the pool, if it has one.
 pub fn deposit(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
-    let [user, authority, mint_lp, user_x, user_y, user_lp, vault_x, vault_y, config, _token_program] =
+    let [user, authority, mint_lp, user_x, user_y, user_lp, vault_x, vault_y, config, _token_program, mint_x, mint_y, token_program_x, token_program_y, remaining @ ..] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
     // Deserialize Data
+    if data.len() < 32 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
     let (amount, max_x, max_y, expiration) = unsafe {
         let [amount, max_x, max_y, expiration] = *(data.as_ptr() as *const [u64; 4]);
         (amount, max_x, max_y, expiration as i64)
     };
 
     // Checks
-    let config_account = Config::from_account_info(config);
-    assert_ne!(config_account.get_status(), 1);
-    assert_eq!(mint_lp.key(), &config_account.mint_lp());
-    assert_eq!(vault_x.key(), &config_account.vault_x());
-    assert_eq!(vault_y.key(), &config_account.vault_y());
-    assert!(expiration < Clock::get()?.unix_timestamp);
+    let (native_x, native_y) = (
+        token::is_native(user, user_x, mint_x)?,
+        token::is_native(user, user_y, mint_y)?,
+    );
+    let config_account = Config::from_account_info(config)?;
+    if config_account.is_paused(PAUSE_DEPOSITS) {
+        return Err(AmmError::Locked.into());
+    }
+    if config_account.flash_loan() != FLASH_LOAN_NONE {
+        return Err(AmmError::FlashLoanActive.into());
+    }
+    if mint_lp.key() != &config_account.mint_lp() {
+        return Err(AmmError::InvalidMint.into());
+    }
+    if vault_x.key() != &config_account.vault_x() || vault_y.key() != &config_account.vault_y() {
+        return Err(AmmError::InvalidVault.into());
+    }
+    token::check_mint(mint_x, &config_account.mint_x(), token_program_x)?;
+    token::check_mint(mint_y, &config_account.mint_y(), token_program_y)?;
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    if now > expiration {
+        return Err(AmmError::Expired.into());
+    }
+    config_account.check_allowlist(user.key(), &data[32..])?;
+
+    // Execute the long-term orders up to now
+    if let Some(twamm) = Twamm::from_config(&config_account, remaining)? {
+        twamm.execute(&config_account, clock.slot, now)?;
+    }
 
     // Calculate the amount of LP tokens to mint and the amount of tokens to deposit
     let supply = unsafe { Mint::from_account_info_unchecked(mint_lp)?.supply() };
-    let vault_x_amount = unsafe { TokenAccount::from_account_info_unchecked(vault_x)?.amount() };
-    let vault_y_amount = unsafe { TokenAccount::from_account_info_unchecked(vault_y)?.amount() };
-
-    let (x, y) = match supply == 0 && vault_x_amount == 0 && vault_y_amount == 0 {
-        true => (max_x, max_y),
-        false => xy_deposit_amounts_from_l(
-            vault_x_amount,
-            vault_y_amount,
-            supply,
-            amount,
-            1_000_000_000,
-        )
-        .map_err(|_| ProgramError::ArithmeticOverflow)?,
+    let (reserve_x, reserve_y) = (config_account.reserve_x(), config_account.reserve_y());
+
+    // Accumulate the price before the reserves move
+    config_account.update_oracle(reserve_x, reserve_y, now)?;
+
+    let fee_x = TransferFee::from_account_info(mint_x, clock.epoch);
+    let fee_y = TransferFee::from_account_info(mint_y, clock.epoch);
+
+    // Amounts the vaults have to receive
+    let (x, y, lp) = match reserve_x == 0 && reserve_y == 0 {
+        // The first deposit sets the price and mints the LP supply from the
+        // curve, MINIMUM_LIQUIDITY of it being locked forever
+        true => {
+            let (x, y) = (fee_x.amount_received(max_x), fee_y.amount_received(max_y));
+            let lp = curve::initial_liquidity(&config_account, x, y)?
+                .checked_sub(MINIMUM_LIQUIDITY)
+                .filter(|lp| *lp > 0)
+                .ok_or(AmmError::InsufficientLiquidity)?;
+            (x, y, lp)
+        }
+        false => {
+            let (x, y) =
+                curve::deposit_amounts(reserve_x, reserve_y, supply + MINIMUM_LIQUIDITY, amount)?;
+            (x, y, amount)
+        }
     };
 
-    assert!(x <= max_x);
-    assert!(y <= max_y);
+    // Amounts the user has to send
+    let (sent_x, sent_y) = (fee_x.amount_to_send(x)?, fee_y.amount_to_send(y)?);
 
-    // Deposit Tokens
-    Transfer {
-        from: user_x,
-        to: vault_x,
-        authority: user,
-        amount: x,
+    if sent_x > max_x || sent_y > max_y || lp < amount {
+        return Err(AmmError::SlippageExceeded.into());
     }
-    .invoke()?;
 
-    Transfer {
-        from: user_y,
-        to: vault_y,
-        authority: user,
-        amount: y,
+    config_account.set_reserves(
+        reserve_x
+            .checked_add(x)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+        reserve_y
+            .checked_add(y)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+    );
+
+    // Deposit Tokens
+    for (native, user_token, mint, vault, token_program, amount) in [
+        (native_x, user_x, mint_x, vault_x, token_program_x, sent_x),
+        (native_y, user_y, mint_y, vault_y, token_program_y, sent_y),
+    ] {
+        if native {
+            token::wrap(user, vault, amount)?;
+            continue;
+        }
+        TransferChecked {
+            from: user_token,
+            mint,
+            to: vault,
+            authority: user,
+            token_program,
+            amount,
+            decimals: token::decimals(mint)?,
+        }
+        .invoke()?;
     }
-    .invoke()?;
 
     // Derive the signer
     let binding = [config_account.authority_bump()];
@@ -97,9 +172,20 @@ pub fn deposit(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
         mint: mint_lp,
         token: user_lp,
         mint_authority: authority,
-        amount,
+        amount: lp,
     }
     .invoke_signed(&signer)?;
 
+    LiquidityEvent {
+        pool: config.key(),
+        user: user.key(),
+        amount_x: x,
+        amount_y: y,
+        lp,
+        reserve_x: config_account.reserve_x(),
+        reserve_y: config_account.reserve_y(),
+    }
+    .emit(DEPOSIT_EVENT);
+
     Ok(())
 }
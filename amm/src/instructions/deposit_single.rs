@@ -0,0 +1,226 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::{instructions::MintTo, state::Mint};
+
+use crate::{
+    constants::{FLASH_LOAN_NONE, MINIMUM_LIQUIDITY, PAUSE_DEPOSITS, PAUSE_SWAPS},
+    curve,
+    error::AmmError,
+    events::{LiquidityEvent, DEPOSIT_EVENT},
+    state::{Config, PriceFeed, Twamm},
+    token::{self, TransferChecked, TransferFee},
+};
+
+/// # DepositSingle
+///
+/// -- Data scheme --
+/// Amount: u64
+/// MinLp: u64
+/// Expiration: i64
+/// Proof: [[u8; 32]]
+///
+/// -- Instruction Logic --
+/// Executes the long-term orders of the pool, then deposits Amount of a
+/// single token. The pool swaps the part of it that leaves the rest in the
+/// ratio of the reserves after the swap on its curve, then mints LP for the
+/// rest and the swap output as a Deposit would. Rounding dust stays in the
+/// reserves. Pools with an oracle guard check the spot price after the swap
+/// against their price feed. Logs a LiquidityEvent under DEPOSIT_EVENT for
+/// the amount received by vault_in. Allowlisted pools take the merkle proof
+/// of the user key after the other fields.
+///
+/// -- Client Side Logic --
+/// Accounts are [user, authority, mint_lp, user_token, user_lp, vault_in,
+/// vault_out, config, token_program, mint_in, token_program_in], vault_in
+/// being the vault of the deposited token, followed by the TWAMM and the
+/// price feed of the pool once it has them, in any order.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// Both deposits and swaps are unpaused and the pool already has liquidity.
+/// MinLp is checked against the LP minted. The swap does not move the spot
+/// price by more than the MaxPriceImpact of the pool. The user is on the
+/// allowlist of the pool, if it has one. The pool is not in batch mode, the
+/// swap half of the deposit being open to sandwiching. The oracle guard of
+/// the pool, if it has one, holds as in `curve::check_oracle_price`.
+pub fn deposit_single(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [user, authority, mint_lp, user_token, user_lp, vault_in, vault_out, config, _token_program, mint_in, token_program_in, remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Deserialize Data
+    if data.len() < 24 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (amount, min_lp, expiration) = unsafe {
+        let [amount, min_lp, expiration] = *(data.as_ptr() as *const [u64; 3]);
+        (amount, min_lp, expiration as i64)
+    };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    let price_feed = PriceFeed::from_config(&config_account, remaining)?;
+    if config_account.is_paused(PAUSE_DEPOSITS) || config_account.is_paused(PAUSE_SWAPS) {
+        return Err(AmmError::Locked.into());
+    }
+    if config_account.flash_loan() != FLASH_LOAN_NONE {
+        return Err(AmmError::FlashLoanActive.into());
+    }
+    if config_account.is_batched() {
+        return Err(AmmError::BatchOnly.into());
+    }
+    if mint_lp.key() != &config_account.mint_lp() {
+        return Err(AmmError::InvalidMint.into());
+    }
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    if now > expiration {
+        return Err(AmmError::Expired.into());
+    }
+    config_account.check_allowlist(user.key(), &data[24..])?;
+
+    let is_x = vault_in.key().eq(&config_account.vault_x());
+    let (expected_in, expected_out, expected_mint) = if is_x {
+        (
+            config_account.vault_x(),
+            config_account.vault_y(),
+            config_account.mint_x(),
+        )
+    } else {
+        (
+            config_account.vault_y(),
+            config_account.vault_x(),
+            config_account.mint_y(),
+        )
+    };
+    if vault_in.key() != &expected_in || vault_out.key() != &expected_out {
+        return Err(AmmError::InvalidVault.into());
+    }
+    token::check_mint(mint_in, &expected_mint, token_program_in)?;
+
+    // Execute the long-term orders up to now
+    if let Some(twamm) = Twamm::from_config(&config_account, remaining)? {
+        twamm.execute(&config_account, clock.slot, now)?;
+    }
+
+    let supply = unsafe { Mint::from_account_info_unchecked(mint_lp)?.supply() };
+    let (reserve_x, reserve_y) = (config_account.reserve_x(), config_account.reserve_y());
+    if reserve_x == 0 || reserve_y == 0 {
+        return Err(AmmError::InsufficientLiquidity.into());
+    }
+    let (reserve_in, reserve_out) = if is_x {
+        (reserve_x, reserve_y)
+    } else {
+        (reserve_y, reserve_x)
+    };
+
+    // Accumulate the price before the reserves move
+    config_account.update_oracle(reserve_x, reserve_y, now)?;
+
+    // Swap the optimal part of the amount the vault actually receives
+    let amount_in = TransferFee::from_account_info(mint_in, clock.epoch).amount_received(amount);
+    let swap_amount = curve::single_deposit_swap_amount(
+        &config_account,
+        is_x,
+        reserve_in,
+        reserve_out,
+        amount_in,
+        now,
+    )?;
+    let (amount_out, fee) = curve::swap_amount_out(
+        &config_account,
+        is_x,
+        reserve_in,
+        reserve_out,
+        swap_amount,
+        now,
+    )?;
+
+    let protocol_fee = config_account.accrue_protocol_fee(!is_x, fee)?;
+    config_account.apply_swap(is_x, swap_amount, amount_out + protocol_fee)?;
+    curve::check_price_impact(&config_account, reserve_x, reserve_y, now)?;
+    if let Some(price_feed) = price_feed {
+        curve::check_oracle_price(
+            &config_account,
+            &price_feed,
+            reserve_x,
+            reserve_y,
+            clock.slot,
+            now,
+        )?;
+    }
+
+    // Mint LP for the smaller share of the reserves after the swap
+    let (reserve_in, reserve_out) = if is_x {
+        (config_account.reserve_x(), config_account.reserve_y())
+    } else {
+        (config_account.reserve_y(), config_account.reserve_x())
+    };
+    let deposit_in = amount_in - swap_amount;
+    let supply = (supply + MINIMUM_LIQUIDITY) as u128;
+    let lp = (deposit_in as u128 * supply / reserve_in as u128)
+        .min(amount_out as u128 * supply / reserve_out as u128) as u64;
+
+    if lp == 0 || lp < min_lp {
+        return Err(AmmError::SlippageExceeded.into());
+    }
+
+    // The swap output never leaves the vault, it is deposited back
+    let reserve_in = reserve_in
+        .checked_add(deposit_in)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let reserve_out = reserve_out + amount_out;
+    if is_x {
+        config_account.set_reserves(reserve_in, reserve_out);
+    } else {
+        config_account.set_reserves(reserve_out, reserve_in);
+    }
+
+    // Deposit Tokens
+    TransferChecked {
+        from: user_token,
+        mint: mint_in,
+        to: vault_in,
+        authority: user,
+        token_program: token_program_in,
+        amount,
+        decimals: token::decimals(mint_in)?,
+    }
+    .invoke()?;
+
+    // Derive the signer
+    let binding = [config_account.authority_bump()];
+    let seeds = [Seed::from(config.key().as_ref()), Seed::from(&binding)];
+    let signer = [Signer::from(&seeds)];
+
+    // Mint LP Tokens
+    MintTo {
+        mint: mint_lp,
+        token: user_lp,
+        mint_authority: authority,
+        amount: lp,
+    }
+    .invoke_signed(&signer)?;
+
+    let (amount_x, amount_y) = if is_x { (amount_in, 0) } else { (0, amount_in) };
+    LiquidityEvent {
+        pool: config.key(),
+        user: user.key(),
+        amount_x,
+        amount_y,
+        lp,
+        reserve_x: config_account.reserve_x(),
+        reserve_y: config_account.reserve_y(),
+    }
+    .emit(DEPOSIT_EVENT);
+
+    Ok(())
+}
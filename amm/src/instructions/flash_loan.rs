@@ -0,0 +1,134 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    ProgramResult,
+};
+
+use crate::{
+    constants::{FLASH_LOAN_NONE, FLASH_LOAN_X, FLASH_LOAN_Y, MAX_FEE, PAUSE_SWAPS},
+    error::AmmError,
+    instructions::AmmInstruction,
+    introspection::Instructions,
+    state::Config,
+    token::{self, TransferChecked},
+};
+
+/// Position of the config account in FlashRepay
+const REPAY_CONFIG_INDEX: usize = 3;
+
+/// # FlashLoan
+///
+/// -- Data scheme --
+/// Amount: u64
+///
+/// -- Instruction Logic --
+/// Lends Amount out of one of the vaults. The pool records the principal plus
+/// the pool fee as owed, and FlashRepay pays it back into the same vault, so
+/// the fee is credited to LPs. The borrower receives Amount net of the
+/// transfer fee of the mint if it has one.
+///
+/// -- Client Side Logic --
+/// Accounts are [borrower, authority, vault, borrower_token, config,
+/// instructions, mint, token_program]. A FlashRepay on the same config must
+/// be a later top level instruction of the transaction.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The pool is unlocked with no outstanding flash loan, the vault belongs to
+/// the pool and the Instructions sysvar holds a matching FlashRepay after the
+/// current instruction.
+pub fn flash_loan(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [borrower, authority, vault, borrower_token, config, instructions, mint, token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !borrower.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 8 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let amount = unsafe { *(data.as_ptr() as *const u64) };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if config_account.is_paused(PAUSE_SWAPS) {
+        return Err(AmmError::Locked.into());
+    }
+    if config_account.flash_loan() != FLASH_LOAN_NONE {
+        return Err(AmmError::FlashLoanActive.into());
+    }
+
+    let (flash_loan, expected_mint) = if vault.key() == &config_account.vault_x() {
+        (FLASH_LOAN_X, config_account.mint_x())
+    } else if vault.key() == &config_account.vault_y() {
+        (FLASH_LOAN_Y, config_account.mint_y())
+    } else {
+        return Err(AmmError::InvalidVault.into());
+    };
+    token::check_mint(mint, &expected_mint, token_program)?;
+
+    // Look for the repayment after this instruction
+    let instructions = Instructions::from_account_info(instructions)?;
+    let repaid = (instructions.current_index()? + 1..instructions.count()?).any(|index| {
+        instructions.get(index).is_ok_and(|instruction| {
+            instruction.program_id() == &crate::ID
+                && instruction.data().first() == Some(&(AmmInstruction::FlashRepay as u8))
+                && instruction.account(REPAY_CONFIG_INDEX) == Some(config.key())
+        })
+    });
+    if !repaid {
+        return Err(AmmError::FlashLoanNotRepaid.into());
+    }
+
+    // Record the debt, the fee is rounded up in favor of the pool
+    let fee = (amount as u128 * config_account.fee() as u128).div_ceil(MAX_FEE as u128) as u64;
+    let repay = amount
+        .checked_add(fee)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    config_account.set_flash_loan(flash_loan, repay);
+
+    // The fee goes to the LPs. The transaction fails unless FlashRepay pays
+    // it, so it can be credited to the reserves right away.
+    let (reserve_x, reserve_y) = (config_account.reserve_x(), config_account.reserve_y());
+    if flash_loan == FLASH_LOAN_X {
+        config_account.set_reserves(
+            reserve_x
+                .checked_add(fee)
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+            reserve_y,
+        );
+    } else {
+        config_account.set_reserves(
+            reserve_x,
+            reserve_y
+                .checked_add(fee)
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+        );
+    }
+
+    // Derive the signer
+    let binding = [config_account.authority_bump()];
+    let seeds = [Seed::from(config.key().as_ref()), Seed::from(&binding)];
+    let signer = [Signer::from(&seeds)];
+
+    // Lend Tokens
+    TransferChecked {
+        from: vault,
+        mint,
+        to: borrower_token,
+        authority,
+        token_program,
+        amount,
+        decimals: token::decimals(mint)?,
+    }
+    .invoke_signed(&signer)?;
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    constants::{FLASH_LOAN_NONE, FLASH_LOAN_X},
+    error::AmmError,
+    state::Config,
+    token::{self, TransferChecked, TransferFee},
+};
+
+/// # FlashRepay
+///
+/// -- Instruction Logic --
+/// Pays the outstanding flash loan of the pool, principal plus fee, back into
+/// the vault it was taken from and clears the debt. The transfer fee of the
+/// mint is paid on top so the vault receives the full debt.
+///
+/// -- Client Side Logic --
+/// Accounts are [payer, payer_token, vault, config, mint, token_program],
+/// FlashLoan relies on the config being the fourth account.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// A flash loan is outstanding and the vault is the one it was taken from.
+pub fn flash_repay(accounts: &[AccountInfo]) -> ProgramResult {
+    let [payer, payer_token, vault, config, mint, token_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    let flash_loan = config_account.flash_loan();
+    if flash_loan == FLASH_LOAN_NONE {
+        return Err(AmmError::NoFlashLoan.into());
+    }
+
+    let (expected_vault, expected_mint) = if flash_loan == FLASH_LOAN_X {
+        (config_account.vault_x(), config_account.mint_x())
+    } else {
+        (config_account.vault_y(), config_account.mint_y())
+    };
+    if vault.key() != &expected_vault {
+        return Err(AmmError::InvalidVault.into());
+    }
+    token::check_mint(mint, &expected_mint, token_program)?;
+
+    let repay = TransferFee::from_account_info(mint, Clock::get()?.epoch)
+        .amount_to_send(config_account.flash_loan_repay())?;
+    config_account.set_flash_loan(FLASH_LOAN_NONE, 0);
+
+    // Repay Tokens
+    TransferChecked {
+        from: payer_token,
+        mint,
+        to: vault,
+        authority: payer,
+        token_program,
+        amount: repay,
+        decimals: token::decimals(mint)?,
+    }
+    .invoke()?;
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::AmmError,
+    state::{Config, Farm},
+    token::{self, TransferChecked, TransferFee},
+};
+
+/// # FundFarm
+///
+/// -- Data scheme --
+/// > Amount: u64
+/// > Duration: i64
+///
+/// -- Instruction Logic --
+/// Deposits Amount of the reward token in the reward vault and emits it,
+/// along with the rewards not emitted yet and the unallocated ones, evenly
+/// over the next Duration seconds. Emission is quoted on the amount the
+/// vault actually receives, the rounding remainder being left unallocated.
+///
+/// -- Client Side Logic --
+/// Accounts are [update_authority, funder_token, reward_vault, farm, config,
+/// reward_mint, reward_token_program].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The update authority of the pool signs, the farm belongs to the pool and
+/// Duration is positive.
+pub fn fund_farm(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [update_authority, funder_token, reward_vault, farm, config, reward_mint, reward_token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !update_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 16 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (amount, duration) = unsafe {
+        let [amount, duration] = *(data.as_ptr() as *const [u64; 2]);
+        (amount, duration as i64)
+    };
+    if duration <= 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if update_authority.key() != &config_account.update_authority() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+    let farm_account = Farm::from_account_info(farm)?;
+    if farm_account.config() != *config.key() {
+        return Err(AmmError::InvalidFarm.into());
+    }
+    if reward_vault.key() != &farm_account.reward_vault() {
+        return Err(AmmError::InvalidVault.into());
+    }
+    token::check_mint(
+        reward_mint,
+        &farm_account.reward_mint(),
+        reward_token_program,
+    )?;
+
+    // Accumulate the rewards emitted at the previous rate
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    farm_account.update(now)?;
+
+    // Spread the new, the remaining and the unallocated rewards over the
+    // duration
+    let leftover = (farm_account.reward_end().saturating_sub(now).max(0) as u128)
+        * farm_account.reward_rate() as u128
+        + farm_account.unallocated() as u128;
+    let amount_in =
+        TransferFee::from_account_info(reward_mint, clock.epoch).amount_received(amount);
+    let rewards = amount_in as u128 + leftover;
+    let reward_rate = rewards / duration as u128;
+    farm_account.set_unallocated((rewards % duration as u128) as u64);
+    let reward_end = now
+        .checked_add(duration)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    farm_account.set_emission(
+        u64::try_from(reward_rate).map_err(|_| ProgramError::ArithmeticOverflow)?,
+        reward_end,
+    );
+
+    // Deposit Rewards
+    TransferChecked {
+        from: funder_token,
+        mint: reward_mint,
+        to: reward_vault,
+        authority: update_authority,
+        token_program: reward_token_program,
+        amount,
+        decimals: token::decimals(reward_mint)?,
+    }
+    .invoke()?;
+
+    Ok(())
+}
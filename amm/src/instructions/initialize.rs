@@ -1,37 +1,298 @@
-use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::{instructions::InitilizeMint2, state::Mint};
+
+use crate::{
+    constants::{
+        CONFIG_SEED, CURVE_CONSTANT_PRODUCT, CURVE_STABLE, CURVE_WEIGHTED, FACTORY_SEED,
+        LP_DECIMALS, MAX_AMP, MAX_FEE, MAX_SWAP_FEE, MINT_LP_SEED, REGISTRY_SEED, VAULT_SEED,
+        WEIGHT_SCALE,
+    },
+    error::AmmError,
+    state::{Config, Factory, Registry},
+    system::CreatePda,
+    token::{self, InitializeAccount3},
+};
 
 /// # Initialize
 ///
 /// -- Data scheme --
 /// > Seed: u16
-/// > Authority: Flag<Pubkey>
-/// > MintX: Pubkey
-/// > MintY: Pubkey
-/// > MintLP: Pubkey
-/// > VaultX: Pubkey
-/// > VaultY: Pubkey
+/// > Authority: Pubkey
 /// > Fee: u16
-/// > AuthorityBump: u8
+/// > ProtocolFee: u16
+/// > Curve: u8
+/// > Amp: u64
+/// > PauseGuardian: Pubkey
+/// > MaxReferralFee: u16
+/// > MaxPriceImpact: u16
+/// > StartWeight: u16
+/// > EndWeight: u16
+/// > StartTime: i64
+/// > EndTime: i64
 ///
 /// -- Instruction Logic --
+/// Creates the config PDA [CONFIG_SEED, seed, mint_x, mint_y], the LP mint
+/// PDA [MINT_LP_SEED, config] and both vault PDAs [VAULT_SEED, config, mint],
+/// all of them controlled by the authority PDA [config]. Each vault is
+/// created by the token program of its mint, with room for the extensions
+/// Token-2022 requires on accounts of the mint. The registry PDA
+/// [REGISTRY_SEED, mint_x, mint_y, fee] records the pool as the canonical
+/// one of the pair and fee tier, its creation failing if one already exists.
 ///
 /// -- Client Side Logic --
+/// Derives every address above and passes them in the expected order,
+/// followed by the token program of the LP mint and the ones of mint_x and
+/// mint_y, the factory and the registry. Mints are sorted, mint_x having the
+/// smaller key.
 ///
 /// -- Account Optimization Logic --
 ///
 /// -- Checks --
-///
+/// Every passed address matches its derivation, mints are sorted and
+/// distinct, Fee is a tier whitelisted by the factory and not above
+/// MAX_SWAP_FEE, and the other fees and the price impact are not above
+/// MAX_FEE. The curve is known and StableSwap
+/// pools have an amplification between 1 and MAX_AMP. Weighted pools have
+/// both weights strictly between 0 and WEIGHT_SCALE and a StartTime not after
+/// their EndTime. The token program of each
+/// mint owns it and the mint has no extension the pool cannot custody.
 pub fn initialize(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
-    let [config] = accounts else {
+    let [initializer, authority, config, mint_x, mint_y, mint_lp, vault_x, vault_y, _system_program, _token_program, token_program_x, token_program_y, factory, registry] =
+        accounts
+    else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    assert!(config.is_signer());
+    if !initializer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
 
-    // Populate Config
-    unsafe {
-        *(config.borrow_mut_data_unchecked().as_ptr() as *mut &[u8]) = data;
+    // Deserialize Data
+    if data.len() < 103 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (
+        seed,
+        update_authority,
+        fee,
+        protocol_fee,
+        curve,
+        amp,
+        pause_guardian,
+        max_referral_fee,
+        max_price_impact,
+        start_weight,
+        end_weight,
+        start_time,
+        end_time,
+    ) = unsafe {
+        (
+            *(data.as_ptr() as *const [u8; 2]),
+            *(data.as_ptr().add(2) as *const Pubkey),
+            u16::from_le_bytes(*(data.as_ptr().add(34) as *const [u8; 2])),
+            u16::from_le_bytes(*(data.as_ptr().add(36) as *const [u8; 2])),
+            *data.as_ptr().add(38),
+            u64::from_le_bytes(*(data.as_ptr().add(39) as *const [u8; 8])),
+            *(data.as_ptr().add(47) as *const Pubkey),
+            u16::from_le_bytes(*(data.as_ptr().add(79) as *const [u8; 2])),
+            u16::from_le_bytes(*(data.as_ptr().add(81) as *const [u8; 2])),
+            u16::from_le_bytes(*(data.as_ptr().add(83) as *const [u8; 2])),
+            u16::from_le_bytes(*(data.as_ptr().add(85) as *const [u8; 2])),
+            i64::from_le_bytes(*(data.as_ptr().add(87) as *const [u8; 8])),
+            i64::from_le_bytes(*(data.as_ptr().add(95) as *const [u8; 8])),
+        )
     };
 
+    // Checks
+    if fee > MAX_SWAP_FEE
+        || protocol_fee > MAX_FEE
+        || max_referral_fee > MAX_FEE
+        || max_price_impact > MAX_FEE
+    {
+        return Err(AmmError::InvalidFee.into());
+    }
+    if mint_x.key() >= mint_y.key() {
+        return Err(AmmError::InvalidMint.into());
+    }
+    token::check_mint(mint_x, mint_x.key(), token_program_x)?;
+    token::check_mint(mint_y, mint_y.key(), token_program_y)?;
+    match curve {
+        CURVE_CONSTANT_PRODUCT => {}
+        CURVE_STABLE if (1..=MAX_AMP).contains(&amp) => {}
+        CURVE_WEIGHTED
+            if (1..WEIGHT_SCALE).contains(&start_weight)
+                && (1..WEIGHT_SCALE).contains(&end_weight)
+                && start_time <= end_time => {}
+        _ => return Err(AmmError::InvalidCurve.into()),
+    }
+
+    let (factory_key, _) = find_program_address(&[FACTORY_SEED], &crate::ID);
+    if factory.key() != &factory_key {
+        return Err(AmmError::InvalidFactory.into());
+    }
+    if !Factory::from_account_info(factory)?.has_fee_tier(fee) {
+        return Err(AmmError::InvalidFeeTier.into());
+    }
+
+    let fee_binding = fee.to_le_bytes();
+    let (registry_key, registry_bump) = find_program_address(
+        &[REGISTRY_SEED, mint_x.key(), mint_y.key(), &fee_binding],
+        &crate::ID,
+    );
+    if registry.key() != &registry_key {
+        return Err(AmmError::InvalidRegistry.into());
+    }
+
+    let (config_key, config_bump) = find_program_address(
+        &[CONFIG_SEED, &seed, mint_x.key(), mint_y.key()],
+        &crate::ID,
+    );
+    if config.key() != &config_key {
+        return Err(AmmError::InvalidConfig.into());
+    }
+
+    let (authority_key, authority_bump) = find_program_address(&[config.key()], &crate::ID);
+    if authority.key() != &authority_key {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+
+    let (mint_lp_key, mint_lp_bump) =
+        find_program_address(&[MINT_LP_SEED, config.key()], &crate::ID);
+    if mint_lp.key() != &mint_lp_key {
+        return Err(AmmError::InvalidMint.into());
+    }
+
+    let (vault_x_key, vault_x_bump) =
+        find_program_address(&[VAULT_SEED, config.key(), mint_x.key()], &crate::ID);
+    let (vault_y_key, vault_y_bump) =
+        find_program_address(&[VAULT_SEED, config.key(), mint_y.key()], &crate::ID);
+    if vault_x.key() != &vault_x_key || vault_y.key() != &vault_y_key {
+        return Err(AmmError::InvalidVault.into());
+    }
+
+    let rent = Rent::get()?;
+
+    // Create Registry
+    let binding = [registry_bump];
+    let seeds = [
+        Seed::from(REGISTRY_SEED),
+        Seed::from(mint_x.key()),
+        Seed::from(mint_y.key()),
+        Seed::from(&fee_binding),
+        Seed::from(&binding),
+    ];
+    CreatePda {
+        from: initializer,
+        to: registry,
+        lamports: rent.minimum_balance(Registry::LEN),
+        space: Registry::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&[Signer::from(&seeds)])?;
+
+    Registry::from_account_info_unchecked(registry).set_config(config.key());
+
+    // Create Config
+    let binding = [config_bump];
+    let seeds = [
+        Seed::from(CONFIG_SEED),
+        Seed::from(&seed),
+        Seed::from(mint_x.key()),
+        Seed::from(mint_y.key()),
+        Seed::from(&binding),
+    ];
+    CreatePda {
+        from: initializer,
+        to: config,
+        lamports: rent.minimum_balance(Config::LEN),
+        space: Config::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&[Signer::from(&seeds)])?;
+
+    // Create LP Mint
+    let binding = [mint_lp_bump];
+    let seeds = [
+        Seed::from(MINT_LP_SEED),
+        Seed::from(config.key()),
+        Seed::from(&binding),
+    ];
+    CreatePda {
+        from: initializer,
+        to: mint_lp,
+        lamports: rent.minimum_balance(Mint::LEN),
+        space: Mint::LEN as u64,
+        owner: &pinocchio_token::ID,
+    }
+    .invoke_signed(&[Signer::from(&seeds)])?;
+
+    InitilizeMint2 {
+        mint: mint_lp,
+        decimals: LP_DECIMALS,
+        mint_authority: authority.key(),
+        freeze_authority: None,
+    }
+    .invoke()?;
+
+    // Create Vaults
+    for (vault, mint, token_program, bump) in [
+        (vault_x, mint_x, token_program_x, vault_x_bump),
+        (vault_y, mint_y, token_program_y, vault_y_bump),
+    ] {
+        let space = token::vault_len(mint)?;
+        let binding = [bump];
+        let seeds = [
+            Seed::from(VAULT_SEED),
+            Seed::from(config.key()),
+            Seed::from(mint.key()),
+            Seed::from(&binding),
+        ];
+        CreatePda {
+            from: initializer,
+            to: vault,
+            lamports: rent.minimum_balance(space),
+            space: space as u64,
+            owner: token_program.key(),
+        }
+        .invoke_signed(&[Signer::from(&seeds)])?;
+
+        InitializeAccount3 {
+            token: vault,
+            mint,
+            owner: authority.key(),
+            token_program,
+        }
+        .invoke()?;
+    }
+
+    // Populate Config
+    let config_data = unsafe { config.borrow_mut_data_unchecked() };
+    config_data[0] = 0;
+    config_data[1..33].copy_from_slice(&update_authority);
+    config_data[33..65].copy_from_slice(mint_x.key());
+    config_data[65..97].copy_from_slice(mint_y.key());
+    config_data[97..129].copy_from_slice(mint_lp.key());
+    config_data[129..161].copy_from_slice(vault_x.key());
+    config_data[161..193].copy_from_slice(vault_y.key());
+    config_data[193..195].copy_from_slice(&fee.to_le_bytes());
+    config_data[195] = authority_bump;
+    config_data[196..198].copy_from_slice(&protocol_fee.to_le_bytes());
+    config_data[263] = curve;
+    config_data[264..272].copy_from_slice(&amp.to_le_bytes());
+    config_data[288..320].copy_from_slice(&pause_guardian);
+    config_data[352..354].copy_from_slice(&max_referral_fee.to_le_bytes());
+    config_data[354..356].copy_from_slice(&max_price_impact.to_le_bytes());
+    config_data[356..358].copy_from_slice(&start_weight.to_le_bytes());
+    config_data[358..360].copy_from_slice(&end_weight.to_le_bytes());
+    config_data[360..368].copy_from_slice(&start_time.to_le_bytes());
+    config_data[368..376].copy_from_slice(&end_time.to_le_bytes());
+
     Ok(())
 }
@@ -0,0 +1,64 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{constants::FACTORY_SEED, error::AmmError, state::Factory, system::CreatePda};
+
+/// # InitializeFactory
+///
+/// -- Data scheme --
+/// > Admin: Pubkey
+///
+/// -- Instruction Logic --
+/// Creates the factory PDA [FACTORY_SEED], holding the admin and the fee
+/// tiers pools can be initialized with. It starts with no fee tier.
+///
+/// -- Client Side Logic --
+/// Accounts are [payer, factory, system_program].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The factory address matches its derivation, creation fails if it exists.
+pub fn initialize_factory(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [payer, factory, _system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 32 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let admin = unsafe { *(data.as_ptr() as *const Pubkey) };
+
+    // Checks
+    let (factory_key, bump) = find_program_address(&[FACTORY_SEED], &crate::ID);
+    if factory.key() != &factory_key {
+        return Err(AmmError::InvalidFactory.into());
+    }
+
+    // Create Factory
+    let binding = [bump];
+    let seeds = [Seed::from(FACTORY_SEED), Seed::from(&binding)];
+    CreatePda {
+        from: payer,
+        to: factory,
+        lamports: Rent::get()?.minimum_balance(Factory::LEN),
+        space: Factory::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&[Signer::from(&seeds)])?;
+
+    Factory::from_account_info_unchecked(factory).set_admin(&admin);
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::{create_program_address, find_program_address},
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    constants::{FARM_SEED, VAULT_SEED},
+    error::AmmError,
+    state::{Config, Farm},
+    system::CreatePda,
+    token::{self, InitializeAccount3},
+};
+
+/// # InitializeFarm
+///
+/// -- Instruction Logic --
+/// Creates the farm PDA [FARM_SEED, config] of a pool along with its reward
+/// vault [VAULT_SEED, farm, reward_mint] and the stake vault [VAULT_SEED,
+/// farm, mint_lp] holding the staked LP tokens, both controlled by the pool
+/// authority. The farm emits nothing until FundFarm is called.
+///
+/// -- Client Side Logic --
+/// Accounts are [update_authority, authority, config, farm, mint_lp,
+/// reward_mint, stake_vault, reward_vault, system_program, token_program,
+/// reward_token_program].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The update authority signs, the authority is the one of the pool, every
+/// passed address matches its derivation and the reward mint is not the LP
+/// mint.
+pub fn initialize_farm(accounts: &[AccountInfo]) -> ProgramResult {
+    let [update_authority, authority, config, farm, mint_lp, reward_mint, stake_vault, reward_vault, _system_program, token_program, reward_token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !update_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if update_authority.key() != &config_account.update_authority() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+    let authority_key = create_program_address(
+        &[config.key(), &[config_account.authority_bump()]],
+        &crate::ID,
+    )?;
+    if authority.key() != &authority_key {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+    token::check_mint(mint_lp, &config_account.mint_lp(), token_program)?;
+    if reward_mint.key() == mint_lp.key() {
+        return Err(AmmError::InvalidMint.into());
+    }
+    token::check_mint(reward_mint, reward_mint.key(), reward_token_program)?;
+
+    let (farm_key, farm_bump) = find_program_address(&[FARM_SEED, config.key()], &crate::ID);
+    if farm.key() != &farm_key {
+        return Err(AmmError::InvalidFarm.into());
+    }
+    let (stake_vault_key, stake_vault_bump) =
+        find_program_address(&[VAULT_SEED, farm.key(), mint_lp.key()], &crate::ID);
+    let (reward_vault_key, reward_vault_bump) =
+        find_program_address(&[VAULT_SEED, farm.key(), reward_mint.key()], &crate::ID);
+    if stake_vault.key() != &stake_vault_key || reward_vault.key() != &reward_vault_key {
+        return Err(AmmError::InvalidVault.into());
+    }
+
+    let rent = Rent::get()?;
+
+    // Create Farm
+    let binding = [farm_bump];
+    let seeds = [
+        Seed::from(FARM_SEED),
+        Seed::from(config.key()),
+        Seed::from(&binding),
+    ];
+    CreatePda {
+        from: update_authority,
+        to: farm,
+        lamports: rent.minimum_balance(Farm::LEN),
+        space: Farm::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&[Signer::from(&seeds)])?;
+
+    // Create Vaults
+    for (vault, mint, token_program, bump) in [
+        (stake_vault, mint_lp, token_program, stake_vault_bump),
+        (
+            reward_vault,
+            reward_mint,
+            reward_token_program,
+            reward_vault_bump,
+        ),
+    ] {
+        let space = token::vault_len(mint)?;
+        let binding = [bump];
+        let seeds = [
+            Seed::from(VAULT_SEED),
+            Seed::from(farm.key()),
+            Seed::from(mint.key()),
+            Seed::from(&binding),
+        ];
+        CreatePda {
+            from: update_authority,
+            to: vault,
+            lamports: rent.minimum_balance(space),
+            space: space as u64,
+            owner: token_program.key(),
+        }
+        .invoke_signed(&[Signer::from(&seeds)])?;
+
+        InitializeAccount3 {
+            token: vault,
+            mint,
+            owner: authority.key(),
+            token_program,
+        }
+        .invoke()?;
+    }
+
+    // Populate Farm
+    Farm::from_account_info_unchecked(farm).set_accounts(
+        config.key(),
+        reward_mint.key(),
+        reward_vault.key(),
+        stake_vault.key(),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    constants::OBSERVATIONS_SEED,
+    error::AmmError,
+    state::{Config, Observations},
+    system::CreatePda,
+};
+
+/// # InitializeObservations
+///
+/// -- Data scheme --
+/// > Capacity: u16
+///
+/// -- Instruction Logic --
+/// Creates the optional observations ring buffer PDA [OBSERVATIONS_SEED, config]
+/// that Observe writes oracle snapshots into.
+///
+/// -- Client Side Logic --
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The config is a pool and the observations address matches its derivation.
+pub fn initialize_observations(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [payer, config, observations, _system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 2 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let capacity = unsafe { u16::from_le_bytes(*(data.as_ptr() as *const [u8; 2])) };
+    if capacity == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Checks
+    Config::from_account_info(config)?;
+    let (observations_key, bump) =
+        find_program_address(&[OBSERVATIONS_SEED, config.key()], &crate::ID);
+    if observations.key() != &observations_key {
+        return Err(AmmError::InvalidObservations.into());
+    }
+
+    // Create Observations
+    let space = Observations::space(capacity);
+    let binding = [bump];
+    let seeds = [
+        Seed::from(OBSERVATIONS_SEED),
+        Seed::from(config.key()),
+        Seed::from(&binding),
+    ];
+    CreatePda {
+        from: payer,
+        to: observations,
+        lamports: Rent::get()?.minimum_balance(space),
+        space: space as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&[Signer::from(&seeds)])?;
+
+    unsafe {
+        *(observations.borrow_mut_data_unchecked().as_mut_ptr() as *mut [u8; 32]) = *config.key()
+    };
+
+    Ok(())
+}
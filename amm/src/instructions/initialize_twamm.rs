@@ -0,0 +1,75 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    constants::TWAMM_SEED,
+    error::AmmError,
+    state::{Config, Twamm},
+    system::CreatePda,
+};
+
+/// # InitializeTwamm
+///
+/// -- Instruction Logic --
+/// Creates the TWAMM PDA [TWAMM_SEED, config] executing the long-term orders
+/// of the pool from the current slot and records it in the config. From then
+/// on every instruction moving the reserves takes it to execute the orders
+/// before it runs.
+///
+/// -- Client Side Logic --
+/// Accounts are [update_authority, config, twamm, system_program].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The signer is the update authority, the pool has no TWAMM yet and the
+/// TWAMM address matches its derivation.
+pub fn initialize_twamm(accounts: &[AccountInfo]) -> ProgramResult {
+    let [update_authority, config, twamm, _system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !update_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if update_authority.key() != &config_account.update_authority() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+    if config_account.twamm() != Pubkey::default() {
+        return Err(AmmError::InvalidTwamm.into());
+    }
+    let (twamm_key, bump) = find_program_address(&[TWAMM_SEED, config.key()], &crate::ID);
+    if twamm.key() != &twamm_key {
+        return Err(AmmError::InvalidTwamm.into());
+    }
+
+    // Create Twamm
+    let binding = [bump];
+    let seeds = [
+        Seed::from(TWAMM_SEED),
+        Seed::from(config.key()),
+        Seed::from(&binding),
+    ];
+    CreatePda {
+        from: update_authority,
+        to: twamm,
+        lamports: Rent::get()?.minimum_balance(Twamm::LEN),
+        space: Twamm::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&[Signer::from(&seeds)])?;
+
+    Twamm::from_account_info_unchecked(twamm).init(config.key(), Clock::get()?.slot);
+    config_account.set_twamm(twamm.key());
+
+    Ok(())
+}
@@ -1,10 +1,43 @@
 use pinocchio::program_error::ProgramError;
 
+pub mod accept_authority;
+pub mod cancel_order;
+pub mod claim_batch;
+pub mod claim_order;
+pub mod claim_rewards;
+pub mod close_pool;
+pub mod collect_protocol_fees;
+pub mod commit_swap;
 pub mod deposit;
+pub mod deposit_single;
+pub mod flash_loan;
+pub mod flash_repay;
+pub mod fund_farm;
 pub mod initialize;
-pub mod lock;
+pub mod initialize_factory;
+pub mod initialize_farm;
+pub mod initialize_observations;
+pub mod initialize_twamm;
+pub mod observe;
+pub mod place_order;
+pub mod propose_authority;
+pub mod renounce_authority;
+pub mod reveal_swap;
+pub mod route_swap;
+pub mod set_allowlist;
+pub mod set_batch;
+pub mod set_fee;
+pub mod set_fee_tier;
+pub mod set_oracle_guard;
+pub mod set_pause;
+pub mod settle_batch;
+pub mod stake_lp;
 pub mod swap;
+pub mod swap_exact_out;
+pub mod sweep_rewards;
+pub mod unstake_lp;
 pub mod withdraw;
+pub mod withdraw_single;
 
 #[derive(Clone, Copy, Debug)]
 pub enum AmmInstruction {
@@ -12,7 +45,40 @@ pub enum AmmInstruction {
     Deposit,
     Withdraw,
     Swap,
-    Lock,
+    SetPause,
+    SwapExactOut,
+    CollectProtocolFees,
+    RouteSwap,
+    InitializeObservations,
+    Observe,
+    FlashLoan,
+    FlashRepay,
+    SetFee,
+    ProposeAuthority,
+    AcceptAuthority,
+    RenounceAuthority,
+    DepositSingle,
+    WithdrawSingle,
+    InitializeFactory,
+    SetFeeTier,
+    InitializeFarm,
+    FundFarm,
+    StakeLp,
+    UnstakeLp,
+    ClaimRewards,
+    ClosePool,
+    InitializeTwamm,
+    PlaceOrder,
+    ClaimOrder,
+    CancelOrder,
+    SetAllowlist,
+    SetBatch,
+    CommitSwap,
+    RevealSwap,
+    SettleBatch,
+    ClaimBatch,
+    SetOracleGuard,
+    SweepRewards,
 }
 
 impl TryFrom<&u8> for AmmInstruction {
@@ -24,7 +90,40 @@ impl TryFrom<&u8> for AmmInstruction {
             1 => Ok(Self::Deposit),
             2 => Ok(Self::Withdraw),
             3 => Ok(Self::Swap),
-            4 => Ok(Self::Lock),
+            4 => Ok(Self::SetPause),
+            5 => Ok(Self::SwapExactOut),
+            6 => Ok(Self::CollectProtocolFees),
+            7 => Ok(Self::RouteSwap),
+            8 => Ok(Self::InitializeObservations),
+            9 => Ok(Self::Observe),
+            10 => Ok(Self::FlashLoan),
+            11 => Ok(Self::FlashRepay),
+            12 => Ok(Self::SetFee),
+            13 => Ok(Self::ProposeAuthority),
+            14 => Ok(Self::AcceptAuthority),
+            15 => Ok(Self::RenounceAuthority),
+            16 => Ok(Self::DepositSingle),
+            17 => Ok(Self::WithdrawSingle),
+            18 => Ok(Self::InitializeFactory),
+            19 => Ok(Self::SetFeeTier),
+            20 => Ok(Self::InitializeFarm),
+            21 => Ok(Self::FundFarm),
+            22 => Ok(Self::StakeLp),
+            23 => Ok(Self::UnstakeLp),
+            24 => Ok(Self::ClaimRewards),
+            25 => Ok(Self::ClosePool),
+            26 => Ok(Self::InitializeTwamm),
+            27 => Ok(Self::PlaceOrder),
+            28 => Ok(Self::ClaimOrder),
+            29 => Ok(Self::CancelOrder),
+            30 => Ok(Self::SetAllowlist),
+            31 => Ok(Self::SetBatch),
+            32 => Ok(Self::CommitSwap),
+            33 => Ok(Self::RevealSwap),
+            34 => Ok(Self::SettleBatch),
+            35 => Ok(Self::ClaimBatch),
+            36 => Ok(Self::SetOracleGuard),
+            37 => Ok(Self::SweepRewards),
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
@@ -0,0 +1,57 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::AmmError,
+    state::{Config, Observation, Observations},
+};
+
+/// # Observe
+///
+/// -- Instruction Logic --
+/// Brings the pool cumulative prices up to the current timestamp and records
+/// them in the observations ring buffer, at most once per timestamp.
+///
+/// -- Client Side Logic --
+/// Permissionless, meant to be called by keepers or by consumers right before
+/// reading a TWAP with [`Observations::twap`].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The observations account belongs to the pool.
+pub fn observe(accounts: &[AccountInfo]) -> ProgramResult {
+    let [config, observations] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    let observations_account = Observations::from_account_info(observations)?;
+    if observations_account.config() != *config.key() {
+        return Err(AmmError::InvalidObservations.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    if observations_account
+        .get_back(0)
+        .is_some_and(|latest| latest.timestamp >= now)
+    {
+        return Ok(());
+    }
+
+    // Accumulate up to now with the current reserves
+    config_account.update_oracle(config_account.reserve_x(), config_account.reserve_y(), now)?;
+
+    observations_account.push(Observation {
+        timestamp: now,
+        price_x_cumulative: config_account.price_x_cumulative(),
+        price_y_cumulative: config_account.price_y_cumulative(),
+    });
+
+    Ok(())
+}
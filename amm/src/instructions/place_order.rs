@@ -0,0 +1,148 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    constants::{FLASH_LOAN_NONE, ORDER_SEED, PAUSE_SWAPS, TWAMM_BUCKETS, TWAMM_INTERVAL},
+    error::AmmError,
+    state::{Config, Order, Twamm},
+    system::CreatePda,
+    token::{self, TransferChecked, TransferFee},
+};
+
+/// # PlaceOrder
+///
+/// -- Data scheme --
+/// > Amount: u64
+/// > Slots: u64
+/// > Proof: [[u8; 32]]
+///
+/// -- Instruction Logic --
+/// Executes the TWAMM, then opens the order PDA [ORDER_SEED, twamm, user]
+/// selling the Amount of X received by the vault evenly until the first
+/// multiple of TWAMM_INTERVAL at least Slots away. The sale rate is rounded
+/// down and only the X it sells is transferred, the remainder staying with
+/// the user. The X of the order is held in
+/// vault_x on top of the reserves until it is sold. Allowlisted pools take
+/// the merkle proof of the user key after the other fields.
+///
+/// -- Client Side Logic --
+/// Accounts are [user, config, twamm, order, user_x, vault_x, mint_x,
+/// system_program, token_program_x]. A user has one order per pool at a time.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// Swaps are not paused, no flash loan is outstanding and the pool has
+/// reserves. The order address matches its derivation and is not open, the
+/// order lasts at least a slot, sells at least one token per slot and ends
+/// within TWAMM_BUCKETS intervals. The user is on the allowlist of the pool,
+/// if it has one.
+pub fn place_order(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [user, config, twamm, order, user_x, vault_x, mint_x, _system_program, token_program_x] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 16 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let [amount, slots] = unsafe { *(data.as_ptr() as *const [u64; 2]) };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if config_account.is_paused(PAUSE_SWAPS) {
+        return Err(AmmError::Locked.into());
+    }
+    if config_account.flash_loan() != FLASH_LOAN_NONE {
+        return Err(AmmError::FlashLoanActive.into());
+    }
+    if config_account.reserve_x() == 0 || config_account.reserve_y() == 0 {
+        return Err(AmmError::InsufficientLiquidity.into());
+    }
+    config_account.check_allowlist(user.key(), &data[16..])?;
+    if twamm.key() != &config_account.twamm() {
+        return Err(AmmError::InvalidTwamm.into());
+    }
+    let twamm_account = Twamm::from_account_info(twamm)?;
+    if vault_x.key() != &config_account.vault_x() {
+        return Err(AmmError::InvalidVault.into());
+    }
+    token::check_mint(mint_x, &config_account.mint_x(), token_program_x)?;
+    let (order_key, order_bump) =
+        find_program_address(&[ORDER_SEED, twamm.key(), user.key()], &crate::ID);
+    if order.key() != &order_key || order.data_len() != 0 {
+        return Err(AmmError::InvalidOrder.into());
+    }
+
+    // Execute the orders up to now, the new one selling from the current slot
+    let clock = Clock::get()?;
+    twamm_account.execute(&config_account, clock.slot, clock.unix_timestamp)?;
+
+    let end_slot = clock
+        .slot
+        .checked_add(slots)
+        .and_then(|end_slot| end_slot.checked_next_multiple_of(TWAMM_INTERVAL))
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let duration = end_slot - clock.slot;
+    if duration == 0 || duration > TWAMM_INTERVAL * TWAMM_BUCKETS as u64 {
+        return Err(AmmError::InvalidOrder.into());
+    }
+    let transfer_fee = TransferFee::from_account_info(mint_x, clock.epoch);
+    let sale_rate = transfer_fee.amount_received(amount) / duration;
+    if sale_rate == 0 {
+        return Err(AmmError::InvalidOrder.into());
+    }
+    let amount = transfer_fee.amount_to_send(sale_rate * duration)?;
+    twamm_account.add_order(end_slot, sale_rate)?;
+
+    // Create Order
+    let binding = [order_bump];
+    let seeds = [
+        Seed::from(ORDER_SEED),
+        Seed::from(twamm.key()),
+        Seed::from(user.key()),
+        Seed::from(&binding),
+    ];
+    CreatePda {
+        from: user,
+        to: order,
+        lamports: Rent::get()?.minimum_balance(Order::LEN),
+        space: Order::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&[Signer::from(&seeds)])?;
+
+    Order::from_account_info_unchecked(order).init(
+        twamm.key(),
+        user.key(),
+        sale_rate,
+        end_slot,
+        twamm_account.proceeds_per_sale_rate(),
+    );
+
+    // Deposit Tokens
+    TransferChecked {
+        from: user_x,
+        mint: mint_x,
+        to: vault_x,
+        authority: user,
+        token_program: token_program_x,
+        amount,
+        decimals: token::decimals(mint_x)?,
+    }
+    .invoke()?;
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{error::AmmError, state::Config};
+
+/// # ProposeAuthority
+///
+/// -- Data scheme --
+/// > NewAuthority: Pubkey
+///
+/// -- Instruction Logic --
+/// Records NewAuthority as the pending update authority, which takes over once
+/// it signs AcceptAuthority. Proposing the default key cancels the handover.
+///
+/// -- Client Side Logic --
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The update authority signs.
+pub fn propose_authority(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [update_authority, config] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !update_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 32 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let new_authority = unsafe { &*(data.as_ptr() as *const Pubkey) };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if update_authority.key() != &config_account.update_authority() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+
+    config_account.set_pending_authority(new_authority);
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{error::AmmError, state::Config};
+
+/// # RenounceAuthority
+///
+/// -- Instruction Logic --
+/// Sets the update authority to None, stored as the default key that nobody
+/// can sign for, and drops any pending authority. The pause guardian is
+/// cleared and the protocol fee set to zero, so the fees and the pause flags
+/// of the pool can no longer be changed by anyone.
+///
+/// -- Client Side Logic --
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The update authority signs and the protocol fees have been collected.
+pub fn renounce_authority(accounts: &[AccountInfo]) -> ProgramResult {
+    let [update_authority, config] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !update_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if update_authority.key() != &config_account.update_authority() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+    if config_account.protocol_fees_x() != 0 || config_account.protocol_fees_y() != 0 {
+        return Err(AmmError::ProtocolFeesNotCollected.into());
+    }
+
+    config_account.set_update_authority(&Pubkey::default());
+    config_account.set_pending_authority(&Pubkey::default());
+    config_account.set_pause_guardian(&Pubkey::default());
+    config_account.set_protocol_fee(0);
+
+    Ok(())
+}
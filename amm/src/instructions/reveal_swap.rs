@@ -0,0 +1,125 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    constants::PAUSE_SWAPS,
+    error::AmmError,
+    state::{Config, Intent, Round},
+    token::{self, TransferChecked, TransferFee},
+};
+
+/// # RevealSwap
+///
+/// -- Data scheme --
+/// > Amount: u64
+/// > Salt: [u8; 32]
+///
+/// -- Instruction Logic --
+/// Reveals the swap committed by the intent, selling Amount of the token of
+/// vault_from, and deposits it into the vault where it is held on top of the
+/// reserves until the round is settled, counted in the BatchX or BatchY of
+/// the pool. The intent and the round record the amount received by the
+/// vault, net of the transfer fee of the mint if it has one.
+///
+/// -- Client Side Logic --
+/// Accounts are [user, config, round, intent, user_from, vault_from,
+/// mint_from, token_program_from]. Intents left unrevealed are refunded
+/// their rent by ClaimBatch and swap nothing.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// Swaps are not paused and the pool has reserves. The round belongs to the
+/// pool and is in its reveal window, the user owns the intent of the round,
+/// which is not revealed yet and commits to Amount of the token of vault_from
+/// with Salt. The vault receives a nonzero amount.
+pub fn reveal_swap(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [user, config, round, intent, user_from, vault_from, mint_from, token_program_from] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 40 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (amount, salt) = unsafe {
+        (
+            u64::from_le_bytes(*(data.as_ptr() as *const [u8; 8])),
+            &*(data.as_ptr().add(8) as *const [u8; 32]),
+        )
+    };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if config_account.is_paused(PAUSE_SWAPS) {
+        return Err(AmmError::Locked.into());
+    }
+    if config_account.reserve_x() == 0 || config_account.reserve_y() == 0 {
+        return Err(AmmError::InsufficientLiquidity.into());
+    }
+    let round_account = Round::from_account_info(round)?;
+    let clock = Clock::get()?;
+    if round_account.config() != *config.key()
+        || clock.slot < round_account.commit_end()
+        || clock.slot >= round_account.reveal_end()
+    {
+        return Err(AmmError::InvalidBatch.into());
+    }
+    let intent_account = Intent::from_account_info(intent)?;
+    if intent_account.round() != *round.key()
+        || intent_account.owner() != *user.key()
+        || intent_account.amount() != 0
+    {
+        return Err(AmmError::InvalidIntent.into());
+    }
+
+    let in_x = vault_from.key() == &config_account.vault_x();
+    if !in_x && vault_from.key() != &config_account.vault_y() {
+        return Err(AmmError::InvalidVault.into());
+    }
+    if Intent::hash(user.key(), in_x, amount, salt) != intent_account.commitment() {
+        return Err(AmmError::InvalidIntent.into());
+    }
+    let mint = if in_x {
+        config_account.mint_x()
+    } else {
+        config_account.mint_y()
+    };
+    token::check_mint(mint_from, &mint, token_program_from)?;
+
+    let received = TransferFee::from_account_info(mint_from, clock.epoch).amount_received(amount);
+    if received == 0 {
+        return Err(AmmError::InvalidIntent.into());
+    }
+    intent_account.reveal(in_x, received);
+    round_account.add(in_x, received)?;
+    if in_x {
+        config_account.hold_batch(received, 0)?;
+    } else {
+        config_account.hold_batch(0, received)?;
+    }
+
+    // Deposit Tokens
+    TransferChecked {
+        from: user_from,
+        mint: mint_from,
+        to: vault_from,
+        authority: user,
+        token_program: token_program_from,
+        amount,
+        decimals: token::decimals(mint_from)?,
+    }
+    .invoke()?;
+
+    Ok(())
+}
@@ -0,0 +1,230 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    constants::{FLASH_LOAN_NONE, PAUSE_SWAPS},
+    curve,
+    error::AmmError,
+    events::SwapEvent,
+    state::{Config, PriceFeed, Twamm},
+    token::{self, TransferChecked, TransferFee},
+};
+
+/// Number of accounts of every hop, the TWAMM and price feed of its pool aside
+const HOP_LEN: usize = 6;
+
+/// # RouteSwap
+///
+/// -- Data scheme --
+/// Amount: u64
+/// MinAmount: u64
+/// Expiration: i64
+///
+/// -- Instruction Logic --
+/// Swaps Amount of user_from through every pool of the route, executing the
+/// long-term orders of each pool before its hop. The output of each hop is
+/// transferred from its vault_to straight into the vault_from of the next
+/// hop, and the output of the last hop to user_to. Every hop quotes on the
+/// amount received by its vault_from, net of transfer fees. Pools with an
+/// oracle guard check their spot price after their hop against their price
+/// feed. Logs a SwapEvent per hop.
+///
+/// -- Client Side Logic --
+/// Accounts are [user, user_from, user_to, mint_from, token_program_from]
+/// followed by one [authority, vault_from, vault_to, config, mint_to,
+/// token_program_to] group per hop, in route order, each group followed by
+/// the TWAMM and the price feed of its pool once it has them, in any order.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// Every hop is checked as in Swap, the spot price of its pool not moving by
+/// more than the MaxPriceImpact of the pool, but slippage is only checked
+/// once on the final amount received by the user. The oracle guard of every
+/// pool, if it has one, holds as in `curve::check_oracle_price`. Allowlisted
+/// pools cannot be routed through, their proofs going to Swap, nor can pools
+/// in batch mode.
+pub fn route_swap(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [user, user_from, user_to, mint_from, token_program_from, hops @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if hops.len() < HOP_LEN {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    // Deserialize Data
+    if data.len() < 24 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (amount, min, expiration) = unsafe {
+        let [amount, min, expiration] = *(data.as_ptr() as *const [u64; 3]);
+        (amount, min, expiration as i64)
+    };
+
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    if now > expiration {
+        return Err(AmmError::Expired.into());
+    }
+
+    // Deposit Tokens into the first pool
+    TransferChecked {
+        from: user_from,
+        mint: mint_from,
+        to: &hops[1],
+        authority: user,
+        token_program: token_program_from,
+        amount,
+        decimals: token::decimals(mint_from)?,
+    }
+    .invoke()?;
+
+    let mut amount_in =
+        TransferFee::from_account_info(mint_from, clock.epoch).amount_received(amount);
+    let (mut mint_in, mut token_program_in) = (mint_from, token_program_from);
+
+    let mut offset = 0;
+    while offset < hops.len() {
+        let [authority, vault_from, vault_to, config, mint_to, token_program_to, extra @ ..] =
+            &hops[offset..]
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Checks
+        let config_account = Config::from_account_info(config)?;
+        // The TWAMM and price feed of the pool, if it has them, close the hop
+        let extra_len = (config_account.twamm() != Pubkey::default()) as usize
+            + (config_account.price_feed() != Pubkey::default()) as usize;
+        if extra.len() < extra_len {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let twamm = Twamm::from_config(&config_account, &extra[..extra_len])?;
+        let price_feed = PriceFeed::from_config(&config_account, &extra[..extra_len])?;
+        let next = offset + HOP_LEN + extra_len;
+        let is_last = next == hops.len();
+        if config_account.is_paused(PAUSE_SWAPS) {
+            return Err(AmmError::Locked.into());
+        }
+        if config_account.flash_loan() != FLASH_LOAN_NONE {
+            return Err(AmmError::FlashLoanActive.into());
+        }
+        if config_account.is_batched() {
+            return Err(AmmError::BatchOnly.into());
+        }
+        if config_account.allowlist_root() != [0; 32] {
+            return Err(AmmError::NotAllowlisted.into());
+        }
+
+        let is_x = vault_from.key().eq(&config_account.vault_x());
+        let (expected_from, expected_to) = if is_x {
+            (config_account.vault_x(), config_account.vault_y())
+        } else {
+            (config_account.vault_y(), config_account.vault_x())
+        };
+        if vault_from.key() != &expected_from || vault_to.key() != &expected_to {
+            return Err(AmmError::InvalidVault.into());
+        }
+        let (expected_mint_in, expected_mint_to) = if is_x {
+            (config_account.mint_x(), config_account.mint_y())
+        } else {
+            (config_account.mint_y(), config_account.mint_x())
+        };
+        token::check_mint(mint_in, &expected_mint_in, token_program_in)?;
+        token::check_mint(mint_to, &expected_mint_to, token_program_to)?;
+
+        // Execute the long-term orders up to now
+        if let Some(twamm) = twamm {
+            twamm.execute(&config_account, clock.slot, now)?;
+        }
+
+        // Read the reserves, accrued protocol fees and donations to the vaults are not part of them
+        let (reserve_x, reserve_y) = (config_account.reserve_x(), config_account.reserve_y());
+        let (vault_from_amount, vault_to_amount) = if is_x {
+            (reserve_x, reserve_y)
+        } else {
+            (reserve_y, reserve_x)
+        };
+
+        // Accumulate the price before the reserves move
+        config_account.update_oracle(reserve_x, reserve_y, now)?;
+
+        let (amount_out, fee) = curve::swap_amount_out(
+            &config_account,
+            is_x,
+            vault_from_amount,
+            vault_to_amount,
+            amount_in,
+            now,
+        )?;
+
+        let protocol_fee = config_account.accrue_protocol_fee(!is_x, fee)?;
+        // The rest of the fee stays in the reserves for the LPs
+        config_account.apply_swap(is_x, amount_in, amount_out + protocol_fee)?;
+        curve::check_price_impact(&config_account, reserve_x, reserve_y, now)?;
+        if let Some(price_feed) = price_feed {
+            curve::check_oracle_price(
+                &config_account,
+                &price_feed,
+                reserve_x,
+                reserve_y,
+                clock.slot,
+                now,
+            )?;
+        }
+
+        // Slippage check on the final hop only
+        let amount_received =
+            TransferFee::from_account_info(mint_to, clock.epoch).amount_received(amount_out);
+        if is_last && amount_received < min {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+
+        // Derive the signer
+        let binding = [config_account.authority_bump()];
+        let seeds = [Seed::from(config.key().as_ref()), Seed::from(&binding)];
+        let signer = [Signer::from(&seeds)];
+
+        // Forward Tokens to the next pool or to the user
+        TransferChecked {
+            from: vault_to,
+            mint: mint_to,
+            to: if is_last {
+                user_to
+            } else {
+                hops.get(next + 1)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?
+            },
+            authority,
+            token_program: token_program_to,
+            amount: amount_out,
+            decimals: token::decimals(mint_to)?,
+        }
+        .invoke_signed(&signer)?;
+
+        SwapEvent {
+            pool: config.key(),
+            user: user.key(),
+            in_x: is_x,
+            amount_in,
+            amount_out,
+            fee,
+            reserve_x: config_account.reserve_x(),
+            reserve_y: config_account.reserve_y(),
+        }
+        .emit();
+
+        amount_in = amount_received;
+        (mint_in, token_program_in) = (mint_to, token_program_to);
+        offset = next;
+    }
+
+    Ok(())
+}
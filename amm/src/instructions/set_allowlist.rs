@@ -0,0 +1,46 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{error::AmmError, state::Config};
+
+/// # SetAllowlist
+///
+/// -- Data scheme --
+/// > AllowlistRoot: [u8; 32]
+///
+/// -- Instruction Logic --
+/// Replaces the merkle root of the users allowed to swap and deposit into
+/// the pool. An all zero root lifts the allowlist.
+///
+/// -- Client Side Logic --
+/// Trees are built with `merkle::leaf` and `merkle::parent`, users passing
+/// the siblings of their leaf as the proof.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The update authority signs.
+pub fn set_allowlist(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [update_authority, config] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !update_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 32 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let root = unsafe { &*(data.as_ptr() as *const [u8; 32]) };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if update_authority.key() != &config_account.update_authority() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+
+    config_account.set_allowlist_root(root);
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{error::AmmError, state::Config};
+
+/// # SetBatch
+///
+/// -- Data scheme --
+/// > CommitSlots: u64
+/// > RevealSlots: u64
+///
+/// -- Instruction Logic --
+/// Switches the pool to batch rounds of CommitSlots slots committing swaps
+/// followed by RevealSlots slots revealing them, or back to continuous swaps
+/// when both are zero. Rounds already open keep their windows, and rounds
+/// opened afterwards keep counting from the BatchRound of the pool.
+///
+/// -- Client Side Logic --
+/// Accounts are [update_authority, config].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The update authority signs and the windows are both zero or both nonzero.
+pub fn set_batch(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [update_authority, config] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !update_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 16 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let [commit_slots, reveal_slots] = unsafe { *(data.as_ptr() as *const [u64; 2]) };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if update_authority.key() != &config_account.update_authority() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+    if (commit_slots == 0) != (reveal_slots == 0)
+        || commit_slots.checked_add(reveal_slots).is_none()
+    {
+        return Err(AmmError::InvalidBatch.into());
+    }
+
+    config_account.set_batch(commit_slots, reveal_slots);
+
+    Ok(())
+}
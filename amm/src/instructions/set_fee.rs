@@ -0,0 +1,139 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    constants::{FACTORY_SEED, MAX_FEE, MAX_SWAP_FEE, REGISTRY_SEED},
+    error::AmmError,
+    state::{Config, Factory, Registry},
+    system::CreatePda,
+};
+
+/// # SetFee
+///
+/// -- Data scheme --
+/// > Fee: u16
+/// > ProtocolFee: u16
+/// > MaxReferralFee: u16
+/// > MaxPriceImpact: u16
+///
+/// -- Instruction Logic --
+/// Replaces the swap fee, the protocol share of it, the largest share
+/// referrers can ask for and the largest price impact of a swap or a batch
+/// settlement. Long-term orders are not held to it, their sales being
+/// spread over the intervals they run for instead. A new swap
+/// fee moves the pool to the registry of its tier: the registry PDA
+/// [REGISTRY_SEED, mint_x, mint_y, fee] is created and the previous one is
+/// closed, its rent going to the update authority.
+///
+/// -- Client Side Logic --
+/// Accounts are [update_authority, config], followed by [factory, registry,
+/// new_registry, system_program] when Fee differs from the current one.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The update authority signs, Fee is not above MAX_SWAP_FEE and the other
+/// fees and the price impact are not above MAX_FEE. A new Fee is a tier
+/// whitelisted by the factory, the registry points at the pool and the new
+/// registry matches its derivation, creation fails if it exists.
+pub fn set_fee(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [update_authority, config, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !update_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 8 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (fee, protocol_fee, max_referral_fee, max_price_impact) = unsafe {
+        (
+            u16::from_le_bytes(*(data.as_ptr() as *const [u8; 2])),
+            u16::from_le_bytes(*(data.as_ptr().add(2) as *const [u8; 2])),
+            u16::from_le_bytes(*(data.as_ptr().add(4) as *const [u8; 2])),
+            u16::from_le_bytes(*(data.as_ptr().add(6) as *const [u8; 2])),
+        )
+    };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if update_authority.key() != &config_account.update_authority() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+    if fee > MAX_SWAP_FEE
+        || protocol_fee > MAX_FEE
+        || max_referral_fee > MAX_FEE
+        || max_price_impact > MAX_FEE
+    {
+        return Err(AmmError::InvalidFee.into());
+    }
+
+    // Move Registry
+    if fee != config_account.fee() {
+        let [factory, registry, new_registry, _system_program] = remaining else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let (factory_key, _) = find_program_address(&[FACTORY_SEED], &crate::ID);
+        if factory.key() != &factory_key {
+            return Err(AmmError::InvalidFactory.into());
+        }
+        if !Factory::from_account_info(factory)?.has_fee_tier(fee) {
+            return Err(AmmError::InvalidFeeTier.into());
+        }
+        if Registry::from_account_info(registry)?.config() != *config.key() {
+            return Err(AmmError::InvalidRegistry.into());
+        }
+
+        let mint_x = config_account.mint_x();
+        let mint_y = config_account.mint_y();
+        let fee_binding = fee.to_le_bytes();
+        let (registry_key, registry_bump) =
+            find_program_address(&[REGISTRY_SEED, &mint_x, &mint_y, &fee_binding], &crate::ID);
+        if new_registry.key() != &registry_key {
+            return Err(AmmError::InvalidRegistry.into());
+        }
+
+        let binding = [registry_bump];
+        let seeds = [
+            Seed::from(REGISTRY_SEED),
+            Seed::from(&mint_x),
+            Seed::from(&mint_y),
+            Seed::from(&fee_binding),
+            Seed::from(&binding),
+        ];
+        CreatePda {
+            from: update_authority,
+            to: new_registry,
+            lamports: Rent::get()?.minimum_balance(Registry::LEN),
+            space: Registry::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&seeds)])?;
+
+        Registry::from_account_info_unchecked(new_registry).set_config(config.key());
+
+        unsafe {
+            *update_authority.borrow_mut_lamports_unchecked() += registry.lamports();
+            *registry.borrow_mut_lamports_unchecked() = 0;
+        }
+        registry.realloc(0, false)?;
+        registry.assign(&pinocchio_system::ID);
+    }
+
+    config_account.set_fee(fee);
+    config_account.set_protocol_fee(protocol_fee);
+    config_account.set_max_referral_fee(max_referral_fee);
+    config_account.set_max_price_impact(max_price_impact);
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{constants::MAX_SWAP_FEE, error::AmmError, state::Factory};
+
+/// # SetFeeTier
+///
+/// -- Data scheme --
+/// > Fee: u16
+/// > Enabled: bool
+///
+/// -- Instruction Logic --
+/// Adds Fee to the tiers pools can be initialized with, or removes it.
+/// Existing pools of a removed tier are left untouched.
+///
+/// -- Client Side Logic --
+/// Accounts are [admin, factory].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The factory admin signs, Fee is not above MAX_SWAP_FEE and a slot
+/// is left.
+pub fn set_fee_tier(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [admin, factory] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !admin.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 3 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (fee, enabled) = unsafe {
+        (
+            u16::from_le_bytes(*(data.as_ptr() as *const [u8; 2])),
+            *data.as_ptr().add(2) != 0,
+        )
+    };
+
+    // Checks
+    let factory_account = Factory::from_account_info(factory)?;
+    if admin.key() != &factory_account.admin() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+    if fee > MAX_SWAP_FEE {
+        return Err(AmmError::InvalidFee.into());
+    }
+
+    if !enabled {
+        factory_account.remove_fee_tier(fee);
+    } else if !factory_account.add_fee_tier(fee) {
+        return Err(AmmError::InvalidFeeTier.into());
+    }
+
+    Ok(())
+}
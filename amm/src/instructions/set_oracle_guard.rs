@@ -0,0 +1,62 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{constants::MAX_FEE, error::AmmError, state::Config};
+
+/// # SetOracleGuard
+///
+/// -- Data scheme --
+/// > PriceFeed: Pubkey
+/// > MaxDeviation: u16
+/// > MaxStaleness: u64
+///
+/// -- Instruction Logic --
+/// Makes Swap check the spot price of the pool against PriceFeed, rejecting
+/// swaps leaving it more than MaxDeviation bps away or reading a feed
+/// published more than MaxStaleness slots ago. The default key lifts the
+/// guard.
+///
+/// -- Client Side Logic --
+/// Accounts are [update_authority, config]. The price feed follows the
+/// PriceFeed layout, its price being the one of X in Y in base units.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The update authority signs and a guarded pool has a nonzero MaxDeviation
+/// of at most MAX_FEE.
+pub fn set_oracle_guard(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [update_authority, config] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !update_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 42 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (price_feed, max_deviation, max_staleness) = unsafe {
+        (
+            &*(data.as_ptr() as *const Pubkey),
+            u16::from_le_bytes(*(data.as_ptr().add(32) as *const [u8; 2])),
+            u64::from_le_bytes(*(data.as_ptr().add(34) as *const [u8; 8])),
+        )
+    };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if update_authority.key() != &config_account.update_authority() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+    if price_feed != &Pubkey::default() && (max_deviation == 0 || max_deviation > MAX_FEE) {
+        return Err(AmmError::InvalidOracle.into());
+    }
+
+    config_account.set_oracle_guard(price_feed, max_deviation, max_staleness);
+
+    Ok(())
+}
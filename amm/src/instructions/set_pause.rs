@@ -0,0 +1,58 @@
+use crate::{
+    constants::{EMERGENCY_MODE, PAUSE_DEPOSITS, PAUSE_SWAPS, PAUSE_WITHDRAWALS},
+    error::AmmError,
+    state::Config,
+};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// # SetPause
+///
+/// -- Data scheme --
+/// > Status: u8
+///
+/// -- Instruction Logic --
+/// Replaces the pause flags of the pool. Swaps, deposits and withdrawals are
+/// paused independently, EMERGENCY_MODE pausing everything but withdrawals.
+///
+/// -- Client Side Logic --
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The signer is the update authority or the pause guardian, which cannot
+/// pause withdrawals. Withdrawals cannot be paused in emergency mode.
+pub fn set_pause(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [authority, config] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    let status = *data.first().ok_or(ProgramError::InvalidInstructionData)?;
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+
+    if status & !(PAUSE_SWAPS | PAUSE_DEPOSITS | PAUSE_WITHDRAWALS | EMERGENCY_MODE) != 0
+        || (status & EMERGENCY_MODE != 0 && status & PAUSE_WITHDRAWALS != 0)
+    {
+        return Err(AmmError::InvalidStatus.into());
+    }
+
+    if authority.key() == &config_account.update_authority() {
+        // The update authority can set any status
+    } else if authority.key() == &config_account.pause_guardian() {
+        if status & PAUSE_WITHDRAWALS != 0 {
+            return Err(AmmError::InvalidAuthority.into());
+        }
+    } else {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+
+    config_account.set_status(status);
+
+    Ok(())
+}
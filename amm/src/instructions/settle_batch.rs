@@ -0,0 +1,99 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    constants::{FLASH_LOAN_NONE, PAUSE_SWAPS},
+    curve,
+    error::AmmError,
+    state::{Config, Round, Twamm},
+};
+
+/// # SettleBatch
+///
+/// -- Instruction Logic --
+/// Executes the long-term orders of the pool, then clears every swap
+/// revealed in the round at a single price. Opposite swaps are matched
+/// against each other and only the imbalance is swapped through the curve,
+/// see `curve::batch_swap`, the fee and the protocol share of it being taken
+/// on that net swap alone. Each side then shares what the round owes it pro
+/// rata, paid out by ClaimBatch. Rounds not settled within
+/// BATCH_TIMEOUT_SLOTS of the end of their reveal window never are, their
+/// swaps being refunded by ClaimBatch instead.
+///
+/// -- Client Side Logic --
+/// Accounts are [config, round], followed by the TWAMM of the pool once it
+/// has one. Anyone can settle a round once its reveal window is over.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// Swaps are not paused and no flash loan is outstanding. The round belongs
+/// to the pool, its reveal window is over, it is not settled yet and it has
+/// not timed out. The net swap does not move the spot price by more than the
+/// MaxPriceImpact of the pool, rounds that would being refunded once they
+/// time out.
+pub fn settle_batch(accounts: &[AccountInfo]) -> ProgramResult {
+    let [config, round, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if config_account.is_paused(PAUSE_SWAPS) {
+        return Err(AmmError::Locked.into());
+    }
+    if config_account.flash_loan() != FLASH_LOAN_NONE {
+        return Err(AmmError::FlashLoanActive.into());
+    }
+    let twamm = Twamm::from_config(&config_account, remaining)?;
+    let round_account = Round::from_account_info(round)?;
+    let clock = Clock::get()?;
+    if round_account.config() != *config.key()
+        || clock.slot < round_account.reveal_end()
+        || round_account.settled()
+        || round_account.timed_out(clock.slot)
+    {
+        return Err(AmmError::InvalidBatch.into());
+    }
+
+    // Execute the long-term orders up to now
+    let now = clock.unix_timestamp;
+    if let Some(twamm) = twamm {
+        twamm.execute(&config_account, clock.slot, now)?;
+    }
+
+    // Swap the imbalance of the round
+    let (total_x, total_y) = (round_account.total_x(), round_account.total_y());
+    let (in_x, amount_in, amount_out, fee) =
+        curve::batch_swap(&config_account, total_x, total_y, now)?;
+    if amount_in != 0 {
+        let (reserve_x, reserve_y) = (config_account.reserve_x(), config_account.reserve_y());
+        config_account.update_oracle(reserve_x, reserve_y, now)?;
+        let protocol_fee = config_account.accrue_protocol_fee(!in_x, fee)?;
+        config_account.apply_swap(in_x, amount_in, amount_out + protocol_fee)?;
+        curve::check_price_impact(&config_account, reserve_x, reserve_y, now)?;
+    }
+
+    // The side in excess gets the other side and the swap output, the other
+    // side what is left of the excess
+    let (out_x, out_y) = if in_x {
+        let out_y = total_y
+            .checked_add(amount_out)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        (total_x - amount_in, out_y)
+    } else {
+        let out_x = total_x
+            .checked_add(amount_out)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        (out_x, total_y - amount_in)
+    };
+    round_account.settle(out_x, out_y);
+    config_account.release_batch(total_x, total_y)?;
+    config_account.hold_batch(out_x, out_y)?;
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::Transfer;
+
+use crate::{
+    constants::STAKE_SEED,
+    error::AmmError,
+    state::{Farm, Stake},
+    system::CreatePda,
+};
+
+/// # StakeLp
+///
+/// -- Data scheme --
+/// > Amount: u64
+///
+/// -- Instruction Logic --
+/// Moves Amount of LP tokens to the stake vault of the farm. The stake PDA
+/// [STAKE_SEED, farm, user] is created on the first stake, the rewards
+/// earned by the previous stake are settled before it grows.
+///
+/// -- Client Side Logic --
+/// Accounts are [user, stake, farm, user_lp, stake_vault, system_program,
+/// token_program].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The stake address matches its derivation and the stake vault is the one
+/// of the farm.
+pub fn stake_lp(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [user, stake, farm, user_lp, stake_vault, _system_program, _token_program] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 8 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let amount = unsafe { u64::from_le_bytes(*(data.as_ptr() as *const [u8; 8])) };
+
+    // Checks
+    let farm_account = Farm::from_account_info(farm)?;
+    if stake_vault.key() != &farm_account.stake_vault() {
+        return Err(AmmError::InvalidVault.into());
+    }
+    let (stake_key, stake_bump) =
+        find_program_address(&[STAKE_SEED, farm.key(), user.key()], &crate::ID);
+    if stake.key() != &stake_key {
+        return Err(AmmError::InvalidStake.into());
+    }
+
+    // Create Stake on the first stake
+    if stake.data_len() == 0 {
+        let binding = [stake_bump];
+        let seeds = [
+            Seed::from(STAKE_SEED),
+            Seed::from(farm.key()),
+            Seed::from(user.key()),
+            Seed::from(&binding),
+        ];
+        CreatePda {
+            from: user,
+            to: stake,
+            lamports: Rent::get()?.minimum_balance(Stake::LEN),
+            space: Stake::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&seeds)])?;
+
+        Stake::from_account_info_unchecked(stake).set_accounts(farm.key(), user.key());
+    }
+    let stake_account = Stake::from_account_info(stake)?;
+
+    // Settle the rewards earned so far
+    farm_account.update(Clock::get()?.unix_timestamp)?;
+    stake_account.settle(&farm_account)?;
+
+    stake_account.set_amount(
+        stake_account
+            .amount()
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+    );
+    farm_account.set_total_staked(
+        farm_account
+            .total_staked()
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+    );
+
+    // Stake LP Tokens
+    Transfer {
+        from: user_lp,
+        to: stake_vault,
+        authority: user,
+        amount,
+    }
+    .invoke()?;
+
+    Ok(())
+}
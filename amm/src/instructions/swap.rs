@@ -1,6 +1,3 @@
-use constant_product_curve::{
-    delta_x_from_y_swap_amount_with_fee, delta_y_from_x_swap_amount_with_fee,
-};
 use pinocchio::{
     account_info::AccountInfo,
     instruction::{Seed, Signer},
@@ -8,9 +5,15 @@ use pinocchio::{
     sysvars::{clock::Clock, Sysvar},
     ProgramResult,
 };
-use pinocchio_token::{instructions::Transfer, state::TokenAccount};
 
-use crate::state::Config;
+use crate::{
+    constants::{FLASH_LOAN_NONE, MAX_FEE, PAUSE_SWAPS},
+    curve,
+    error::AmmError,
+    events::SwapEvent,
+    state::{Config, PriceFeed, Twamm},
+    token::{self, TransferChecked, TransferFee, Unwrap},
+};
 
 /// # Swap
 ///
@@ -18,65 +21,180 @@ use crate::state::Config;
 /// Amount: u64
 /// MinAmount: u64
 /// Expiration: i64
+/// ReferralFee: Option<u16>
+/// Proof: [[u8; 32]]
 ///
 /// -- Instruction Logic --
+/// Executes the long-term orders of the pool, then quotes the swap on the
+/// amount received by vault_from, net of the transfer fee of the mint if it
+/// has one. Weighted pools quote with the weights of their schedule at the
+/// Clock timestamp. With a referrer, ReferralFee bps of the swap fee are paid
+/// to it before the protocol takes its share of the rest. SOL is wrapped
+/// into or unwrapped from the vault of the wrapped SOL mint when the user
+/// passes its wallet in place of its token account. Pools with an oracle
+/// guard check the spot price after the swap against their price feed.
+/// Logs a SwapEvent.
+/// Allowlisted pools take the merkle proof of the user key after the other
+/// fields, ReferralFee included when there is a referrer.
 ///
 /// -- Client Side Logic --
+/// Accounts are [user, authority, user_x, user_y, vault_from, vault_to,
+/// config, mint_x, mint_y, token_program_x, token_program_y], the token
+/// program of each mint being either the Token program or Token-2022,
+/// followed, when user_x or user_y is the user wallet, by the system program
+/// and the native account PDA [NATIVE_SEED, config], then optionally by the
+/// referrer token account of the mint going out and, once the pool has them,
+/// by its TWAMM and its price feed, in any order.
 ///
 /// -- Account Optimization Logic --
 ///
 /// -- Checks --
-///
+/// MinAmount is checked against the amount received by the user, net of the
+/// transfer fee of the mint going out. ReferralFee is not above the
+/// MaxReferralFee of the pool. The spot price does not move by more than the
+/// MaxPriceImpact of the pool. The user is on the allowlist of the pool, if
+/// it has one. Pools in batch mode only swap through CommitSwap. The oracle
+/// guard of the pool, if it has one, holds as in
+/// `curve::check_oracle_price`.
 pub fn swap(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
-    let [user, authority, user_x, user_y, vault_from, vault_to, config, _token_program] = accounts
+    let [user, authority, user_x, user_y, vault_from, vault_to, config, mint_x, mint_y, token_program_x, token_program_y, remaining @ ..] =
+        accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
     // Deserialize Data
+    if data.len() < 24 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
     let (amount, min, expiration) = unsafe {
         let [amount, min, expiration] = *(data.as_ptr() as *const [u64; 3]);
         (amount, min, expiration as i64)
     };
 
     // Checks
-    let config_account = Config::from_account_info(config);
-    assert_ne!(config_account.get_status(), 1);
-    assert!(expiration < Clock::get()?.unix_timestamp);
+    let (native_x, native_y) = (
+        token::is_native(user, user_x, mint_x)?,
+        token::is_native(user, user_y, mint_y)?,
+    );
+    let (native_account, remaining) = match native_x || native_y {
+        true => {
+            let [_system_program, native_account, remaining @ ..] = remaining else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            (Some(native_account), remaining)
+        }
+        false => (None, remaining),
+    };
+    let config_account = Config::from_account_info(config)?;
+    let twamm = Twamm::from_config(&config_account, remaining)?;
+    let price_feed = PriceFeed::from_config(&config_account, remaining)?;
+    let referrer = match remaining.iter().find(|account| {
+        account.key() != &config_account.twamm() && account.key() != &config_account.price_feed()
+    }) {
+        Some(referrer) => {
+            if data.len() < 26 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let referral_fee =
+                unsafe { u16::from_le_bytes(*(data.as_ptr().add(24) as *const [u8; 2])) };
+            Some((referrer, referral_fee))
+        }
+        None => None,
+    };
+    let proof = &data[if referrer.is_some() { 26 } else { 24 }..];
+    if config_account.is_paused(PAUSE_SWAPS) {
+        return Err(AmmError::Locked.into());
+    }
+    if config_account.flash_loan() != FLASH_LOAN_NONE {
+        return Err(AmmError::FlashLoanActive.into());
+    }
+    if config_account.is_batched() {
+        return Err(AmmError::BatchOnly.into());
+    }
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    if now > expiration {
+        return Err(AmmError::Expired.into());
+    }
+    if referrer.is_some_and(|(_, referral_fee)| referral_fee > config_account.max_referral_fee()) {
+        return Err(AmmError::InvalidFee.into());
+    }
+    config_account.check_allowlist(user.key(), proof)?;
 
     let is_x = vault_from.key().eq(&config_account.vault_x());
-    if is_x {
-        assert_eq!(vault_to.key(), &config_account.vault_y());
+    let (expected_from, expected_to) = if is_x {
+        (config_account.vault_x(), config_account.vault_y())
     } else {
-        assert_eq!(vault_to.key(), &config_account.vault_x());
-        assert_eq!(vault_from.key(), &config_account.vault_y());
+        (config_account.vault_y(), config_account.vault_x())
+    };
+    if vault_from.key() != &expected_from || vault_to.key() != &expected_to {
+        return Err(AmmError::InvalidVault.into());
     }
+    token::check_mint(mint_x, &config_account.mint_x(), token_program_x)?;
+    token::check_mint(mint_y, &config_account.mint_y(), token_program_y)?;
+    let (mint_from, mint_to, token_program_from, token_program_to) = if is_x {
+        (mint_x, mint_y, token_program_x, token_program_y)
+    } else {
+        (mint_y, mint_x, token_program_y, token_program_x)
+    };
+    let (user_from, user_to, native_from) = if is_x {
+        (user_x, user_y, native_x)
+    } else {
+        (user_y, user_x, native_y)
+    };
 
-    // Calculate the amount of LP tokens to mint and the amount of tokens to deposit
-    let vault_from_amount =
-        unsafe { TokenAccount::from_account_info_unchecked(vault_from)?.amount() };
-    let vault_to_amount = unsafe { TokenAccount::from_account_info_unchecked(vault_to)?.amount() };
+    // Execute the long-term orders up to now
+    if let Some(twamm) = twamm {
+        twamm.execute(&config_account, clock.slot, now)?;
+    }
 
-    // Determine swap direction and fee calculation
-    let (amount_out, _) = if is_x {
-        delta_y_from_x_swap_amount_with_fee(
-            vault_from_amount,
-            vault_to_amount,
-            amount,
-            config_account.fee(),
-        )
+    // Read the reserves, accrued protocol fees and donations to the vaults are not part of them
+    let (reserve_x, reserve_y) = (config_account.reserve_x(), config_account.reserve_y());
+    let (vault_from_amount, vault_to_amount) = if is_x {
+        (reserve_x, reserve_y)
     } else {
-        delta_x_from_y_swap_amount_with_fee(
-            vault_from_amount,
-            vault_to_amount,
-            amount,
-            config_account.fee(),
-        )
+        (reserve_y, reserve_x)
+    };
+
+    // Accumulate the price before the reserves move
+    config_account.update_oracle(reserve_x, reserve_y, now)?;
+
+    // Quote on the amount the vault actually receives
+    let amount_in = TransferFee::from_account_info(mint_from, clock.epoch).amount_received(amount);
+    let (amount_out, fee) = curve::swap_amount_out(
+        &config_account,
+        is_x,
+        vault_from_amount,
+        vault_to_amount,
+        amount_in,
+        now,
+    )?;
+
+    // Slippage check on the amount the user actually receives
+    if TransferFee::from_account_info(mint_to, clock.epoch).amount_received(amount_out) < min {
+        return Err(AmmError::SlippageExceeded.into());
     }
-    .map_err(|_| ProgramError::ArithmeticOverflow)?;
 
-    // Slippage check
-    assert!(amount_out >= min);
+    // The fee is paid in the token going out, the referrer is paid first
+    let referral_fee = referrer.map_or(0, |(_, referral_fee)| {
+        (fee as u128 * referral_fee as u128 / MAX_FEE as u128) as u64
+    });
+    // The protocol keeps its share of the rest in the vault
+    let protocol_fee = config_account.accrue_protocol_fee(!is_x, fee - referral_fee)?;
+    // The rest of the fee stays in the reserves for the LPs
+    config_account.apply_swap(is_x, amount_in, amount_out + referral_fee + protocol_fee)?;
+    curve::check_price_impact(&config_account, reserve_x, reserve_y, now)?;
+    if let Some(price_feed) = price_feed {
+        curve::check_oracle_price(
+            &config_account,
+            &price_feed,
+            reserve_x,
+            reserve_y,
+            clock.slot,
+            now,
+        )?;
+    }
 
     // Derive the signer
     let binding = [config_account.authority_bump()];
@@ -84,21 +202,70 @@ pub fn swap(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     let signer = [Signer::from(&seeds)];
 
     // Deposit Tokens
-    Transfer {
-        from: if is_x { user_x } else { user_y },
-        to: vault_from,
-        authority: user,
-        amount,
+    if native_from {
+        token::wrap(user, vault_from, amount)?;
+    } else {
+        TransferChecked {
+            from: user_from,
+            mint: mint_from,
+            to: vault_from,
+            authority: user,
+            token_program: token_program_from,
+            amount,
+            decimals: token::decimals(mint_from)?,
+        }
+        .invoke()?;
+    }
+
+    match native_account.filter(|_| !native_from) {
+        Some(native_account) => Unwrap {
+            from: vault_to,
+            account: native_account,
+            mint: mint_to,
+            authority,
+            wallet: user,
+            token_program: token_program_to,
+            config: config.key(),
+            amount: amount_out,
+        }
+        .invoke_signed(&signer)?,
+        None => TransferChecked {
+            from: vault_to,
+            mint: mint_to,
+            to: user_to,
+            authority,
+            token_program: token_program_to,
+            amount: amount_out,
+            decimals: token::decimals(mint_to)?,
+        }
+        .invoke_signed(&signer)?,
+    }
+
+    // Pay the Referrer
+    if let Some((referrer, _)) = referrer.filter(|_| referral_fee > 0) {
+        TransferChecked {
+            from: vault_to,
+            mint: mint_to,
+            to: referrer,
+            authority,
+            token_program: token_program_to,
+            amount: referral_fee,
+            decimals: token::decimals(mint_to)?,
+        }
+        .invoke_signed(&signer)?;
     }
-    .invoke()?;
 
-    Transfer {
-        from: vault_to,
-        to: if is_x { user_y } else { user_x },
-        authority,
-        amount: amount_out,
+    SwapEvent {
+        pool: config.key(),
+        user: user.key(),
+        in_x: is_x,
+        amount_in,
+        amount_out,
+        fee,
+        reserve_x: config_account.reserve_x(),
+        reserve_y: config_account.reserve_y(),
     }
-    .invoke_signed(&signer)?;
+    .emit();
 
     Ok(())
 }
@@ -0,0 +1,230 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    constants::{FLASH_LOAN_NONE, PAUSE_SWAPS},
+    curve,
+    error::AmmError,
+    events::SwapEvent,
+    state::{Config, PriceFeed, Twamm},
+    token::{self, TransferChecked, TransferFee, Unwrap},
+};
+
+/// # SwapExactOut
+///
+/// -- Data scheme --
+/// Amount: u64
+/// MaxAmount: u64
+/// Expiration: i64
+/// Proof: [[u8; 32]]
+///
+/// -- Instruction Logic --
+/// Executes the long-term orders of the pool, then transfers exactly Amount
+/// out of vault_to, charging the user the amount of vault_from tokens quoted
+/// by the curve, fee included. Transfer fees are added on top of both legs so
+/// the user receives Amount and vault_from the quoted amount. SOL is wrapped
+/// or unwrapped as in Swap when the user passes its wallet in place of its
+/// token account. Logs a SwapEvent. Pools with an oracle guard check the spot
+/// price after the swap against their price feed. Allowlisted pools take the
+/// merkle proof of the user key after the other fields.
+///
+/// -- Client Side Logic --
+/// Accounts are [user, authority, user_x, user_y, vault_from, vault_to,
+/// config, mint_x, mint_y, token_program_x, token_program_y], as in Swap,
+/// followed, when user_x or user_y is the user wallet, by the system program
+/// and the native account PDA [NATIVE_SEED, config], then by the TWAMM and
+/// the price feed of the pool once it has them, in any order.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// MaxAmount is checked against the amount sent by the user, transfer fee
+/// included. The spot price does not move by more than the MaxPriceImpact of
+/// the pool. The user is on the allowlist of the pool, if it has one. Pools
+/// in batch mode only swap through CommitSwap. The oracle guard of the pool,
+/// if it has one, holds as in `curve::check_oracle_price`.
+pub fn swap_exact_out(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [user, authority, user_x, user_y, vault_from, vault_to, config, mint_x, mint_y, token_program_x, token_program_y, remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Deserialize Data
+    if data.len() < 24 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (amount_out, max, expiration) = unsafe {
+        let [amount_out, max, expiration] = *(data.as_ptr() as *const [u64; 3]);
+        (amount_out, max, expiration as i64)
+    };
+
+    // Checks
+    let (native_x, native_y) = (
+        token::is_native(user, user_x, mint_x)?,
+        token::is_native(user, user_y, mint_y)?,
+    );
+    let (native_account, remaining) = match native_x || native_y {
+        true => {
+            let [_system_program, native_account, remaining @ ..] = remaining else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            (Some(native_account), remaining)
+        }
+        false => (None, remaining),
+    };
+    let config_account = Config::from_account_info(config)?;
+    let price_feed = PriceFeed::from_config(&config_account, remaining)?;
+    if config_account.is_paused(PAUSE_SWAPS) {
+        return Err(AmmError::Locked.into());
+    }
+    if config_account.flash_loan() != FLASH_LOAN_NONE {
+        return Err(AmmError::FlashLoanActive.into());
+    }
+    if config_account.is_batched() {
+        return Err(AmmError::BatchOnly.into());
+    }
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    if now > expiration {
+        return Err(AmmError::Expired.into());
+    }
+    config_account.check_allowlist(user.key(), &data[24..])?;
+
+    let is_x = vault_from.key().eq(&config_account.vault_x());
+    let (expected_from, expected_to) = if is_x {
+        (config_account.vault_x(), config_account.vault_y())
+    } else {
+        (config_account.vault_y(), config_account.vault_x())
+    };
+    if vault_from.key() != &expected_from || vault_to.key() != &expected_to {
+        return Err(AmmError::InvalidVault.into());
+    }
+    token::check_mint(mint_x, &config_account.mint_x(), token_program_x)?;
+    token::check_mint(mint_y, &config_account.mint_y(), token_program_y)?;
+    let (mint_from, mint_to, token_program_from, token_program_to) = if is_x {
+        (mint_x, mint_y, token_program_x, token_program_y)
+    } else {
+        (mint_y, mint_x, token_program_y, token_program_x)
+    };
+    let (user_from, user_to, native_from) = if is_x {
+        (user_x, user_y, native_x)
+    } else {
+        (user_y, user_x, native_y)
+    };
+
+    // Execute the long-term orders up to now
+    if let Some(twamm) = Twamm::from_config(&config_account, remaining)? {
+        twamm.execute(&config_account, clock.slot, now)?;
+    }
+
+    // Read the reserves, accrued protocol fees and donations to the vaults are not part of them
+    let (reserve_x, reserve_y) = (config_account.reserve_x(), config_account.reserve_y());
+    let (vault_from_amount, vault_to_amount) = if is_x {
+        (reserve_x, reserve_y)
+    } else {
+        (reserve_y, reserve_x)
+    };
+
+    // Accumulate the price before the reserves move
+    config_account.update_oracle(reserve_x, reserve_y, now)?;
+
+    // Quote the amount in for the requested amount out, fee included
+    let amount_sent =
+        TransferFee::from_account_info(mint_to, clock.epoch).amount_to_send(amount_out)?;
+    let (amount_in, fee) = curve::swap_amount_in(
+        &config_account,
+        is_x,
+        vault_from_amount,
+        vault_to_amount,
+        amount_sent,
+        now,
+    )?;
+    let amount =
+        TransferFee::from_account_info(mint_from, clock.epoch).amount_to_send(amount_in)?;
+
+    // Slippage check
+    if amount > max {
+        return Err(AmmError::SlippageExceeded.into());
+    }
+
+    // The fee is paid in the token going out, the protocol keeps its share in the vault
+    let protocol_fee = config_account.accrue_protocol_fee(!is_x, fee)?;
+    // The rest of the fee stays in the reserves for the LPs
+    config_account.apply_swap(is_x, amount_in, amount_sent + protocol_fee)?;
+    curve::check_price_impact(&config_account, reserve_x, reserve_y, now)?;
+    if let Some(price_feed) = price_feed {
+        curve::check_oracle_price(
+            &config_account,
+            &price_feed,
+            reserve_x,
+            reserve_y,
+            clock.slot,
+            now,
+        )?;
+    }
+
+    // Derive the signer
+    let binding = [config_account.authority_bump()];
+    let seeds = [Seed::from(config.key().as_ref()), Seed::from(&binding)];
+    let signer = [Signer::from(&seeds)];
+
+    // Deposit Tokens
+    if native_from {
+        token::wrap(user, vault_from, amount)?;
+    } else {
+        TransferChecked {
+            from: user_from,
+            mint: mint_from,
+            to: vault_from,
+            authority: user,
+            token_program: token_program_from,
+            amount,
+            decimals: token::decimals(mint_from)?,
+        }
+        .invoke()?;
+    }
+
+    match native_account.filter(|_| !native_from) {
+        Some(native_account) => Unwrap {
+            from: vault_to,
+            account: native_account,
+            mint: mint_to,
+            authority,
+            wallet: user,
+            token_program: token_program_to,
+            config: config.key(),
+            amount: amount_sent,
+        }
+        .invoke_signed(&signer)?,
+        None => TransferChecked {
+            from: vault_to,
+            mint: mint_to,
+            to: user_to,
+            authority,
+            token_program: token_program_to,
+            amount: amount_sent,
+            decimals: token::decimals(mint_to)?,
+        }
+        .invoke_signed(&signer)?,
+    }
+
+    SwapEvent {
+        pool: config.key(),
+        user: user.key(),
+        in_x: is_x,
+        amount_in,
+        amount_out: amount_sent,
+        fee,
+        reserve_x: config_account.reserve_x(),
+        reserve_y: config_account.reserve_y(),
+    }
+    .emit();
+
+    Ok(())
+}
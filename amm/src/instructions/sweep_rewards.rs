@@ -0,0 +1,92 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::AmmError,
+    state::{Config, Farm},
+    token::{self, TransferChecked},
+};
+
+/// # SweepRewards
+///
+/// -- Instruction Logic --
+/// Sweeps the reward vault of an ended farm to a token account of the update
+/// authority, leaving the rewards owed to the stakes in it. This recovers the
+/// unallocated rewards and the rounding dust of the emission. Transfer fees
+/// are paid out of the swept amount.
+///
+/// -- Client Side Logic --
+/// Accounts are [update_authority, authority, farm, config, authority_reward,
+/// reward_vault, reward_mint, reward_token_program].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The update authority of the pool signs, the farm belongs to the pool, its
+/// emission is over and no LP token is staked in it.
+pub fn sweep_rewards(accounts: &[AccountInfo]) -> ProgramResult {
+    let [update_authority, authority, farm, config, authority_reward, reward_vault, reward_mint, reward_token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !update_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    if update_authority.key() != &config_account.update_authority() {
+        return Err(AmmError::InvalidAuthority.into());
+    }
+    let farm_account = Farm::from_account_info(farm)?;
+    if farm_account.config() != *config.key() {
+        return Err(AmmError::InvalidFarm.into());
+    }
+    if reward_vault.key() != &farm_account.reward_vault() {
+        return Err(AmmError::InvalidVault.into());
+    }
+    token::check_mint(
+        reward_mint,
+        &farm_account.reward_mint(),
+        reward_token_program,
+    )?;
+
+    // Every reward emitted is settled in a stake once none is staked
+    let now = Clock::get()?.unix_timestamp;
+    if now < farm_account.reward_end() || farm_account.total_staked() != 0 {
+        return Err(AmmError::FarmActive.into());
+    }
+    farm_account.update(now)?;
+    farm_account.set_unallocated(0);
+
+    let amount = token::amount(reward_vault)?.saturating_sub(farm_account.owed_rewards());
+    if amount == 0 {
+        return Ok(());
+    }
+
+    // Derive the signer
+    let binding = [config_account.authority_bump()];
+    let seeds = [Seed::from(config.key().as_ref()), Seed::from(&binding)];
+    let signer = [Signer::from(&seeds)];
+
+    // Sweep Rewards
+    TransferChecked {
+        from: reward_vault,
+        mint: reward_mint,
+        to: authority_reward,
+        authority,
+        token_program: reward_token_program,
+        amount,
+        decimals: token::decimals(reward_mint)?,
+    }
+    .invoke_signed(&signer)?;
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::Transfer;
+
+use crate::{
+    error::AmmError,
+    state::{Config, Farm, Stake},
+};
+
+/// # UnstakeLp
+///
+/// -- Data scheme --
+/// > Amount: u64
+///
+/// -- Instruction Logic --
+/// Returns Amount of staked LP tokens to the user. The rewards earned so far
+/// are settled and stay claimable.
+///
+/// -- Client Side Logic --
+/// Accounts are [user, authority, stake, farm, config, user_lp, stake_vault,
+/// token_program].
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// The user signs and owns the stake, the farm belongs to the pool and
+/// Amount is not above the stake. Never paused, like Withdraw in emergency
+/// mode.
+pub fn unstake_lp(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [user, authority, stake, farm, config, user_lp, stake_vault, _token_program] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize Data
+    if data.len() < 8 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let amount = unsafe { u64::from_le_bytes(*(data.as_ptr() as *const [u8; 8])) };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    let farm_account = Farm::from_account_info(farm)?;
+    if farm_account.config() != *config.key() {
+        return Err(AmmError::InvalidFarm.into());
+    }
+    if stake_vault.key() != &farm_account.stake_vault() {
+        return Err(AmmError::InvalidVault.into());
+    }
+    let stake_account = Stake::from_account_info(stake)?;
+    if stake_account.farm() != *farm.key() || stake_account.owner() != *user.key() {
+        return Err(AmmError::InvalidStake.into());
+    }
+
+    // Settle the rewards earned so far
+    farm_account.update(Clock::get()?.unix_timestamp)?;
+    stake_account.settle(&farm_account)?;
+
+    stake_account.set_amount(
+        stake_account
+            .amount()
+            .checked_sub(amount)
+            .ok_or(ProgramError::InsufficientFunds)?,
+    );
+    farm_account.set_total_staked(farm_account.total_staked() - amount);
+
+    // Derive the signer
+    let binding = [config_account.authority_bump()];
+    let seeds = [Seed::from(config.key().as_ref()), Seed::from(&binding)];
+    let signer = [Signer::from(&seeds)];
+
+    // Unstake LP Tokens
+    Transfer {
+        from: stake_vault,
+        to: user_lp,
+        authority,
+        amount,
+    }
+    .invoke_signed(&signer)?;
+
+    Ok(())
+}
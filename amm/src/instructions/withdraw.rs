@@ -1,4 +1,3 @@
-use constant_product_curve::xy_withdraw_amounts_from_l;
 use pinocchio::{
     account_info::AccountInfo,
     instruction::{Seed, Signer},
@@ -6,12 +5,16 @@ use pinocchio::{
     sysvars::{clock::Clock, Sysvar},
     ProgramResult,
 };
-use pinocchio_token::{
-    instructions::{Burn, Transfer},
-    state::{Mint, TokenAccount},
-};
+use pinocchio_token::{instructions::Burn, state::Mint};
 
-use crate::state::Config;
+use crate::{
+    constants::{FLASH_LOAN_NONE, MINIMUM_LIQUIDITY, PAUSE_WITHDRAWALS},
+    curve,
+    error::AmmError,
+    events::{LiquidityEvent, WITHDRAW_EVENT},
+    state::{Config, Twamm},
+    token::{self, TransferChecked, TransferFee, Unwrap},
+};
 
 /// # Withdraw
 ///
@@ -22,53 +25,90 @@ use crate::state::Config;
 /// Expiration: i64
 ///
 /// -- Instruction Logic --
+/// Executes the long-term orders of the pool, then burns Amount of LP for a
+/// proportional share of the reserves. SOL is unwrapped from the vault of the
+/// wrapped SOL mint when the user passes its wallet in place of its token
+/// account. Logs a LiquidityEvent under WITHDRAW_EVENT.
 ///
 /// -- Client Side Logic --
+/// Accounts are the fixed ones of Deposit, followed by the system program and
+/// the native account PDA [NATIVE_SEED, config] when user_x or user_y is the
+/// user wallet, then by the TWAMM of the pool once it has one.
 ///
 /// -- Account Optimization Logic --
 ///
 /// -- Checks --
-///
+/// MinX and MinY are checked against the amounts received by the user, net
+/// of transfer fees.
 pub fn withdraw(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
-    let [user, authority, mint_lp, user_x, user_y, user_lp, vault_x, vault_y, config, _token_program] =
+    let [user, authority, mint_lp, user_x, user_y, user_lp, vault_x, vault_y, config, _token_program, mint_x, mint_y, token_program_x, token_program_y, remaining @ ..] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
     // Deserialize Data
+    if data.len() < 32 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
     let (amount, min_x, min_y, expiration) = unsafe {
         let [amount, min_x, min_y, expiration] = *(data.as_ptr() as *const [u64; 4]);
         (amount, min_x, min_y, expiration as i64)
     };
 
     // Checks
-    let config_account = Config::from_account_info(config);
-    assert_ne!(config_account.get_status(), 1);
-    assert_eq!(mint_lp.key(), &config_account.mint_lp());
-    assert_eq!(vault_x.key(), &config_account.vault_x());
-    assert_eq!(vault_y.key(), &config_account.vault_y());
-    assert!(expiration < Clock::get()?.unix_timestamp);
+    let (native_x, native_y) = (
+        token::is_native(user, user_x, mint_x)?,
+        token::is_native(user, user_y, mint_y)?,
+    );
+    let native_account = match (native_x || native_y, remaining) {
+        (true, [_system_program, native_account, ..]) => Some(native_account),
+        (true, _) => return Err(ProgramError::NotEnoughAccountKeys),
+        (false, _) => None,
+    };
+    let config_account = Config::from_account_info(config)?;
+    if config_account.is_paused(PAUSE_WITHDRAWALS) {
+        return Err(AmmError::Locked.into());
+    }
+    if config_account.flash_loan() != FLASH_LOAN_NONE {
+        return Err(AmmError::FlashLoanActive.into());
+    }
+    if mint_lp.key() != &config_account.mint_lp() {
+        return Err(AmmError::InvalidMint.into());
+    }
+    if vault_x.key() != &config_account.vault_x() || vault_y.key() != &config_account.vault_y() {
+        return Err(AmmError::InvalidVault.into());
+    }
+    token::check_mint(mint_x, &config_account.mint_x(), token_program_x)?;
+    token::check_mint(mint_y, &config_account.mint_y(), token_program_y)?;
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    if now > expiration {
+        return Err(AmmError::Expired.into());
+    }
 
-    // Calculate the amount of LP tokens to mint and the amount of tokens to deposit
+    // Execute the long-term orders up to now
+    if let Some(twamm) = Twamm::from_config(&config_account, remaining)? {
+        twamm.execute(&config_account, clock.slot, now)?;
+    }
+
+    // Calculate the amount of tokens to withdraw
     let supply = unsafe { Mint::from_account_info_unchecked(mint_lp)?.supply() };
-    let vault_x_amount = unsafe { TokenAccount::from_account_info_unchecked(vault_x)?.amount() };
-    let vault_y_amount = unsafe { TokenAccount::from_account_info_unchecked(vault_y)?.amount() };
+    let (reserve_x, reserve_y) = (config_account.reserve_x(), config_account.reserve_y());
 
-    let (x, y) = match supply == 0 && vault_x_amount == 0 && vault_y_amount == 0 {
-        true => (min_x, min_y),
-        false => xy_withdraw_amounts_from_l(
-            vault_x_amount,
-            vault_y_amount,
-            supply,
-            amount,
-            1_000_000_000,
-        )
-        .map_err(|_| ProgramError::ArithmeticOverflow)?,
-    };
+    // Accumulate the price before the reserves move
+    config_account.update_oracle(reserve_x, reserve_y, now)?;
+
+    // The locked MINIMUM_LIQUIDITY is part of the supply but can never be burnt
+    let (x, y) = curve::withdraw_amounts(reserve_x, reserve_y, supply + MINIMUM_LIQUIDITY, amount)?;
+
+    if TransferFee::from_account_info(mint_x, clock.epoch).amount_received(x) < min_x
+        || TransferFee::from_account_info(mint_y, clock.epoch).amount_received(y) < min_y
+    {
+        return Err(AmmError::SlippageExceeded.into());
+    }
 
-    assert!(x <= min_x);
-    assert!(y <= min_y);
+    config_account.set_reserves(reserve_x - x, reserve_y - y);
 
     // Derive the signer
     let binding = [config_account.authority_bump()];
@@ -76,21 +116,34 @@ pub fn withdraw(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     let signer = [Signer::from(&seeds)];
 
     // Withdraw Tokens
-    Transfer {
-        from: vault_x,
-        to: user_x,
-        authority,
-        amount: x,
-    }
-    .invoke_signed(&signer.clone())?;
-
-    Transfer {
-        from: vault_y,
-        to: user_y,
-        authority,
-        amount: y,
+    for (native, user_token, mint, vault, token_program, amount) in [
+        (native_x, user_x, mint_x, vault_x, token_program_x, x),
+        (native_y, user_y, mint_y, vault_y, token_program_y, y),
+    ] {
+        match native_account.filter(|_| native) {
+            Some(native_account) => Unwrap {
+                from: vault,
+                account: native_account,
+                mint,
+                authority,
+                wallet: user,
+                token_program,
+                config: config.key(),
+                amount,
+            }
+            .invoke_signed(&signer)?,
+            None => TransferChecked {
+                from: vault,
+                mint,
+                to: user_token,
+                authority,
+                token_program,
+                amount,
+                decimals: token::decimals(mint)?,
+            }
+            .invoke_signed(&signer)?,
+        }
     }
-    .invoke_signed(&signer.clone())?;
 
     // Burn LP Tokens
     Burn {
@@ -101,5 +154,16 @@ pub fn withdraw(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     }
     .invoke()?;
 
+    LiquidityEvent {
+        pool: config.key(),
+        user: user.key(),
+        amount_x: x,
+        amount_y: y,
+        lp: amount,
+        reserve_x: config_account.reserve_x(),
+        reserve_y: config_account.reserve_y(),
+    }
+    .emit(WITHDRAW_EVENT);
+
     Ok(())
 }
@@ -0,0 +1,198 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::{instructions::Burn, state::Mint};
+
+use crate::{
+    constants::{FLASH_LOAN_NONE, MINIMUM_LIQUIDITY, PAUSE_SWAPS, PAUSE_WITHDRAWALS},
+    curve,
+    error::AmmError,
+    events::{LiquidityEvent, WITHDRAW_EVENT},
+    state::{Config, PriceFeed, Twamm},
+    token::{self, TransferChecked, TransferFee},
+};
+
+/// # WithdrawSingle
+///
+/// -- Data scheme --
+/// Amount: u64
+/// MinAmount: u64
+/// Expiration: i64
+///
+/// -- Instruction Logic --
+/// Executes the long-term orders of the pool, then burns Amount of LP for a
+/// single token. The share of the other token is withdrawn as in Withdraw and
+/// swapped through the curve against the reserves left, the user receiving
+/// both parts in the requested token. Pools with an oracle guard check the
+/// spot price after the swap against their price feed. Logs a LiquidityEvent
+/// under WITHDRAW_EVENT for the amount sent out of vault_out.
+///
+/// -- Client Side Logic --
+/// Accounts are [user, authority, mint_lp, user_token, user_lp, vault_out,
+/// vault_other, config, token_program, mint_out, token_program_out],
+/// vault_out being the vault of the requested token, followed by the TWAMM
+/// and the price feed of the pool once it has them, in any order.
+///
+/// -- Account Optimization Logic --
+///
+/// -- Checks --
+/// Both withdrawals and swaps are unpaused. MinAmount is checked against the
+/// amount received by the user, net of the transfer fee. The swap does not
+/// move the spot price by more than the MaxPriceImpact of the pool. The pool
+/// is not in batch mode, the swap half of the withdrawal being open to
+/// sandwiching. The oracle guard of the pool, if it has one, holds as in
+/// `curve::check_oracle_price`.
+pub fn withdraw_single(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [user, authority, mint_lp, user_token, user_lp, vault_out, vault_other, config, _token_program, mint_out, token_program_out, remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Deserialize Data
+    if data.len() < 24 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (amount, min, expiration) = unsafe {
+        let [amount, min, expiration] = *(data.as_ptr() as *const [u64; 3]);
+        (amount, min, expiration as i64)
+    };
+
+    // Checks
+    let config_account = Config::from_account_info(config)?;
+    let price_feed = PriceFeed::from_config(&config_account, remaining)?;
+    if config_account.is_paused(PAUSE_WITHDRAWALS) || config_account.is_paused(PAUSE_SWAPS) {
+        return Err(AmmError::Locked.into());
+    }
+    if config_account.flash_loan() != FLASH_LOAN_NONE {
+        return Err(AmmError::FlashLoanActive.into());
+    }
+    if config_account.is_batched() {
+        return Err(AmmError::BatchOnly.into());
+    }
+    if mint_lp.key() != &config_account.mint_lp() {
+        return Err(AmmError::InvalidMint.into());
+    }
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    if now > expiration {
+        return Err(AmmError::Expired.into());
+    }
+
+    let is_x = vault_out.key().eq(&config_account.vault_x());
+    let (expected_out, expected_other, expected_mint) = if is_x {
+        (
+            config_account.vault_x(),
+            config_account.vault_y(),
+            config_account.mint_x(),
+        )
+    } else {
+        (
+            config_account.vault_y(),
+            config_account.vault_x(),
+            config_account.mint_y(),
+        )
+    };
+    if vault_out.key() != &expected_out || vault_other.key() != &expected_other {
+        return Err(AmmError::InvalidVault.into());
+    }
+    token::check_mint(mint_out, &expected_mint, token_program_out)?;
+
+    // Execute the long-term orders up to now
+    if let Some(twamm) = Twamm::from_config(&config_account, remaining)? {
+        twamm.execute(&config_account, clock.slot, now)?;
+    }
+
+    let supply = unsafe { Mint::from_account_info_unchecked(mint_lp)?.supply() };
+    let (reserve_x, reserve_y) = (config_account.reserve_x(), config_account.reserve_y());
+
+    // Accumulate the price before the reserves move
+    config_account.update_oracle(reserve_x, reserve_y, now)?;
+
+    // Withdraw both shares, the locked MINIMUM_LIQUIDITY is part of the supply
+    let (x, y) = curve::withdraw_amounts(reserve_x, reserve_y, supply + MINIMUM_LIQUIDITY, amount)?;
+    config_account.set_reserves(reserve_x - x, reserve_y - y);
+
+    // Swap the other share back into the pool for the requested token
+    let (share_out, share_other, reserve_out, reserve_other) = if is_x {
+        (x, y, reserve_x - x, reserve_y - y)
+    } else {
+        (y, x, reserve_y - y, reserve_x - x)
+    };
+    let (amount_swapped, fee) = curve::swap_amount_out(
+        &config_account,
+        !is_x,
+        reserve_other,
+        reserve_out,
+        share_other,
+        now,
+    )?;
+
+    let protocol_fee = config_account.accrue_protocol_fee(is_x, fee)?;
+    config_account.apply_swap(!is_x, share_other, amount_swapped + protocol_fee)?;
+    curve::check_price_impact(&config_account, reserve_x - x, reserve_y - y, now)?;
+    if let Some(price_feed) = price_feed {
+        curve::check_oracle_price(
+            &config_account,
+            &price_feed,
+            reserve_x - x,
+            reserve_y - y,
+            clock.slot,
+            now,
+        )?;
+    }
+
+    // Slippage check on the amount the user actually receives
+    let amount_out = share_out + amount_swapped;
+    if TransferFee::from_account_info(mint_out, clock.epoch).amount_received(amount_out) < min {
+        return Err(AmmError::SlippageExceeded.into());
+    }
+
+    // Derive the signer
+    let binding = [config_account.authority_bump()];
+    let seeds = [Seed::from(config.key().as_ref()), Seed::from(&binding)];
+    let signer = [Signer::from(&seeds)];
+
+    // Withdraw Tokens
+    TransferChecked {
+        from: vault_out,
+        mint: mint_out,
+        to: user_token,
+        authority,
+        token_program: token_program_out,
+        amount: amount_out,
+        decimals: token::decimals(mint_out)?,
+    }
+    .invoke_signed(&signer)?;
+
+    // Burn LP Tokens
+    Burn {
+        mint: mint_lp,
+        token: user_lp,
+        authority: user,
+        amount,
+    }
+    .invoke()?;
+
+    let (amount_x, amount_y) = if is_x {
+        (amount_out, 0)
+    } else {
+        (0, amount_out)
+    };
+    LiquidityEvent {
+        pool: config.key(),
+        user: user.key(),
+        amount_x,
+        amount_y,
+        lp: amount,
+        reserve_x: config_account.reserve_x(),
+        reserve_y: config_account.reserve_y(),
+    }
+    .emit(WITHDRAW_EVENT);
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! Read-only view over the serialized Instructions sysvar.
+//!
+//! The account data is laid out as
+//! `[count: u16][offsets: [u16; count]][instructions..][current index: u16]`
+//! where every instruction is
+//! `[account count: u16][(flags: u8, key: Pubkey)..][program id: Pubkey][data len: u16][data]`.
+
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::constants::INSTRUCTIONS_SYSVAR_ID;
+
+#[inline(always)]
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, ProgramError> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or(ProgramError::InvalidAccountData)
+}
+
+pub struct Instructions<'a>(&'a [u8]);
+
+impl<'a> Instructions<'a> {
+    pub fn from_account_info(account_info: &'a AccountInfo) -> Result<Self, ProgramError> {
+        if account_info.key() != &INSTRUCTIONS_SYSVAR_ID {
+            return Err(ProgramError::UnsupportedSysvar);
+        }
+        let data = unsafe { account_info.borrow_data_unchecked() };
+        if data.len() < 4 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self(data))
+    }
+
+    pub fn count(&self) -> Result<u16, ProgramError> {
+        read_u16(self.0, 0)
+    }
+
+    /// Index of the top level instruction being executed
+    pub fn current_index(&self) -> Result<u16, ProgramError> {
+        read_u16(self.0, self.0.len() - 2)
+    }
+
+    pub fn get(&self, index: u16) -> Result<Instruction<'a>, ProgramError> {
+        if index >= self.count()? {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let offset = read_u16(self.0, 2 + index as usize * 2)? as usize;
+        let data = self
+            .0
+            .get(offset..)
+            .ok_or(ProgramError::InvalidAccountData)?;
+
+        let accounts_len = read_u16(data, 0)? as usize;
+        let program_id = 2 + accounts_len * 33;
+        let data_len = read_u16(data, program_id + 32)? as usize;
+        let end = program_id + 32 + 2 + data_len;
+        if data.len() < end {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Instruction(&data[..end], accounts_len))
+    }
+}
+
+/// A single serialized instruction and its number of accounts
+pub struct Instruction<'a>(&'a [u8], usize);
+
+impl Instruction<'_> {
+    pub fn program_id(&self) -> &Pubkey {
+        unsafe { &*(self.0.as_ptr().add(2 + self.1 * 33) as *const Pubkey) }
+    }
+
+    /// Key of the account at `index`, if the instruction has that many accounts
+    pub fn account(&self, index: usize) -> Option<&Pubkey> {
+        (index < self.1)
+            .then(|| unsafe { &*(self.0.as_ptr().add(2 + index * 33 + 1) as *const Pubkey) })
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.0[2 + self.1 * 33 + 32 + 2..]
+    }
+}
@@ -3,15 +3,47 @@ use pinocchio::entrypoint;
 use pinocchio::pubkey::Pubkey;
 use pinocchio::{program_error::ProgramError, ProgramResult};
 
+mod constants;
+pub use constants::*;
+
+mod curve;
+
+mod error;
+pub use error::*;
+
+pub mod events;
+
 mod instructions;
 use instructions::AmmInstruction;
 use instructions::{
-    deposit::deposit, initialize::initialize, lock::lock, swap::swap, withdraw::withdraw,
+    accept_authority::accept_authority, cancel_order::cancel_order, claim_batch::claim_batch,
+    claim_order::claim_order, claim_rewards::claim_rewards, close_pool::close_pool,
+    collect_protocol_fees::collect_protocol_fees, commit_swap::commit_swap, deposit::deposit,
+    deposit_single::deposit_single, flash_loan::flash_loan, flash_repay::flash_repay,
+    fund_farm::fund_farm, initialize::initialize, initialize_factory::initialize_factory,
+    initialize_farm::initialize_farm, initialize_observations::initialize_observations,
+    initialize_twamm::initialize_twamm, observe::observe, place_order::place_order,
+    propose_authority::propose_authority, renounce_authority::renounce_authority,
+    reveal_swap::reveal_swap, route_swap::route_swap, set_allowlist::set_allowlist,
+    set_batch::set_batch, set_fee::set_fee, set_fee_tier::set_fee_tier,
+    set_oracle_guard::set_oracle_guard, set_pause::set_pause, settle_batch::settle_batch,
+    stake_lp::stake_lp, swap::swap, swap_exact_out::swap_exact_out, sweep_rewards::sweep_rewards,
+    unstake_lp::unstake_lp, withdraw::withdraw, withdraw_single::withdraw_single,
 };
 
+mod introspection;
+
+pub mod merkle;
+
+pub mod oracle;
+
 mod state;
 pub use state::*;
 
+mod system;
+
+mod token;
+
 entrypoint!(process_instruction);
 
 pub const PDA_MARKER: &[u8; 21] = b"ProgramDerivedAddress";
@@ -33,6 +65,39 @@ fn process_instruction(
         AmmInstruction::Deposit => deposit(accounts, data),
         AmmInstruction::Withdraw => withdraw(accounts, data),
         AmmInstruction::Swap => swap(accounts, data),
-        AmmInstruction::Lock => lock(accounts),
+        AmmInstruction::SetPause => set_pause(accounts, data),
+        AmmInstruction::SwapExactOut => swap_exact_out(accounts, data),
+        AmmInstruction::CollectProtocolFees => collect_protocol_fees(accounts),
+        AmmInstruction::RouteSwap => route_swap(accounts, data),
+        AmmInstruction::InitializeObservations => initialize_observations(accounts, data),
+        AmmInstruction::Observe => observe(accounts),
+        AmmInstruction::FlashLoan => flash_loan(accounts, data),
+        AmmInstruction::FlashRepay => flash_repay(accounts),
+        AmmInstruction::SetFee => set_fee(accounts, data),
+        AmmInstruction::ProposeAuthority => propose_authority(accounts, data),
+        AmmInstruction::AcceptAuthority => accept_authority(accounts),
+        AmmInstruction::RenounceAuthority => renounce_authority(accounts),
+        AmmInstruction::DepositSingle => deposit_single(accounts, data),
+        AmmInstruction::WithdrawSingle => withdraw_single(accounts, data),
+        AmmInstruction::InitializeFactory => initialize_factory(accounts, data),
+        AmmInstruction::SetFeeTier => set_fee_tier(accounts, data),
+        AmmInstruction::InitializeFarm => initialize_farm(accounts),
+        AmmInstruction::FundFarm => fund_farm(accounts, data),
+        AmmInstruction::StakeLp => stake_lp(accounts, data),
+        AmmInstruction::UnstakeLp => unstake_lp(accounts, data),
+        AmmInstruction::ClaimRewards => claim_rewards(accounts),
+        AmmInstruction::ClosePool => close_pool(accounts),
+        AmmInstruction::InitializeTwamm => initialize_twamm(accounts),
+        AmmInstruction::PlaceOrder => place_order(accounts, data),
+        AmmInstruction::ClaimOrder => claim_order(accounts),
+        AmmInstruction::CancelOrder => cancel_order(accounts),
+        AmmInstruction::SetAllowlist => set_allowlist(accounts, data),
+        AmmInstruction::SetBatch => set_batch(accounts, data),
+        AmmInstruction::CommitSwap => commit_swap(accounts, data),
+        AmmInstruction::RevealSwap => reveal_swap(accounts, data),
+        AmmInstruction::SettleBatch => settle_batch(accounts),
+        AmmInstruction::ClaimBatch => claim_batch(accounts),
+        AmmInstruction::SetOracleGuard => set_oracle_guard(accounts, data),
+        AmmInstruction::SweepRewards => sweep_rewards(accounts),
     }
 }
@@ -0,0 +1,70 @@
+//! Merkle allowlists of user keys.
+//!
+//! Leaves are `sha256(0x00 || key)` and nodes `sha256(0x01 || a || b)`, the
+//! two children sorted so that proofs are the bare list of siblings from the
+//! leaf up. The prefixes keep a node from being passed off as a leaf.
+
+use solana_nostd_sha256::hashv;
+
+pub const HASH_LEN: usize = 32;
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Leaf of `key` in the tree
+#[inline(always)]
+pub fn leaf(key: &[u8; 32]) -> [u8; HASH_LEN] {
+    hashv(&[LEAF_PREFIX, key])
+}
+
+/// Parent of two nodes, in either order
+#[inline(always)]
+pub fn parent(a: &[u8; HASH_LEN], b: &[u8; HASH_LEN]) -> [u8; HASH_LEN] {
+    if a <= b {
+        hashv(&[NODE_PREFIX, a, b])
+    } else {
+        hashv(&[NODE_PREFIX, b, a])
+    }
+}
+
+/// Whether `proof`, the concatenated siblings of the leaf of `key` up to the
+/// root, leads to `root`
+pub fn verify(root: &[u8; HASH_LEN], key: &[u8; 32], proof: &[u8]) -> bool {
+    if !proof.len().is_multiple_of(HASH_LEN) {
+        return false;
+    }
+    let node = proof
+        .chunks_exact(HASH_LEN)
+        .fold(leaf(key), |node, sibling| {
+            parent(&node, sibling.try_into().unwrap())
+        });
+    &node == root
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::merkle::{leaf, parent, verify};
+
+    #[test]
+    fn verify_proof() {
+        let keys = [[1u8; 32], [2u8; 32], [3u8; 32]];
+        let leaves = keys.map(|key| leaf(&key));
+        // The odd leaf is paired with itself
+        let (left, right) = (
+            parent(&leaves[0], &leaves[1]),
+            parent(&leaves[2], &leaves[2]),
+        );
+        let root = parent(&left, &right);
+
+        let proof = [leaves[1], right].concat();
+        assert!(verify(&root, &keys[0], &proof));
+        let proof = [leaves[2], left].concat();
+        assert!(verify(&root, &keys[2], &proof));
+
+        // Not a member, a wrong sibling, a truncated proof and a node as a leaf
+        assert!(!verify(&root, &[4u8; 32], &[leaves[1], right].concat()));
+        assert!(!verify(&root, &keys[0], &[leaves[2], right].concat()));
+        assert!(!verify(&root, &keys[0], &[leaves[1], right].concat()[..63]));
+        assert!(!verify(&root, &keys[0], &leaves[1]));
+    }
+}
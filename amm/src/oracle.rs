@@ -0,0 +1,127 @@
+//! Time-weighted average price helpers.
+//!
+//! Prices are Q64.64 fixed point numbers: the spot price of X in Y on the
+//! curve of the pool, `(reserve_y << 64) / reserve_x` on constant product
+//! pools. Cumulative prices are the sum of the price times the seconds it was
+//! in effect and are allowed to wrap, only the difference between two
+//! snapshots is meaningful.
+
+/// Q64.64 price of X in Y
+#[inline(always)]
+pub fn price_q64(reserve_x: u64, reserve_y: u64) -> u128 {
+    ((reserve_y as u128) << 64) / reserve_x as u128
+}
+
+/// Q64.64 value of `numerator / denominator`, both being shifted down to 64
+/// significant bits first, saturating when the denominator vanishes
+#[inline]
+pub fn ratio_q64(numerator: u128, denominator: u128) -> u128 {
+    let shift = (128 - numerator.max(denominator).leading_zeros()).saturating_sub(64);
+    let (numerator, denominator) = (numerator >> shift, denominator >> shift);
+    if denominator == 0 {
+        return u128::MAX;
+    }
+    (numerator << 64) / denominator
+}
+
+/// Cumulative prices of X and Y after `elapsed` seconds at the given prices
+#[inline]
+pub fn accumulate(
+    price_x_cumulative: u128,
+    price_y_cumulative: u128,
+    price_x: u128,
+    price_y: u128,
+    elapsed: u64,
+) -> (u128, u128) {
+    (
+        price_x_cumulative.wrapping_add(price_x.wrapping_mul(elapsed as u128)),
+        price_y_cumulative.wrapping_add(price_y.wrapping_mul(elapsed as u128)),
+    )
+}
+
+/// Average Q64.64 price between two cumulative snapshots, `None` if no time elapsed
+#[inline]
+pub fn twap(
+    start_cumulative: u128,
+    start_timestamp: i64,
+    end_cumulative: u128,
+    end_timestamp: i64,
+) -> Option<u128> {
+    let elapsed = end_timestamp
+        .checked_sub(start_timestamp)
+        .filter(|elapsed| *elapsed > 0)?;
+    Some(end_cumulative.wrapping_sub(start_cumulative) / elapsed as u128)
+}
+
+/// Q64.64 value of `value` · 10^`exponent`, the scale of price feeds, `None`
+/// if it does not fit
+#[inline]
+pub fn scale_q64(value: u64, exponent: i32) -> Option<u128> {
+    let scale = 10u128.checked_pow(exponent.unsigned_abs())?;
+    let value = (value as u128) << 64;
+    if exponent >= 0 {
+        value.checked_mul(scale)
+    } else {
+        Some(value / scale)
+    }
+}
+
+/// `bps` basis points of `price`, rounded down
+#[inline(always)]
+pub fn bps_of(price: u128, bps: u16) -> u128 {
+    let bps = bps as u128;
+    price / 10_000 * bps + price % 10_000 * bps / 10_000
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::oracle::{accumulate, bps_of, price_q64, ratio_q64, scale_q64, twap};
+
+    #[test]
+    fn accumulate_prices() {
+        let (x, y) = accumulate(0, 0, 2 << 64, 1 << 63, 10);
+        assert_eq!(x, 20 << 64);
+        assert_eq!(y, 5 << 64);
+
+        // No time elapsed, nothing accumulated
+        assert_eq!(accumulate(7, 8, 2 << 64, 1 << 63, 0), (7, 8));
+    }
+
+    #[test]
+    fn twap_over_window() {
+        // 10s at a price of 2 then 30s at a price of 4
+        let (start, _) = accumulate(0, 0, 2 << 64, 0, 10);
+        let (end, _) = accumulate(start, 0, 4 << 64, 0, 30);
+        assert_eq!(twap(0, 0, end, 40), Some(price_q64(2, 7)));
+        assert_eq!(twap(start, 10, end, 40), Some(4 << 64));
+        assert_eq!(twap(start, 10, end, 10), None);
+    }
+
+    #[test]
+    fn twap_across_wrap() {
+        let start = u128::MAX - (1 << 64) + 1;
+        let (end, _) = accumulate(start, 0, 1 << 64, 0, 3);
+        assert_eq!(twap(start, 0, end, 3), Some(1 << 64));
+    }
+
+    #[test]
+    fn ratios() {
+        assert_eq!(ratio_q64(2_000, 1_000), price_q64(1_000, 2_000));
+        assert_eq!(ratio_q64(u64::MAX as u128, 1), price_q64(1, u64::MAX));
+        // Wide ratios keep their 64 most significant bits
+        assert_eq!(ratio_q64(3 << 100, 2 << 100), price_q64(2, 3));
+        assert_eq!(ratio_q64(1, 0), u128::MAX);
+    }
+
+    #[test]
+    fn scale_feed_prices() {
+        assert_eq!(scale_q64(2, 0), Some(2 << 64));
+        assert_eq!(scale_q64(25, -1), Some(price_q64(2, 5)));
+        assert_eq!(scale_q64(3, 2), Some(300 << 64));
+        assert_eq!(scale_q64(1, 39), None);
+        assert_eq!(scale_q64(u64::MAX, 20), None);
+
+        assert_eq!(bps_of(3 << 64, 50), price_q64(200, 3));
+        assert_eq!(bps_of(u128::MAX, 10_000), u128::MAX);
+    }
+}
@@ -1,4 +1,15 @@
-use pinocchio::{account_info::AccountInfo, pubkey::Pubkey};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+use solana_nostd_sha256::hashv;
+
+use crate::{
+    constants::{
+        BATCH_TIMEOUT_SLOTS, EMERGENCY_MODE, FLASH_LOAN_NONE, MAX_FEE_TIERS, PAUSE_SWAPS,
+        PAUSE_WITHDRAWALS, PROCEEDS_PRECISION, REWARD_PRECISION, TWAMM_BUCKETS, TWAMM_INTERVAL,
+    },
+    curve,
+    error::AmmError,
+    merkle, oracle,
+};
 
 /// # State
 ///
@@ -11,30 +22,120 @@ use pinocchio::{account_info::AccountInfo, pubkey::Pubkey};
 /// > VaultY: Pubkey
 /// > Fee: u16
 /// > AuthorityBump: u8
+/// > ProtocolFee: u16
+/// > ProtocolFeesX: u64
+/// > ProtocolFeesY: u64
+/// > LastUpdate: i64
+/// > PriceXCumulative: u128
+/// > PriceYCumulative: u128
+/// > FlashLoan: u8
+/// > FlashLoanRepay: u64
+/// > Curve: u8
+/// > Amp: u64
+/// > ReserveX: u64
+/// > ReserveY: u64
+/// > PauseGuardian: Pubkey
+/// > PendingAuthority: Pubkey
+/// > MaxReferralFee: u16
+/// > MaxPriceImpact: u16
+/// > StartWeight: u16
+/// > EndWeight: u16
+/// > StartTime: i64
+/// > EndTime: i64
+/// > Twamm: Pubkey
+/// > AllowlistRoot: [u8; 32]
+/// > CommitSlots: u64
+/// > RevealSlots: u64
+/// > PriceFeed: Pubkey
+/// > MaxOracleDeviation: u16
+/// > MaxOracleStaleness: u64
+/// > BatchRound: u64
+/// > BatchCommitEnd: u64
+/// > BatchRevealEnd: u64
+/// > BatchX: u64
+/// > BatchY: u64
 pub struct Config(*const u8);
 
 impl Config {
-    pub const LEN: usize = 1 + 32 + 32 + 32 + 32 + 32 + 32 + 2 + 1;
+    pub const LEN: usize = 1
+        + 32
+        + 32
+        + 32
+        + 32
+        + 32
+        + 32
+        + 2
+        + 1
+        + 2
+        + 8
+        + 8
+        + 8
+        + 16
+        + 16
+        + 1
+        + 8
+        + 1
+        + 8
+        + 8
+        + 8
+        + 32
+        + 2
+        + 2
+        + 2
+        + 2
+        + 8
+        + 8
+        + 32
+        + 32
+        + 8
+        + 8
+        + 32
+        + 2
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8;
 
     #[inline(always)]
     pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
-        unsafe { Self(account_info.borrow_data_unchecked().as_ptr()) }
+        // Derived from the mutable borrow so the setters below can write through it
+        unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
     }
 
-    pub fn from_account_info(account_info: &AccountInfo) -> Self {
-        assert_eq!(account_info.data_len(), Self::LEN);
-        assert_eq!(account_info.owner(), &crate::ID);
-        Self::from_account_info_unchecked(account_info)
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        if account_info.data_len() != Self::LEN || account_info.owner() != &crate::ID {
+            return Err(AmmError::InvalidConfig.into());
+        }
+        Ok(Self::from_account_info_unchecked(account_info))
     }
 
+    /// Pause flags, see [`crate::PAUSE_SWAPS`]
     pub fn get_status(&self) -> u8 {
-        unsafe { *(self.0 as *const u8) }
+        unsafe { *self.0 }
+    }
+
+    pub fn set_status(&self, status: u8) {
+        unsafe { *(self.0 as *mut u8) = status }
     }
 
+    /// Whether the operations behind `flag` are paused, emergency mode pausing
+    /// everything but withdrawals
+    pub fn is_paused(&self, flag: u8) -> bool {
+        let status = self.get_status();
+        status & flag != 0 || (status & EMERGENCY_MODE != 0 && flag != PAUSE_WITHDRAWALS)
+    }
+
+    /// Admin of the pool, the default key once renounced
     pub fn update_authority(&self) -> Pubkey {
         unsafe { *(self.0.add(1) as *const [u8; 32]) }
     }
 
+    pub fn set_update_authority(&self, update_authority: &Pubkey) {
+        unsafe { *(self.0.add(1) as *mut [u8; 32]) = *update_authority }
+    }
+
     pub fn mint_x(&self) -> Pubkey {
         unsafe { *(self.0.add(33) as *const [u8; 32]) }
     }
@@ -59,7 +160,1500 @@ impl Config {
         unsafe { *(self.0.add(193) as *const u16) }
     }
 
+    pub fn set_fee(&self, fee: u16) {
+        unsafe { *(self.0.add(193) as *mut [u8; 2]) = fee.to_le_bytes() }
+    }
+
     pub fn authority_bump(&self) -> u8 {
-        unsafe { *(self.0.add(195) as *const u8) }
+        unsafe { *self.0.add(195) }
+    }
+
+    /// Share of the swap fee kept by the protocol, in bps of the fee
+    pub fn protocol_fee(&self) -> u16 {
+        unsafe { u16::from_le_bytes(*(self.0.add(196) as *const [u8; 2])) }
+    }
+
+    pub fn set_protocol_fee(&self, protocol_fee: u16) {
+        unsafe { *(self.0.add(196) as *mut [u8; 2]) = protocol_fee.to_le_bytes() }
+    }
+
+    pub fn protocol_fees_x(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(198) as *const [u8; 8])) }
+    }
+
+    pub fn protocol_fees_y(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(206) as *const [u8; 8])) }
+    }
+
+    pub fn set_protocol_fees_x(&self, amount: u64) {
+        unsafe { *(self.0.add(198) as *mut [u8; 8]) = amount.to_le_bytes() }
+    }
+
+    pub fn set_protocol_fees_y(&self, amount: u64) {
+        unsafe { *(self.0.add(206) as *mut [u8; 8]) = amount.to_le_bytes() }
+    }
+
+    /// Adds the protocol share of a swap fee, paid in X when `in_x` or in Y otherwise,
+    /// and returns it
+    pub fn accrue_protocol_fee(&self, in_x: bool, fee: u64) -> Result<u64, ProgramError> {
+        let protocol_fee = (fee as u128 * self.protocol_fee() as u128 / 10_000) as u64;
+        if in_x {
+            self.set_protocol_fees_x(
+                self.protocol_fees_x()
+                    .checked_add(protocol_fee)
+                    .ok_or(ProgramError::ArithmeticOverflow)?,
+            );
+        } else {
+            self.set_protocol_fees_y(
+                self.protocol_fees_y()
+                    .checked_add(protocol_fee)
+                    .ok_or(ProgramError::ArithmeticOverflow)?,
+            );
+        }
+        Ok(protocol_fee)
+    }
+
+    /// Timestamp of the last oracle update
+    pub fn last_update(&self) -> i64 {
+        unsafe { i64::from_le_bytes(*(self.0.add(214) as *const [u8; 8])) }
+    }
+
+    /// Cumulative Q64.64 price of X in Y, see [`oracle`]
+    pub fn price_x_cumulative(&self) -> u128 {
+        unsafe { u128::from_le_bytes(*(self.0.add(222) as *const [u8; 16])) }
+    }
+
+    /// Cumulative Q64.64 price of Y in X, see [`oracle`]
+    pub fn price_y_cumulative(&self) -> u128 {
+        unsafe { u128::from_le_bytes(*(self.0.add(238) as *const [u8; 16])) }
+    }
+
+    /// Accumulates the spot prices of the curve at the reserves since the last
+    /// update, empty pools accumulating nothing. Must be called with the
+    /// reserves as they were before being modified.
+    pub fn update_oracle(
+        &self,
+        reserve_x: u64,
+        reserve_y: u64,
+        now: i64,
+    ) -> Result<(), ProgramError> {
+        let elapsed = now.saturating_sub(self.last_update()).max(0) as u64;
+        let (price_x_cumulative, price_y_cumulative) =
+            if reserve_x == 0 || reserve_y == 0 || elapsed == 0 {
+                (self.price_x_cumulative(), self.price_y_cumulative())
+            } else {
+                let (price_x, price_y) = curve::spot_prices(self, reserve_x, reserve_y, now)?;
+                oracle::accumulate(
+                    self.price_x_cumulative(),
+                    self.price_y_cumulative(),
+                    price_x,
+                    price_y,
+                    elapsed,
+                )
+            };
+
+        unsafe {
+            *(self.0.add(214) as *mut [u8; 8]) = now.to_le_bytes();
+            *(self.0.add(222) as *mut [u8; 16]) = price_x_cumulative.to_le_bytes();
+            *(self.0.add(238) as *mut [u8; 16]) = price_y_cumulative.to_le_bytes();
+        }
+
+        Ok(())
+    }
+
+    /// Vault lent out by an outstanding flash loan, see [`crate::FLASH_LOAN_NONE`]
+    pub fn flash_loan(&self) -> u8 {
+        unsafe { *self.0.add(254) }
+    }
+
+    /// Amount owed to the vault by the outstanding flash loan, principal plus fee
+    pub fn flash_loan_repay(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(255) as *const [u8; 8])) }
+    }
+
+    pub fn set_flash_loan(&self, flash_loan: u8, repay: u64) {
+        unsafe {
+            *(self.0.add(254) as *mut u8) = flash_loan;
+            *(self.0.add(255) as *mut [u8; 8]) = repay.to_le_bytes();
+        }
+    }
+
+    /// Invariant of the pool, see [`crate::CURVE_CONSTANT_PRODUCT`]
+    pub fn curve(&self) -> u8 {
+        unsafe { *self.0.add(263) }
+    }
+
+    /// Amplification of the StableSwap invariant, unused by other curves
+    pub fn amp(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(264) as *const [u8; 8])) }
+    }
+
+    /// Tokens of X owned by the LPs. Tracked instead of read from the vault so
+    /// that tokens donated to the vault do not move the price or the LP value.
+    pub fn reserve_x(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(272) as *const [u8; 8])) }
+    }
+
+    /// Tokens of Y owned by the LPs, see [`Config::reserve_x`]
+    pub fn reserve_y(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(280) as *const [u8; 8])) }
+    }
+
+    /// Key allowed to pause swaps and deposits, but not withdrawals
+    pub fn pause_guardian(&self) -> Pubkey {
+        unsafe { *(self.0.add(288) as *const [u8; 32]) }
+    }
+
+    pub fn set_pause_guardian(&self, pause_guardian: &Pubkey) {
+        unsafe { *(self.0.add(288) as *mut [u8; 32]) = *pause_guardian }
+    }
+
+    /// Key proposed as the next update authority, the default key if none
+    pub fn pending_authority(&self) -> Pubkey {
+        unsafe { *(self.0.add(320) as *const [u8; 32]) }
+    }
+
+    pub fn set_pending_authority(&self, pending_authority: &Pubkey) {
+        unsafe { *(self.0.add(320) as *mut [u8; 32]) = *pending_authority }
+    }
+
+    /// Largest share of the swap fee a referrer can ask for, in bps of the fee
+    pub fn max_referral_fee(&self) -> u16 {
+        unsafe { u16::from_le_bytes(*(self.0.add(352) as *const [u8; 2])) }
+    }
+
+    pub fn set_max_referral_fee(&self, max_referral_fee: u16) {
+        unsafe { *(self.0.add(352) as *mut [u8; 2]) = max_referral_fee.to_le_bytes() }
+    }
+
+    /// Largest move of the spot price a single swap can cause, in bps of the
+    /// price before it, zero disabling the guard
+    pub fn max_price_impact(&self) -> u16 {
+        unsafe { u16::from_le_bytes(*(self.0.add(354) as *const [u8; 2])) }
+    }
+
+    pub fn set_max_price_impact(&self, max_price_impact: u16) {
+        unsafe { *(self.0.add(354) as *mut [u8; 2]) = max_price_impact.to_le_bytes() }
+    }
+
+    /// Weight of X out of WEIGHT_SCALE when the schedule of a weighted pool
+    /// starts, Y weighing the rest
+    pub fn start_weight(&self) -> u16 {
+        unsafe { u16::from_le_bytes(*(self.0.add(356) as *const [u8; 2])) }
+    }
+
+    /// Weight of X out of WEIGHT_SCALE once the schedule ends
+    pub fn end_weight(&self) -> u16 {
+        unsafe { u16::from_le_bytes(*(self.0.add(358) as *const [u8; 2])) }
+    }
+
+    pub fn start_time(&self) -> i64 {
+        unsafe { i64::from_le_bytes(*(self.0.add(360) as *const [u8; 8])) }
+    }
+
+    pub fn end_time(&self) -> i64 {
+        unsafe { i64::from_le_bytes(*(self.0.add(368) as *const [u8; 8])) }
+    }
+
+    /// Weight of X at `now`, moving linearly from StartWeight at StartTime to
+    /// EndWeight at EndTime and held outside of the schedule
+    pub fn weight_x(&self, now: i64) -> u16 {
+        let (start_weight, end_weight) = (self.start_weight(), self.end_weight());
+        let (start_time, end_time) = (self.start_time(), self.end_time());
+        if now <= start_time {
+            return start_weight;
+        }
+        if now >= end_time {
+            return end_weight;
+        }
+        let elapsed = now as i128 - start_time as i128;
+        let duration = end_time as i128 - start_time as i128;
+        let weight =
+            start_weight as i128 + (end_weight as i128 - start_weight as i128) * elapsed / duration;
+        weight as u16
+    }
+
+    /// TWAMM executing the long-term orders of the pool, the default key if none
+    pub fn twamm(&self) -> Pubkey {
+        unsafe { *(self.0.add(376) as *const [u8; 32]) }
+    }
+
+    pub fn set_twamm(&self, twamm: &Pubkey) {
+        unsafe { *(self.0.add(376) as *mut [u8; 32]) = *twamm }
+    }
+
+    /// Merkle root of the users allowed to trade and deposit, all zero for
+    /// a permissionless pool
+    pub fn allowlist_root(&self) -> [u8; 32] {
+        unsafe { *(self.0.add(408) as *const [u8; 32]) }
+    }
+
+    pub fn set_allowlist_root(&self, root: &[u8; 32]) {
+        unsafe { *(self.0.add(408) as *mut [u8; 32]) = *root }
+    }
+
+    /// Checks that `user` is on the allowlist of the pool, if it has one,
+    /// given the merkle proof found in `proof`
+    pub fn check_allowlist(&self, user: &Pubkey, proof: &[u8]) -> Result<(), ProgramError> {
+        let root = self.allowlist_root();
+        if root != [0; 32] && !merkle::verify(&root, user, proof) {
+            return Err(AmmError::NotAllowlisted.into());
+        }
+        Ok(())
+    }
+
+    /// Slots of the commit window of a batch round, zero when the pool swaps
+    /// continuously
+    pub fn commit_slots(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(440) as *const [u8; 8])) }
+    }
+
+    /// Slots of the reveal window following the commit window
+    pub fn reveal_slots(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(448) as *const [u8; 8])) }
+    }
+
+    pub fn set_batch(&self, commit_slots: u64, reveal_slots: u64) {
+        unsafe {
+            *(self.0.add(440) as *mut [u8; 8]) = commit_slots.to_le_bytes();
+            *(self.0.add(448) as *mut [u8; 8]) = reveal_slots.to_le_bytes();
+        }
+    }
+
+    /// Whether swaps only go through batch rounds
+    pub fn is_batched(&self) -> bool {
+        self.commit_slots() != 0
+    }
+
+    /// Price feed guarding swaps, the default key if none
+    pub fn price_feed(&self) -> Pubkey {
+        unsafe { *(self.0.add(456) as *const [u8; 32]) }
+    }
+
+    /// Basis points the spot price may be away from the price feed
+    pub fn max_oracle_deviation(&self) -> u16 {
+        unsafe { u16::from_le_bytes(*(self.0.add(488) as *const [u8; 2])) }
+    }
+
+    /// Slots since its publication the price feed can be used for
+    pub fn max_oracle_staleness(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(490) as *const [u8; 8])) }
+    }
+
+    pub fn set_oracle_guard(&self, price_feed: &Pubkey, max_deviation: u16, max_staleness: u64) {
+        unsafe {
+            *(self.0.add(456) as *mut [u8; 32]) = *price_feed;
+            *(self.0.add(488) as *mut [u8; 2]) = max_deviation.to_le_bytes();
+            *(self.0.add(490) as *mut [u8; 8]) = max_staleness.to_le_bytes();
+        }
+    }
+
+    /// Index of the last batch round opened, see [`Round`]
+    pub fn batch_round(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(498) as *const [u8; 8])) }
+    }
+
+    /// First slot of the reveal window of the last batch round opened
+    pub fn batch_commit_end(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(506) as *const [u8; 8])) }
+    }
+
+    /// First slot the last batch round opened can be settled
+    pub fn batch_reveal_end(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(514) as *const [u8; 8])) }
+    }
+
+    pub fn open_round(&self, index: u64, commit_end: u64, reveal_end: u64) {
+        unsafe {
+            *(self.0.add(498) as *mut [u8; 8]) = index.to_le_bytes();
+            *(self.0.add(506) as *mut [u8; 8]) = commit_end.to_le_bytes();
+            *(self.0.add(514) as *mut [u8; 8]) = reveal_end.to_le_bytes();
+        }
+    }
+
+    /// X held by the vault for batch rounds on top of the reserves, revealed
+    /// swaps waiting for settlement and settled shares not claimed yet
+    pub fn batch_x(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(522) as *const [u8; 8])) }
+    }
+
+    /// Y held by the vault for batch rounds on top of the reserves
+    pub fn batch_y(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(530) as *const [u8; 8])) }
+    }
+
+    fn set_batch_held(&self, batch_x: u64, batch_y: u64) {
+        unsafe {
+            *(self.0.add(522) as *mut [u8; 8]) = batch_x.to_le_bytes();
+            *(self.0.add(530) as *mut [u8; 8]) = batch_y.to_le_bytes();
+        }
+    }
+
+    /// Adds `x` and `y` to the amounts held for batch rounds
+    pub fn hold_batch(&self, x: u64, y: u64) -> Result<(), ProgramError> {
+        let batch_x = self.batch_x().checked_add(x);
+        let batch_y = self.batch_y().checked_add(y);
+        let (Some(batch_x), Some(batch_y)) = (batch_x, batch_y) else {
+            return Err(ProgramError::ArithmeticOverflow);
+        };
+        self.set_batch_held(batch_x, batch_y);
+        Ok(())
+    }
+
+    /// Removes `x` and `y` from the amounts held for batch rounds
+    pub fn release_batch(&self, x: u64, y: u64) -> Result<(), ProgramError> {
+        let batch_x = self.batch_x().checked_sub(x);
+        let batch_y = self.batch_y().checked_sub(y);
+        let (Some(batch_x), Some(batch_y)) = (batch_x, batch_y) else {
+            return Err(ProgramError::ArithmeticOverflow);
+        };
+        self.set_batch_held(batch_x, batch_y);
+        Ok(())
+    }
+
+    pub fn set_reserves(&self, reserve_x: u64, reserve_y: u64) {
+        unsafe {
+            *(self.0.add(272) as *mut [u8; 8]) = reserve_x.to_le_bytes();
+            *(self.0.add(280) as *mut [u8; 8]) = reserve_y.to_le_bytes();
+        }
+    }
+
+    /// Moves the reserves by a swap of `amount_in` of X when `in_x` or Y otherwise,
+    /// `amount_out` including the protocol share of the fee
+    pub fn apply_swap(
+        &self,
+        in_x: bool,
+        amount_in: u64,
+        amount_out: u64,
+    ) -> Result<(), ProgramError> {
+        let (reserve_in, reserve_out) = if in_x {
+            (self.reserve_x(), self.reserve_y())
+        } else {
+            (self.reserve_y(), self.reserve_x())
+        };
+        let reserve_in = reserve_in
+            .checked_add(amount_in)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let reserve_out = reserve_out
+            .checked_sub(amount_out)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if in_x {
+            self.set_reserves(reserve_in, reserve_out);
+        } else {
+            self.set_reserves(reserve_out, reserve_in);
+        }
+        Ok(())
+    }
+}
+
+/// -- Observation --
+/// > Timestamp: i64
+/// > PriceXCumulative: u128
+/// > PriceYCumulative: u128
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Observation {
+    pub timestamp: i64,
+    pub price_x_cumulative: u128,
+    pub price_y_cumulative: u128,
+}
+
+/// -- Observations --
+/// > Config: Pubkey
+/// > Index: u16
+/// > Count: u16
+/// > Observations: [Observation; Capacity]
+///
+/// Ring buffer of oracle snapshots, `Index` being the next slot to write.
+pub struct Observations(*const u8, u16);
+
+impl Observations {
+    pub const HEADER_LEN: usize = 32 + 2 + 2;
+    pub const OBSERVATION_LEN: usize = 8 + 16 + 16;
+
+    pub const fn space(capacity: u16) -> usize {
+        Self::HEADER_LEN + capacity as usize * Self::OBSERVATION_LEN
+    }
+
+    #[inline(always)]
+    pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
+        let capacity = (account_info.data_len().saturating_sub(Self::HEADER_LEN)
+            / Self::OBSERVATION_LEN) as u16;
+        unsafe {
+            Self(
+                account_info.borrow_mut_data_unchecked().as_mut_ptr(),
+                capacity,
+            )
+        }
+    }
+
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        let data_len = account_info.data_len();
+        if data_len <= Self::HEADER_LEN
+            || !(data_len - Self::HEADER_LEN).is_multiple_of(Self::OBSERVATION_LEN)
+            || account_info.owner() != &crate::ID
+        {
+            return Err(AmmError::InvalidObservations.into());
+        }
+        Ok(Self::from_account_info_unchecked(account_info))
+    }
+
+    pub fn config(&self) -> Pubkey {
+        unsafe { *(self.0 as *const [u8; 32]) }
+    }
+
+    pub fn index(&self) -> u16 {
+        unsafe { u16::from_le_bytes(*(self.0.add(32) as *const [u8; 2])) }
+    }
+
+    pub fn count(&self) -> u16 {
+        unsafe { u16::from_le_bytes(*(self.0.add(34) as *const [u8; 2])) }
+    }
+
+    pub fn capacity(&self) -> u16 {
+        self.1
+    }
+
+    /// Observation stored in `slot`, which must be below the capacity
+    pub fn get(&self, slot: u16) -> Observation {
+        unsafe {
+            let observation = self
+                .0
+                .add(Self::HEADER_LEN + slot as usize * Self::OBSERVATION_LEN);
+            Observation {
+                timestamp: i64::from_le_bytes(*(observation as *const [u8; 8])),
+                price_x_cumulative: u128::from_le_bytes(*(observation.add(8) as *const [u8; 16])),
+                price_y_cumulative: u128::from_le_bytes(*(observation.add(24) as *const [u8; 16])),
+            }
+        }
+    }
+
+    /// Observation written `age` pushes ago, `age` 0 being the latest one
+    pub fn get_back(&self, age: u16) -> Option<Observation> {
+        if age >= self.count() {
+            return None;
+        }
+        let capacity = self.capacity() as u32;
+        let slot = (self.index() as u32 + capacity - 1 - age as u32) % capacity;
+        Some(self.get(slot as u16))
+    }
+
+    /// Writes `observation` in the next slot, overwriting the oldest one once full
+    pub fn push(&self, observation: Observation) {
+        let index = self.index();
+        unsafe {
+            let slot = self
+                .0
+                .add(Self::HEADER_LEN + index as usize * Self::OBSERVATION_LEN)
+                as *mut u8;
+            *(slot as *mut [u8; 8]) = observation.timestamp.to_le_bytes();
+            *(slot.add(8) as *mut [u8; 16]) = observation.price_x_cumulative.to_le_bytes();
+            *(slot.add(24) as *mut [u8; 16]) = observation.price_y_cumulative.to_le_bytes();
+
+            *(self.0.add(32) as *mut [u8; 2]) = ((index + 1) % self.capacity()).to_le_bytes();
+            *(self.0.add(34) as *mut [u8; 2]) =
+                (self.count() + 1).min(self.capacity()).to_le_bytes();
+        }
+    }
+
+    /// Q64.64 average prices of X in Y and Y in X between the latest observation
+    /// and the most recent one at least `window` seconds older
+    pub fn twap(&self, window: i64) -> Option<(u128, u128)> {
+        let latest = self.get_back(0)?;
+        let start = (1..self.count())
+            .filter_map(|age| self.get_back(age))
+            .find(|observation| observation.timestamp <= latest.timestamp - window)?;
+
+        Some((
+            oracle::twap(
+                start.price_x_cumulative,
+                start.timestamp,
+                latest.price_x_cumulative,
+                latest.timestamp,
+            )?,
+            oracle::twap(
+                start.price_y_cumulative,
+                start.timestamp,
+                latest.price_y_cumulative,
+                latest.timestamp,
+            )?,
+        ))
+    }
+}
+
+/// -- PriceFeed --
+/// > Price: i64
+/// > Exponent: i32
+/// > Confidence: u64
+/// > PublishSlot: u64
+///
+/// External price of X in Y: Price · 10^Exponent base units of Y per base
+/// unit of X, give or take Confidence at the same scale, as published at
+/// PublishSlot. The account belongs to whichever program publishes it, pools
+/// trusting the one their update authority set.
+pub struct PriceFeed(*const u8);
+
+impl PriceFeed {
+    pub const LEN: usize = 8 + 4 + 8 + 8;
+
+    #[inline(always)]
+    pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
+        unsafe { Self(account_info.borrow_data_unchecked().as_ptr()) }
+    }
+
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        if account_info.data_len() < Self::LEN {
+            return Err(AmmError::InvalidOracle.into());
+        }
+        Ok(Self::from_account_info_unchecked(account_info))
+    }
+
+    /// Price feed guarding the pool among `accounts`, required once the pool
+    /// has one
+    pub fn from_config(
+        config: &Config,
+        accounts: &[AccountInfo],
+    ) -> Result<Option<Self>, ProgramError> {
+        let price_feed = config.price_feed();
+        if price_feed == Pubkey::default() {
+            return Ok(None);
+        }
+        let account = accounts
+            .iter()
+            .find(|account| account.key() == &price_feed)
+            .ok_or(AmmError::InvalidOracle)?;
+        Ok(Some(Self::from_account_info(account)?))
+    }
+
+    pub fn price(&self) -> i64 {
+        unsafe { i64::from_le_bytes(*(self.0 as *const [u8; 8])) }
+    }
+
+    pub fn exponent(&self) -> i32 {
+        unsafe { i32::from_le_bytes(*(self.0.add(8) as *const [u8; 4])) }
+    }
+
+    pub fn confidence(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(12) as *const [u8; 8])) }
+    }
+
+    pub fn publish_slot(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(20) as *const [u8; 8])) }
+    }
+}
+
+/// -- Factory --
+/// > Admin: Pubkey
+/// > FeeTierCount: u8
+/// > FeeTiers: [u16; MAX_FEE_TIERS]
+pub struct Factory(*const u8);
+
+impl Factory {
+    pub const LEN: usize = 32 + 1 + 2 * MAX_FEE_TIERS;
+
+    #[inline(always)]
+    pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
+        unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
+    }
+
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        if account_info.data_len() != Self::LEN || account_info.owner() != &crate::ID {
+            return Err(AmmError::InvalidFactory.into());
+        }
+        Ok(Self::from_account_info_unchecked(account_info))
+    }
+
+    /// Key allowed to whitelist fee tiers
+    pub fn admin(&self) -> Pubkey {
+        unsafe { *(self.0 as *const [u8; 32]) }
+    }
+
+    pub fn set_admin(&self, admin: &Pubkey) {
+        unsafe { *(self.0 as *mut [u8; 32]) = *admin }
+    }
+
+    pub fn fee_tier_count(&self) -> u8 {
+        unsafe { *self.0.add(32) }
+    }
+
+    /// Fee tier stored in `slot`, which must be below the fee tier count
+    pub fn fee_tier(&self, slot: u8) -> u16 {
+        unsafe { u16::from_le_bytes(*(self.0.add(33 + 2 * slot as usize) as *const [u8; 2])) }
+    }
+
+    pub fn has_fee_tier(&self, fee: u16) -> bool {
+        (0..self.fee_tier_count()).any(|slot| self.fee_tier(slot) == fee)
+    }
+
+    /// Whitelists `fee`, false when every slot is taken
+    pub fn add_fee_tier(&self, fee: u16) -> bool {
+        if self.has_fee_tier(fee) {
+            return true;
+        }
+        let count = self.fee_tier_count();
+        if count as usize == MAX_FEE_TIERS {
+            return false;
+        }
+        unsafe {
+            *(self.0.add(33 + 2 * count as usize) as *mut [u8; 2]) = fee.to_le_bytes();
+            *(self.0.add(32) as *mut u8) = count + 1;
+        }
+        true
+    }
+
+    /// Removes `fee` from the whitelist, moving the last tier in its slot
+    pub fn remove_fee_tier(&self, fee: u16) {
+        let count = self.fee_tier_count();
+        if let Some(slot) = (0..count).find(|slot| self.fee_tier(*slot) == fee) {
+            let last = self.fee_tier(count - 1);
+            unsafe {
+                *(self.0.add(33 + 2 * slot as usize) as *mut [u8; 2]) = last.to_le_bytes();
+                *(self.0.add(32) as *mut u8) = count - 1;
+            }
+        }
+    }
+}
+
+/// -- Registry --
+/// > Config: Pubkey
+///
+/// One per mint pair and fee tier, pointing at the canonical pool.
+pub struct Registry(*const u8);
+
+impl Registry {
+    pub const LEN: usize = 32;
+
+    #[inline(always)]
+    pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
+        unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
+    }
+
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        if account_info.data_len() != Self::LEN || account_info.owner() != &crate::ID {
+            return Err(AmmError::InvalidRegistry.into());
+        }
+        Ok(Self::from_account_info_unchecked(account_info))
+    }
+
+    pub fn config(&self) -> Pubkey {
+        unsafe { *(self.0 as *const [u8; 32]) }
+    }
+
+    pub fn set_config(&self, config: &Pubkey) {
+        unsafe { *(self.0 as *mut [u8; 32]) = *config }
+    }
+}
+
+/// -- Farm --
+/// > Config: Pubkey
+/// > RewardMint: Pubkey
+/// > RewardVault: Pubkey
+/// > StakeVault: Pubkey
+/// > RewardRate: u64
+/// > RewardEnd: i64
+/// > LastUpdate: i64
+/// > RewardPerShare: u128
+/// > TotalStaked: u64
+/// > Unallocated: u64
+/// > OwedRewards: u64
+///
+/// Liquidity mining of a pool, emitting RewardRate tokens per second until
+/// RewardEnd to the LP tokens staked in the StakeVault. Unallocated holds the
+/// rewards in the vault no emission covers, re-emitted by the next FundFarm,
+/// and OwedRewards the rewards settled in stakes but not claimed yet.
+pub struct Farm(*const u8);
+
+impl Farm {
+    pub const LEN: usize = 32 + 32 + 32 + 32 + 8 + 8 + 8 + 16 + 8 + 8 + 8;
+
+    #[inline(always)]
+    pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
+        unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
+    }
+
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        if account_info.data_len() != Self::LEN || account_info.owner() != &crate::ID {
+            return Err(AmmError::InvalidFarm.into());
+        }
+        Ok(Self::from_account_info_unchecked(account_info))
+    }
+
+    pub fn config(&self) -> Pubkey {
+        unsafe { *(self.0 as *const [u8; 32]) }
+    }
+
+    pub fn reward_mint(&self) -> Pubkey {
+        unsafe { *(self.0.add(32) as *const [u8; 32]) }
+    }
+
+    pub fn reward_vault(&self) -> Pubkey {
+        unsafe { *(self.0.add(64) as *const [u8; 32]) }
+    }
+
+    /// Vault holding the staked LP tokens
+    pub fn stake_vault(&self) -> Pubkey {
+        unsafe { *(self.0.add(96) as *const [u8; 32]) }
+    }
+
+    pub fn set_accounts(
+        &self,
+        config: &Pubkey,
+        reward_mint: &Pubkey,
+        reward_vault: &Pubkey,
+        stake_vault: &Pubkey,
+    ) {
+        unsafe {
+            *(self.0 as *mut [u8; 32]) = *config;
+            *(self.0.add(32) as *mut [u8; 32]) = *reward_mint;
+            *(self.0.add(64) as *mut [u8; 32]) = *reward_vault;
+            *(self.0.add(96) as *mut [u8; 32]) = *stake_vault;
+        }
+    }
+
+    /// Reward tokens emitted per second until the reward end
+    pub fn reward_rate(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(128) as *const [u8; 8])) }
+    }
+
+    pub fn reward_end(&self) -> i64 {
+        unsafe { i64::from_le_bytes(*(self.0.add(136) as *const [u8; 8])) }
+    }
+
+    pub fn set_emission(&self, reward_rate: u64, reward_end: i64) {
+        unsafe {
+            *(self.0.add(128) as *mut [u8; 8]) = reward_rate.to_le_bytes();
+            *(self.0.add(136) as *mut [u8; 8]) = reward_end.to_le_bytes();
+        }
+    }
+
+    pub fn last_update(&self) -> i64 {
+        unsafe { i64::from_le_bytes(*(self.0.add(144) as *const [u8; 8])) }
+    }
+
+    /// Rewards earned by a staked LP token since the farm started, scaled by
+    /// REWARD_PRECISION
+    pub fn reward_per_share(&self) -> u128 {
+        unsafe { u128::from_le_bytes(*(self.0.add(152) as *const [u8; 16])) }
+    }
+
+    pub fn total_staked(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(168) as *const [u8; 8])) }
+    }
+
+    pub fn set_total_staked(&self, total_staked: u64) {
+        unsafe { *(self.0.add(168) as *mut [u8; 8]) = total_staked.to_le_bytes() }
+    }
+
+    /// Rewards held by the reward vault that no emission covers
+    pub fn unallocated(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(176) as *const [u8; 8])) }
+    }
+
+    pub fn set_unallocated(&self, unallocated: u64) {
+        unsafe { *(self.0.add(176) as *mut [u8; 8]) = unallocated.to_le_bytes() }
+    }
+
+    /// Rewards settled in the stakes of the farm and not claimed yet
+    pub fn owed_rewards(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(184) as *const [u8; 8])) }
+    }
+
+    pub fn set_owed_rewards(&self, owed_rewards: u64) {
+        unsafe { *(self.0.add(184) as *mut [u8; 8]) = owed_rewards.to_le_bytes() }
+    }
+
+    /// Accumulates the rewards emitted since the last update. Nothing accrues
+    /// while no LP token is staked, those rewards are left unallocated.
+    pub fn update(&self, now: i64) -> Result<(), ProgramError> {
+        let last_update = self.last_update();
+        let elapsed = now
+            .min(self.reward_end())
+            .saturating_sub(last_update)
+            .max(0) as u128;
+        let total_staked = self.total_staked() as u128;
+
+        if elapsed > 0 && total_staked > 0 {
+            let reward_per_share = (self.reward_rate() as u128 * elapsed)
+                .checked_mul(REWARD_PRECISION)
+                .ok_or(ProgramError::ArithmeticOverflow)?
+                / total_staked;
+            let reward_per_share = self
+                .reward_per_share()
+                .checked_add(reward_per_share)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            unsafe { *(self.0.add(152) as *mut [u8; 16]) = reward_per_share.to_le_bytes() };
+        } else if elapsed > 0 {
+            let unallocated = u64::try_from(self.reward_rate() as u128 * elapsed)
+                .ok()
+                .and_then(|emitted| self.unallocated().checked_add(emitted))
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            self.set_unallocated(unallocated);
+        }
+        unsafe { *(self.0.add(144) as *mut [u8; 8]) = now.max(last_update).to_le_bytes() };
+
+        Ok(())
+    }
+}
+
+/// -- Stake --
+/// > Farm: Pubkey
+/// > Owner: Pubkey
+/// > Amount: u64
+/// > RewardPerSharePaid: u128
+/// > PendingRewards: u64
+///
+/// LP tokens staked by a user in a farm, at the PDA [STAKE_SEED, farm, owner].
+pub struct Stake(*const u8);
+
+impl Stake {
+    pub const LEN: usize = 32 + 32 + 8 + 16 + 8;
+
+    #[inline(always)]
+    pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
+        unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
+    }
+
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        if account_info.data_len() != Self::LEN || account_info.owner() != &crate::ID {
+            return Err(AmmError::InvalidStake.into());
+        }
+        Ok(Self::from_account_info_unchecked(account_info))
+    }
+
+    pub fn farm(&self) -> Pubkey {
+        unsafe { *(self.0 as *const [u8; 32]) }
+    }
+
+    pub fn owner(&self) -> Pubkey {
+        unsafe { *(self.0.add(32) as *const [u8; 32]) }
+    }
+
+    pub fn set_accounts(&self, farm: &Pubkey, owner: &Pubkey) {
+        unsafe {
+            *(self.0 as *mut [u8; 32]) = *farm;
+            *(self.0.add(32) as *mut [u8; 32]) = *owner;
+        }
+    }
+
+    pub fn amount(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(64) as *const [u8; 8])) }
+    }
+
+    pub fn set_amount(&self, amount: u64) {
+        unsafe { *(self.0.add(64) as *mut [u8; 8]) = amount.to_le_bytes() }
+    }
+
+    /// Reward per share of the farm when the rewards were last settled
+    pub fn reward_per_share_paid(&self) -> u128 {
+        unsafe { u128::from_le_bytes(*(self.0.add(72) as *const [u8; 16])) }
+    }
+
+    /// Rewards settled but not claimed yet
+    pub fn pending_rewards(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(88) as *const [u8; 8])) }
+    }
+
+    pub fn set_pending_rewards(&self, pending_rewards: u64) {
+        unsafe { *(self.0.add(88) as *mut [u8; 8]) = pending_rewards.to_le_bytes() }
+    }
+
+    /// Moves the rewards earned since the last settlement to the pending
+    /// rewards, owed by the farm. Must be called with an updated farm before
+    /// the amount changes.
+    pub fn settle(&self, farm: &Farm) -> Result<(), ProgramError> {
+        let reward_per_share = farm.reward_per_share();
+        let earned = (self.amount() as u128)
+            .checked_mul(reward_per_share - self.reward_per_share_paid())
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / REWARD_PRECISION;
+        let earned = u64::try_from(earned).map_err(|_| ProgramError::ArithmeticOverflow)?;
+        let pending_rewards = self
+            .pending_rewards()
+            .checked_add(earned)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let owed_rewards = farm
+            .owed_rewards()
+            .checked_add(earned)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.set_pending_rewards(pending_rewards);
+        farm.set_owed_rewards(owed_rewards);
+        unsafe { *(self.0.add(72) as *mut [u8; 16]) = reward_per_share.to_le_bytes() };
+
+        Ok(())
+    }
+}
+
+/// -- Twamm --
+/// > Config: Pubkey
+/// > SaleRate: u64
+/// > LastSlot: u64
+/// > ProceedsPerSaleRate: u128
+/// > Buckets: [Bucket; TWAMM_BUCKETS]
+///
+/// -- Bucket --
+/// > EndSlot: u64
+/// > SaleRateEnding: u64
+/// > ProceedsPerSaleRate: u128
+/// > Orders: u64
+///
+/// Virtual pool of the long-term orders of a pool, at the PDA [TWAMM_SEED,
+/// config], selling SaleRate X per slot into the pool. Orders end on a
+/// multiple of TWAMM_INTERVAL, each expiry having a bucket that holds the
+/// sale rate ending there and, once crossed, the proceeds per sale rate
+/// earned until then. A bucket is reused by a later expiry only once every
+/// order of the previous one is closed.
+pub struct Twamm(*const u8);
+
+impl Twamm {
+    pub const HEADER_LEN: usize = 32 + 8 + 8 + 16;
+    pub const BUCKET_LEN: usize = 8 + 8 + 16 + 8;
+    pub const LEN: usize = Self::HEADER_LEN + TWAMM_BUCKETS * Self::BUCKET_LEN;
+
+    #[inline(always)]
+    pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
+        unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
+    }
+
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        if account_info.data_len() != Self::LEN || account_info.owner() != &crate::ID {
+            return Err(AmmError::InvalidTwamm.into());
+        }
+        Ok(Self::from_account_info_unchecked(account_info))
+    }
+
+    /// TWAMM of the pool among `accounts`, required once the pool has one
+    pub fn from_config(
+        config: &Config,
+        accounts: &[AccountInfo],
+    ) -> Result<Option<Self>, ProgramError> {
+        let twamm = config.twamm();
+        if twamm == Pubkey::default() {
+            return Ok(None);
+        }
+        let account = accounts
+            .iter()
+            .find(|account| account.key() == &twamm)
+            .ok_or(AmmError::InvalidTwamm)?;
+        Ok(Some(Self::from_account_info(account)?))
+    }
+
+    pub fn config(&self) -> Pubkey {
+        unsafe { *(self.0 as *const [u8; 32]) }
+    }
+
+    pub fn init(&self, config: &Pubkey, slot: u64) {
+        unsafe {
+            *(self.0 as *mut [u8; 32]) = *config;
+            *(self.0.add(40) as *mut [u8; 8]) = slot.to_le_bytes();
+        }
+    }
+
+    /// X sold into the pool per slot by the open orders
+    pub fn sale_rate(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(32) as *const [u8; 8])) }
+    }
+
+    fn set_sale_rate(&self, sale_rate: u64) {
+        unsafe { *(self.0.add(32) as *mut [u8; 8]) = sale_rate.to_le_bytes() }
+    }
+
+    /// Slot up to which the orders are executed
+    pub fn last_slot(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(40) as *const [u8; 8])) }
+    }
+
+    /// Y earned per unit of sale rate since the TWAMM started, scaled by
+    /// PROCEEDS_PRECISION
+    pub fn proceeds_per_sale_rate(&self) -> u128 {
+        unsafe { u128::from_le_bytes(*(self.0.add(48) as *const [u8; 16])) }
+    }
+
+    #[inline(always)]
+    fn bucket(&self, end_slot: u64) -> *mut u8 {
+        let index = (end_slot / TWAMM_INTERVAL) as usize % TWAMM_BUCKETS;
+        unsafe { self.0.add(Self::HEADER_LEN + index * Self::BUCKET_LEN) as *mut u8 }
+    }
+
+    /// End slot, sale rate ending, proceeds per sale rate and open orders of
+    /// the bucket of `end_slot`, which may belong to another expiry
+    pub fn get_bucket(&self, end_slot: u64) -> (u64, u64, u128, u64) {
+        let bucket = self.bucket(end_slot);
+        unsafe {
+            (
+                u64::from_le_bytes(*(bucket as *const [u8; 8])),
+                u64::from_le_bytes(*(bucket.add(8) as *const [u8; 8])),
+                u128::from_le_bytes(*(bucket.add(16) as *const [u8; 16])),
+                u64::from_le_bytes(*(bucket.add(32) as *const [u8; 8])),
+            )
+        }
+    }
+
+    /// Orders placed and not yet closed, over every bucket
+    pub fn open_orders(&self) -> u64 {
+        (0..TWAMM_BUCKETS)
+            .map(|index| unsafe {
+                u64::from_le_bytes(
+                    *(self.0.add(Self::HEADER_LEN + index * Self::BUCKET_LEN + 32)
+                        as *const [u8; 8]),
+                )
+            })
+            .sum()
+    }
+
+    fn set_bucket(
+        &self,
+        end_slot: u64,
+        sale_rate_ending: u64,
+        proceeds_per_sale_rate: u128,
+        orders: u64,
+    ) {
+        let bucket = self.bucket(end_slot);
+        unsafe {
+            *(bucket as *mut [u8; 8]) = end_slot.to_le_bytes();
+            *(bucket.add(8) as *mut [u8; 8]) = sale_rate_ending.to_le_bytes();
+            *(bucket.add(16) as *mut [u8; 16]) = proceeds_per_sale_rate.to_le_bytes();
+            *(bucket.add(32) as *mut [u8; 8]) = orders.to_le_bytes();
+        }
+    }
+
+    /// Adds an order selling `sale_rate` per slot until `end_slot`. Must be
+    /// called on an executed TWAMM.
+    pub fn add_order(&self, end_slot: u64, sale_rate: u64) -> Result<(), ProgramError> {
+        let (bucket_end_slot, sale_rate_ending, _, orders) = self.get_bucket(end_slot);
+        let (sale_rate_ending, orders) = match bucket_end_slot == end_slot {
+            true => (sale_rate_ending, orders),
+            // The bucket still tracks an older expiry with orders left to close
+            false if orders != 0 => return Err(AmmError::InvalidOrder.into()),
+            false => (0, 0),
+        };
+        self.set_bucket(
+            end_slot,
+            sale_rate_ending
+                .checked_add(sale_rate)
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+            0,
+            orders + 1,
+        );
+        self.set_sale_rate(
+            self.sale_rate()
+                .checked_add(sale_rate)
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+        );
+        Ok(())
+    }
+
+    /// Removes an order closed at its expiry or, if it is still selling,
+    /// cancelled. Must be called on an executed TWAMM.
+    pub fn remove_order(&self, end_slot: u64, sale_rate: u64) {
+        let (_, sale_rate_ending, proceeds_per_sale_rate, orders) = self.get_bucket(end_slot);
+        if self.last_slot() < end_slot {
+            self.set_bucket(
+                end_slot,
+                sale_rate_ending - sale_rate,
+                proceeds_per_sale_rate,
+                orders - 1,
+            );
+            self.set_sale_rate(self.sale_rate() - sale_rate);
+        } else {
+            self.set_bucket(
+                end_slot,
+                sale_rate_ending,
+                proceeds_per_sale_rate,
+                orders - 1,
+            );
+        }
+    }
+
+    /// Proceeds per sale rate earned by an order ending at `end_slot`, frozen
+    /// once the TWAMM is executed past its expiry
+    pub fn proceeds_until(&self, end_slot: u64) -> u128 {
+        if self.last_slot() >= end_slot {
+            self.get_bucket(end_slot).2
+        } else {
+            self.proceeds_per_sale_rate()
+        }
+    }
+
+    /// Sells the X of the orders into the pool up to `slot`, one swap per
+    /// interval and each expiry crossed, the Y bought staying in the vault on
+    /// top of the reserves. Nothing executes while swaps are paused or a flash
+    /// loan is outstanding, the orders catching up afterwards. The swaps are
+    /// not held to the MaxPriceImpact of the pool: refusing them would stall
+    /// every instruction executing the orders.
+    pub fn execute(&self, config: &Config, slot: u64, now: i64) -> Result<(), ProgramError> {
+        let mut last_slot = self.last_slot();
+        if slot <= last_slot
+            || config.is_paused(PAUSE_SWAPS)
+            || config.flash_loan() != FLASH_LOAN_NONE
+        {
+            return Ok(());
+        }
+
+        // Accumulate the price before the reserves move
+        config.update_oracle(config.reserve_x(), config.reserve_y(), now)?;
+
+        let mut sale_rate = self.sale_rate();
+        let mut proceeds_per_sale_rate = self.proceeds_per_sale_rate();
+        while last_slot < slot {
+            // Without orders nothing can expire either
+            if sale_rate == 0 {
+                last_slot = slot;
+                break;
+            }
+
+            let boundary = (last_slot / TWAMM_INTERVAL + 1) * TWAMM_INTERVAL;
+            let next_slot = boundary.min(slot);
+
+            let amount_in = sale_rate
+                .checked_mul(next_slot - last_slot)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            let (amount_out, fee) = curve::swap_amount_out(
+                config,
+                true,
+                config.reserve_x(),
+                config.reserve_y(),
+                amount_in,
+                now,
+            )?;
+            let protocol_fee = config.accrue_protocol_fee(false, fee)?;
+            config.apply_swap(true, amount_in, amount_out + protocol_fee)?;
+            proceeds_per_sale_rate = proceeds_per_sale_rate
+                .checked_add(amount_out as u128 * PROCEEDS_PRECISION / sale_rate as u128)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+
+            // Orders ending at the boundary stop selling
+            let (end_slot, sale_rate_ending, _, orders) = self.get_bucket(boundary);
+            if next_slot == boundary && end_slot == boundary {
+                sale_rate -= sale_rate_ending;
+                self.set_bucket(end_slot, sale_rate_ending, proceeds_per_sale_rate, orders);
+            }
+            last_slot = next_slot;
+        }
+
+        self.set_sale_rate(sale_rate);
+        unsafe {
+            *(self.0.add(40) as *mut [u8; 8]) = last_slot.to_le_bytes();
+            *(self.0.add(48) as *mut [u8; 16]) = proceeds_per_sale_rate.to_le_bytes();
+        }
+
+        Ok(())
+    }
+}
+
+/// -- Order --
+/// > Twamm: Pubkey
+/// > Owner: Pubkey
+/// > SaleRate: u64
+/// > EndSlot: u64
+/// > ProceedsPerSaleRatePaid: u128
+///
+/// Long-term order of a user selling X, at the PDA [ORDER_SEED, twamm, owner].
+pub struct Order(*const u8);
+
+impl Order {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 16;
+
+    #[inline(always)]
+    pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
+        unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
+    }
+
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        if account_info.data_len() != Self::LEN || account_info.owner() != &crate::ID {
+            return Err(AmmError::InvalidOrder.into());
+        }
+        Ok(Self::from_account_info_unchecked(account_info))
+    }
+
+    pub fn twamm(&self) -> Pubkey {
+        unsafe { *(self.0 as *const [u8; 32]) }
+    }
+
+    pub fn owner(&self) -> Pubkey {
+        unsafe { *(self.0.add(32) as *const [u8; 32]) }
+    }
+
+    pub fn sale_rate(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(64) as *const [u8; 8])) }
+    }
+
+    pub fn end_slot(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(72) as *const [u8; 8])) }
+    }
+
+    /// Proceeds per sale rate of the TWAMM when the proceeds were last paid
+    pub fn proceeds_per_sale_rate_paid(&self) -> u128 {
+        unsafe { u128::from_le_bytes(*(self.0.add(80) as *const [u8; 16])) }
+    }
+
+    pub fn init(
+        &self,
+        twamm: &Pubkey,
+        owner: &Pubkey,
+        sale_rate: u64,
+        end_slot: u64,
+        proceeds_per_sale_rate: u128,
+    ) {
+        unsafe {
+            *(self.0 as *mut [u8; 32]) = *twamm;
+            *(self.0.add(32) as *mut [u8; 32]) = *owner;
+            *(self.0.add(64) as *mut [u8; 8]) = sale_rate.to_le_bytes();
+            *(self.0.add(72) as *mut [u8; 8]) = end_slot.to_le_bytes();
+            *(self.0.add(80) as *mut [u8; 16]) = proceeds_per_sale_rate.to_le_bytes();
+        }
+    }
+
+    /// Returns the Y earned since the proceeds were last paid and marks them
+    /// paid. Must be called with an executed TWAMM.
+    pub fn settle(&self, twamm: &Twamm) -> Result<u64, ProgramError> {
+        let proceeds_per_sale_rate = twamm.proceeds_until(self.end_slot());
+        let proceeds = (self.sale_rate() as u128)
+            .checked_mul(proceeds_per_sale_rate - self.proceeds_per_sale_rate_paid())
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / PROCEEDS_PRECISION;
+        unsafe { *(self.0.add(80) as *mut [u8; 16]) = proceeds_per_sale_rate.to_le_bytes() };
+        u64::try_from(proceeds).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+}
+
+/// -- Round --
+/// > Config: Pubkey
+/// > CommitEnd: u64
+/// > RevealEnd: u64
+/// > TotalX: u64
+/// > TotalY: u64
+/// > OutX: u64
+/// > OutY: u64
+/// > Settled: u8
+/// > Opener: Pubkey
+/// > Intents: u64
+/// > LeftX: u64
+/// > LeftY: u64
+///
+/// Batch round of a pool, at the PDA [BATCH_SEED, config, index] where index
+/// counts the rounds opened by the pool, see [`Config::batch_round`]. Totals
+/// are the amounts revealed on each side, outs what each side shares once
+/// settled: OutX the X owed to the sellers of Y and OutY the Y owed to the
+/// sellers of X. Intents counts the intents not closed yet and lefts what
+/// the round still holds, the round being closed to its opener along with
+/// its last intent.
+pub struct Round(*const u8);
+
+impl Round {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 32 + 8 + 8 + 8;
+
+    #[inline(always)]
+    pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
+        unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
+    }
+
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        if account_info.data_len() != Self::LEN || account_info.owner() != &crate::ID {
+            return Err(AmmError::InvalidBatch.into());
+        }
+        Ok(Self::from_account_info_unchecked(account_info))
+    }
+
+    pub fn config(&self) -> Pubkey {
+        unsafe { *(self.0 as *const [u8; 32]) }
+    }
+
+    /// First slot of the reveal window
+    pub fn commit_end(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(32) as *const [u8; 8])) }
+    }
+
+    /// First slot the round can be settled
+    pub fn reveal_end(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(40) as *const [u8; 8])) }
+    }
+
+    /// Whether the round was never settled and no longer can be, its
+    /// revealed swaps being refunded instead
+    pub fn timed_out(&self, slot: u64) -> bool {
+        !self.settled() && slot >= self.reveal_end().saturating_add(BATCH_TIMEOUT_SLOTS)
+    }
+
+    pub fn init(&self, config: &Pubkey, opener: &Pubkey, commit_end: u64, reveal_end: u64) {
+        unsafe {
+            *(self.0 as *mut [u8; 32]) = *config;
+            *(self.0.add(32) as *mut [u8; 8]) = commit_end.to_le_bytes();
+            *(self.0.add(40) as *mut [u8; 8]) = reveal_end.to_le_bytes();
+            *(self.0.add(81) as *mut [u8; 32]) = *opener;
+        }
+    }
+
+    /// Payer of the rent of the round, refunded when it is closed
+    pub fn opener(&self) -> Pubkey {
+        unsafe { *(self.0.add(81) as *const [u8; 32]) }
+    }
+
+    pub fn intents(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(113) as *const [u8; 8])) }
+    }
+
+    pub fn add_intent(&self) -> Result<(), ProgramError> {
+        let intents = self
+            .intents()
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        unsafe { *(self.0.add(113) as *mut [u8; 8]) = intents.to_le_bytes() };
+        Ok(())
+    }
+
+    pub fn left_x(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(121) as *const [u8; 8])) }
+    }
+
+    pub fn left_y(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(129) as *const [u8; 8])) }
+    }
+
+    fn set_left(&self, left_x: u64, left_y: u64) {
+        unsafe {
+            *(self.0.add(121) as *mut [u8; 8]) = left_x.to_le_bytes();
+            *(self.0.add(129) as *mut [u8; 8]) = left_y.to_le_bytes();
+        }
+    }
+
+    /// Closes an intent paid `amount` of X when `paid_x` or Y otherwise,
+    /// returning the intents left
+    pub fn close_intent(&self, paid_x: bool, amount: u64) -> Result<u64, ProgramError> {
+        let (left_x, left_y) = if paid_x {
+            (self.left_x().checked_sub(amount), Some(self.left_y()))
+        } else {
+            (Some(self.left_x()), self.left_y().checked_sub(amount))
+        };
+        let intents = self.intents().checked_sub(1);
+        let (Some(left_x), Some(left_y), Some(intents)) = (left_x, left_y, intents) else {
+            return Err(ProgramError::ArithmeticOverflow);
+        };
+        self.set_left(left_x, left_y);
+        unsafe { *(self.0.add(113) as *mut [u8; 8]) = intents.to_le_bytes() };
+        Ok(intents)
+    }
+
+    pub fn total_x(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(48) as *const [u8; 8])) }
+    }
+
+    pub fn total_y(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(56) as *const [u8; 8])) }
+    }
+
+    /// Adds a revealed sale of `amount` of X when `in_x` or Y otherwise
+    pub fn add(&self, in_x: bool, amount: u64) -> Result<(), ProgramError> {
+        let (offset, total) = if in_x {
+            (48, self.total_x())
+        } else {
+            (56, self.total_y())
+        };
+        let total = total
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        unsafe { *(self.0.add(offset) as *mut [u8; 8]) = total.to_le_bytes() };
+        self.set_left(self.total_x(), self.total_y());
+        Ok(())
+    }
+
+    pub fn out_x(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(64) as *const [u8; 8])) }
+    }
+
+    pub fn out_y(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(72) as *const [u8; 8])) }
+    }
+
+    pub fn settled(&self) -> bool {
+        unsafe { *self.0.add(80) != 0 }
+    }
+
+    pub fn settle(&self, out_x: u64, out_y: u64) {
+        unsafe {
+            *(self.0.add(64) as *mut [u8; 8]) = out_x.to_le_bytes();
+            *(self.0.add(72) as *mut [u8; 8]) = out_y.to_le_bytes();
+            *(self.0.add(80) as *mut u8) = 1;
+        }
+        self.set_left(out_x, out_y);
+    }
+
+    /// Share of a sale of `amount` of X when `in_x` or Y otherwise, paid in
+    /// the other token. Must be called on a settled round.
+    pub fn payout(&self, in_x: bool, amount: u64) -> u64 {
+        let (out, total) = if in_x {
+            (self.out_y(), self.total_x())
+        } else {
+            (self.out_x(), self.total_y())
+        };
+        (out as u128 * amount as u128 / total as u128) as u64
+    }
+}
+
+/// -- Intent --
+/// > Round: Pubkey
+/// > Owner: Pubkey
+/// > Commitment: [u8; 32]
+/// > InX: u8
+/// > Amount: u64
+///
+/// Swap committed by a user to a batch round, at the PDA [INTENT_SEED, round,
+/// owner]. InX and Amount are zero until revealed, Amount then being what the
+/// vault received.
+pub struct Intent(*const u8);
+
+impl Intent {
+    pub const LEN: usize = 32 + 32 + 32 + 1 + 8;
+
+    #[inline(always)]
+    pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
+        unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
+    }
+
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        if account_info.data_len() != Self::LEN || account_info.owner() != &crate::ID {
+            return Err(AmmError::InvalidIntent.into());
+        }
+        Ok(Self::from_account_info_unchecked(account_info))
+    }
+
+    pub fn round(&self) -> Pubkey {
+        unsafe { *(self.0 as *const [u8; 32]) }
+    }
+
+    pub fn owner(&self) -> Pubkey {
+        unsafe { *(self.0.add(32) as *const [u8; 32]) }
+    }
+
+    /// sha256(owner || in_x || amount || salt) of the swap, see [`Intent::hash`]
+    pub fn commitment(&self) -> [u8; 32] {
+        unsafe { *(self.0.add(64) as *const [u8; 32]) }
+    }
+
+    pub fn init(&self, round: &Pubkey, owner: &Pubkey, commitment: &[u8; 32]) {
+        unsafe {
+            *(self.0 as *mut [u8; 32]) = *round;
+            *(self.0.add(32) as *mut [u8; 32]) = *owner;
+            *(self.0.add(64) as *mut [u8; 32]) = *commitment;
+        }
+    }
+
+    pub fn in_x(&self) -> bool {
+        unsafe { *self.0.add(96) != 0 }
+    }
+
+    pub fn amount(&self) -> u64 {
+        unsafe { u64::from_le_bytes(*(self.0.add(97) as *const [u8; 8])) }
+    }
+
+    pub fn reveal(&self, in_x: bool, amount: u64) {
+        unsafe {
+            *(self.0.add(96) as *mut u8) = in_x as u8;
+            *(self.0.add(97) as *mut [u8; 8]) = amount.to_le_bytes();
+        }
+    }
+
+    /// Commitment to selling `amount` of X when `in_x` or Y otherwise, the
+    /// salt keeping the swap hidden until revealed
+    pub fn hash(owner: &Pubkey, in_x: bool, amount: u64, salt: &[u8; 32]) -> [u8; 32] {
+        hashv(&[owner, &[in_x as u8], &amount.to_le_bytes(), salt])
     }
 }
@@ -0,0 +1,64 @@
+//! System program helpers for the accounts the pool creates at derived
+//! addresses.
+//!
+//! Anyone can send lamports to an address before its account is created,
+//! which makes CreateAccount fail. Addresses that already hold lamports are
+//! topped up to rent exemption, allocated and assigned instead, so funding
+//! them beforehand cannot block their creation.
+
+use pinocchio::{account_info::AccountInfo, instruction::Signer, pubkey::Pubkey, ProgramResult};
+use pinocchio_system::instructions::{Allocate, Assign, CreateAccount, Transfer};
+
+/// Creates an account at a program derived address, even if the address was
+/// funded beforehand.
+///
+/// ### Accounts:
+///   0. `[WRITE, SIGNER]` The funding account.
+///   1. `[WRITE, SIGNER]` The new account, signed for by its seeds.
+pub struct CreatePda<'a> {
+    /// Funding account.
+    pub from: &'a AccountInfo,
+    /// New account.
+    pub to: &'a AccountInfo,
+    /// Number of lamports the new account holds at least.
+    pub lamports: u64,
+    /// Number of bytes of memory to allocate.
+    pub space: u64,
+    /// Address of program that will own the new account.
+    pub owner: &'a Pubkey,
+}
+
+impl CreatePda<'_> {
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        if self.to.lamports() == 0 {
+            return CreateAccount {
+                from: self.from,
+                to: self.to,
+                lamports: self.lamports,
+                space: self.space,
+                owner: self.owner,
+            }
+            .invoke_signed(signers);
+        }
+
+        let top_up = self.lamports.saturating_sub(self.to.lamports());
+        if top_up > 0 {
+            Transfer {
+                from: self.from,
+                to: self.to,
+                lamports: top_up,
+            }
+            .invoke()?;
+        }
+        Allocate {
+            account: self.to,
+            space: self.space,
+        }
+        .invoke_signed(signers)?;
+        Assign {
+            account: self.to,
+            owner: self.owner,
+        }
+        .invoke_signed(signers)
+    }
+}
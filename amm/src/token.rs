@@ -0,0 +1,531 @@
+//! Token program helpers working with both the Token program and Token-2022.
+//!
+//! Both programs share the base mint and token account layouts as well as the
+//! instruction encodings. Token-2022 mints and accounts may carry extensions
+//! after the base state, the only one the pool accounts for is the transfer
+//! fee, which is withheld from the amount received by the destination.
+//!
+//! Native SOL can be paid from and to a wallet in place of a token account
+//! of the wrapped SOL mint, being wrapped and unwrapped on the spot.
+
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{AccountMeta, Instruction, Seed, Signer},
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::instructions::Transfer;
+use pinocchio_token::instructions::SyncNative;
+
+use crate::{
+    constants::{MAX_FEE, NATIVE_MINT, NATIVE_SEED, TOKEN_2022_PROGRAM_ID},
+    error::AmmError,
+    system::CreatePda,
+};
+
+/// Size of the base mint state
+const MINT_LEN: usize = 82;
+/// Size of the base token account state, extensions start after it
+const ACCOUNT_LEN: usize = 165;
+/// Offset of the decimals in the base mint state
+const DECIMALS_OFFSET: usize = 44;
+/// Offset of the amount in the base token account state
+const AMOUNT_OFFSET: usize = 64;
+
+/// Token-2022 account types, stored right after the base state
+const ACCOUNT_TYPE_MINT: u8 = 1;
+
+/// Token-2022 extension types
+const EXTENSION_TRANSFER_FEE_CONFIG: u16 = 1;
+const EXTENSION_NON_TRANSFERABLE: u16 = 9;
+const EXTENSION_PERMANENT_DELEGATE: u16 = 12;
+const EXTENSION_TRANSFER_HOOK: u16 = 14;
+const EXTENSION_CONFIDENTIAL_TRANSFER_FEE_CONFIG: u16 = 16;
+
+/// Size of the TransferFeeConfig extension and of the TransferFeeAmount one
+const TRANSFER_FEE_CONFIG_LEN: usize = 108;
+const TRANSFER_FEE_AMOUNT_LEN: usize = 8;
+/// Offsets of the older and newer fees in the TransferFeeConfig extension
+const OLDER_TRANSFER_FEE_OFFSET: usize = 72;
+const NEWER_TRANSFER_FEE_OFFSET: usize = 90;
+
+/// Checks the mint is the expected one and the token program is the one
+/// owning it, either the Token program or Token-2022
+pub fn check_mint(
+    mint: &AccountInfo,
+    expected: &Pubkey,
+    token_program: &AccountInfo,
+) -> Result<(), ProgramError> {
+    if mint.key() != expected {
+        return Err(AmmError::InvalidMint.into());
+    }
+    if token_program.key() != &pinocchio_token::ID && token_program.key() != &TOKEN_2022_PROGRAM_ID
+    {
+        return Err(AmmError::InvalidTokenProgram.into());
+    }
+    if mint.owner() != token_program.key() {
+        return Err(AmmError::InvalidTokenProgram.into());
+    }
+
+    Ok(())
+}
+
+/// Decimals of a mint of either token program
+pub fn decimals(mint: &AccountInfo) -> Result<u8, ProgramError> {
+    let data = unsafe { mint.borrow_data_unchecked() };
+    data.get(DECIMALS_OFFSET)
+        .copied()
+        .filter(|_| data.len() >= MINT_LEN)
+        .ok_or(ProgramError::InvalidAccountData)
+}
+
+/// Balance of a token account of either token program
+pub fn amount(account: &AccountInfo) -> Result<u64, ProgramError> {
+    let data = unsafe { account.borrow_data_unchecked() };
+    data.get(AMOUNT_OFFSET..AMOUNT_OFFSET + 8)
+        .filter(|_| data.len() >= ACCOUNT_LEN)
+        .map(|amount| u64::from_le_bytes(amount.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidAccountData)
+}
+
+/// Whether `account` is the wallet of `user` passed in place of its token
+/// account of `mint`, which has to be the wrapped SOL mint
+pub fn is_native(
+    user: &AccountInfo,
+    account: &AccountInfo,
+    mint: &AccountInfo,
+) -> Result<bool, ProgramError> {
+    if account.key() != user.key() {
+        return Ok(false);
+    }
+    if mint.key() != &NATIVE_MINT {
+        return Err(AmmError::InvalidMint.into());
+    }
+    Ok(true)
+}
+
+/// Wraps `amount` of SOL from the `wallet` signer straight into the native
+/// token account `vault`
+pub fn wrap(wallet: &AccountInfo, vault: &AccountInfo, amount: u64) -> ProgramResult {
+    Transfer {
+        from: wallet,
+        to: vault,
+        lamports: amount,
+    }
+    .invoke()?;
+
+    SyncNative {
+        native_token: vault,
+    }
+    .invoke()
+}
+
+/// Size of a vault for the mint: the base account plus the TransferFeeAmount
+/// extension Token-2022 requires on accounts of transfer fee mints
+///
+/// Mints with extensions that could move tokens out of the vault or need
+/// extra accounts on every transfer are not supported.
+pub fn vault_len(mint: &AccountInfo) -> Result<usize, ProgramError> {
+    let data = unsafe { mint.borrow_data_unchecked() };
+    let mut len = ACCOUNT_LEN;
+
+    for (extension, _) in extensions(data) {
+        match extension {
+            EXTENSION_TRANSFER_FEE_CONFIG => {
+                // Account type, then the TLV header and value
+                len = ACCOUNT_LEN + 1 + 4 + TRANSFER_FEE_AMOUNT_LEN;
+            }
+            EXTENSION_NON_TRANSFERABLE
+            | EXTENSION_PERMANENT_DELEGATE
+            | EXTENSION_TRANSFER_HOOK
+            | EXTENSION_CONFIDENTIAL_TRANSFER_FEE_CONFIG => {
+                return Err(AmmError::InvalidMint.into())
+            }
+            _ => {}
+        }
+    }
+
+    Ok(len)
+}
+
+/// Iterates over the TLV extensions of a Token-2022 mint, nothing for base mints
+fn extensions(data: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut tlv = match data.get(ACCOUNT_LEN) {
+        Some(&ACCOUNT_TYPE_MINT) => &data[ACCOUNT_LEN + 1..],
+        _ => &[][..],
+    };
+
+    core::iter::from_fn(move || {
+        let extension = u16::from_le_bytes(tlv.get(0..2)?.try_into().ok()?);
+        let len = u16::from_le_bytes(tlv.get(2..4)?.try_into().ok()?) as usize;
+        let value = tlv.get(4..4 + len)?;
+        tlv = &tlv[4 + len..];
+        // Uninitialized extensions mark the end of the TLV data
+        (extension != 0).then_some((extension, value))
+    })
+}
+
+/// Transfer fee of a mint in effect for an epoch, zero for mints without the
+/// extension
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct TransferFee {
+    pub maximum_fee: u64,
+    pub basis_points: u16,
+}
+
+impl TransferFee {
+    pub fn from_account_info(mint: &AccountInfo, epoch: u64) -> Self {
+        Self::from_mint_data(unsafe { mint.borrow_data_unchecked() }, epoch)
+    }
+
+    pub fn from_mint_data(data: &[u8], epoch: u64) -> Self {
+        let Some((_, config)) = extensions(data).find(|(extension, value)| {
+            *extension == EXTENSION_TRANSFER_FEE_CONFIG && value.len() == TRANSFER_FEE_CONFIG_LEN
+        }) else {
+            return Self::default();
+        };
+
+        let read = |offset: usize| {
+            (
+                u64::from_le_bytes(config[offset..offset + 8].try_into().unwrap()),
+                u64::from_le_bytes(config[offset + 8..offset + 16].try_into().unwrap()),
+                u16::from_le_bytes(config[offset + 16..offset + 18].try_into().unwrap()),
+            )
+        };
+
+        // The newer fee applies from its epoch on
+        let (newer_epoch, ..) = read(NEWER_TRANSFER_FEE_OFFSET);
+        let (_, maximum_fee, basis_points) = read(if epoch >= newer_epoch {
+            NEWER_TRANSFER_FEE_OFFSET
+        } else {
+            OLDER_TRANSFER_FEE_OFFSET
+        });
+
+        Self {
+            maximum_fee,
+            basis_points,
+        }
+    }
+
+    /// Fee withheld on a transfer of amount, rounded up
+    pub fn fee(&self, amount: u64) -> u64 {
+        if self.basis_points == 0 || amount == 0 {
+            return 0;
+        }
+
+        let fee = (amount as u128 * self.basis_points as u128).div_ceil(MAX_FEE as u128);
+        fee.min(self.maximum_fee as u128) as u64
+    }
+
+    /// Amount received by the destination of a transfer of amount
+    pub fn amount_received(&self, amount: u64) -> u64 {
+        amount - self.fee(amount)
+    }
+
+    /// Amount to transfer for the destination to receive amount
+    pub fn amount_to_send(&self, amount: u64) -> Result<u64, ProgramError> {
+        let with_maximum_fee = || {
+            amount
+                .checked_add(self.maximum_fee)
+                .ok_or(ProgramError::ArithmeticOverflow)
+        };
+
+        match (self.basis_points, amount) {
+            (0, _) | (_, 0) => Ok(amount),
+            (MAX_FEE, _) => with_maximum_fee(),
+            (basis_points, _) => {
+                let amount_to_send =
+                    (amount as u128 * MAX_FEE as u128).div_ceil((MAX_FEE - basis_points) as u128);
+                if amount_to_send - amount as u128 >= self.maximum_fee as u128 {
+                    with_maximum_fee()
+                } else {
+                    u64::try_from(amount_to_send).map_err(|_| ProgramError::ArithmeticOverflow)
+                }
+            }
+        }
+    }
+}
+
+/// Transfer Tokens from one Token Account to another, through the token
+/// program owning the mint.
+///
+/// ### Accounts:
+///   0. `[WRITE]` The source account.
+///   1. `[]` The token mint.
+///   2. `[WRITE]` The destination account.
+///   3. `[SIGNER]` The source account's owner/delegate.
+pub struct TransferChecked<'a> {
+    /// Sender account.
+    pub from: &'a AccountInfo,
+    /// Mint Account
+    pub mint: &'a AccountInfo,
+    /// Recipient account.
+    pub to: &'a AccountInfo,
+    /// Authority account.
+    pub authority: &'a AccountInfo,
+    /// Token program owning the mint.
+    pub token_program: &'a AccountInfo,
+    /// Amount of microtokens to transfer.
+    pub amount: u64,
+    /// Decimal for the Token
+    pub decimals: u8,
+}
+
+impl TransferChecked<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas: [AccountMeta; 4] = [
+            AccountMeta::writable(self.from.key()),
+            AccountMeta::readonly(self.mint.key()),
+            AccountMeta::writable(self.to.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        // Instruction data layout:
+        // -  [0]: instruction discriminator
+        // -  [1..9]: amount
+        // -  [9]: decimals
+        let mut instruction_data = [0u8; 10];
+        instruction_data[0] = 12;
+        instruction_data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        instruction_data[9] = self.decimals;
+
+        let instruction = Instruction {
+            program_id: self.token_program.key(),
+            accounts: &account_metas,
+            data: &instruction_data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.from, self.mint, self.to, self.authority],
+            signers,
+        )
+    }
+}
+
+/// Initialize a new Token Account, through the token program owning the mint.
+///
+/// ### Accounts:
+///   0. `[WRITE]`  The account to initialize.
+///   1. `[]` The mint this account will be associated with.
+pub struct InitializeAccount3<'a> {
+    /// New Account.
+    pub token: &'a AccountInfo,
+    /// Mint Account.
+    pub mint: &'a AccountInfo,
+    /// Owner of the new Account.
+    pub owner: &'a Pubkey,
+    /// Token program owning the mint.
+    pub token_program: &'a AccountInfo,
+}
+
+impl InitializeAccount3<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas: [AccountMeta; 2] = [
+            AccountMeta::writable(self.token.key()),
+            AccountMeta::readonly(self.mint.key()),
+        ];
+
+        // Instruction data layout:
+        // -  [0]: instruction discriminator
+        // -  [1..33]: owner
+        let mut instruction_data = [0u8; 33];
+        instruction_data[0] = 18;
+        instruction_data[1..].copy_from_slice(self.owner);
+
+        let instruction = Instruction {
+            program_id: self.token_program.key(),
+            accounts: &account_metas,
+            data: &instruction_data,
+        };
+
+        invoke_signed(&instruction, &[self.token, self.mint], &[])
+    }
+}
+
+/// Close an empty Token Account, through the token program owning it.
+///
+/// ### Accounts:
+///   0. `[WRITE]` The account to close.
+///   1. `[WRITE]` The destination account for the rent.
+///   2. `[SIGNER]` The account's owner.
+pub struct CloseAccount<'a> {
+    /// Token Account.
+    pub account: &'a AccountInfo,
+    /// Destination Account.
+    pub destination: &'a AccountInfo,
+    /// Owner Account.
+    pub authority: &'a AccountInfo,
+    /// Token program owning the account.
+    pub token_program: &'a AccountInfo,
+}
+
+impl CloseAccount<'_> {
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas: [AccountMeta; 3] = [
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::writable(self.destination.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        let instruction = Instruction {
+            program_id: self.token_program.key(),
+            accounts: &account_metas,
+            data: &[9],
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.account, self.destination, self.authority],
+            signers,
+        )
+    }
+}
+
+/// Unwrap SOL from a native token account to a wallet, through the token
+/// account PDA [NATIVE_SEED, config] opened and closed on the spot, its rent
+/// being fronted by the wallet.
+///
+/// ### Accounts:
+///   0. `[WRITE]` The native source account.
+///   1. `[WRITE]` The temporary token account.
+///   2. `[]` The wrapped SOL mint.
+///   3. `[SIGNER]` The source account's owner.
+///   4. `[WRITE, SIGNER]` The wallet receiving the SOL.
+pub struct Unwrap<'a> {
+    /// Native source account.
+    pub from: &'a AccountInfo,
+    /// Temporary token account.
+    pub account: &'a AccountInfo,
+    /// Wrapped SOL mint.
+    pub mint: &'a AccountInfo,
+    /// Owner of the source account, signed for by the caller.
+    pub authority: &'a AccountInfo,
+    /// Wallet receiving the SOL.
+    pub wallet: &'a AccountInfo,
+    /// Token program owning the mint.
+    pub token_program: &'a AccountInfo,
+    /// Pool the temporary account is derived from.
+    pub config: &'a Pubkey,
+    /// Amount of lamports to unwrap.
+    pub amount: u64,
+}
+
+impl Unwrap<'_> {
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let (key, bump) = find_program_address(&[NATIVE_SEED, self.config], &crate::ID);
+        if self.account.key() != &key {
+            return Err(AmmError::InvalidNativeAccount.into());
+        }
+        let binding = [bump];
+        let seeds = [
+            Seed::from(NATIVE_SEED),
+            Seed::from(self.config),
+            Seed::from(&binding),
+        ];
+        let account_signer = [Signer::from(&seeds)];
+
+        // Lamports sent to the address beforehand cannot block its creation,
+        // they are topped up to rent exemption and end up in the wallet
+        CreatePda {
+            from: self.wallet,
+            to: self.account,
+            lamports: Rent::get()?.minimum_balance(ACCOUNT_LEN),
+            space: ACCOUNT_LEN as u64,
+            owner: self.token_program.key(),
+        }
+        .invoke_signed(&account_signer)?;
+
+        InitializeAccount3 {
+            token: self.account,
+            mint: self.mint,
+            owner: self.authority.key(),
+            token_program: self.token_program,
+        }
+        .invoke()?;
+
+        TransferChecked {
+            from: self.from,
+            mint: self.mint,
+            to: self.account,
+            authority: self.authority,
+            token_program: self.token_program,
+            amount: self.amount,
+            decimals: decimals(self.mint)?,
+        }
+        .invoke_signed(signers)?;
+
+        CloseAccount {
+            account: self.account,
+            destination: self.wallet,
+            authority: self.authority,
+            token_program: self.token_program,
+        }
+        .invoke_signed(signers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::token::{TransferFee, ACCOUNT_LEN, ACCOUNT_TYPE_MINT, MINT_LEN};
+
+    fn mint_data(older: (u64, u64, u16), newer: (u64, u64, u16)) -> Vec<u8> {
+        let mut data = vec![0u8; ACCOUNT_LEN];
+        data[MINT_LEN - 1] = 1;
+        data.push(ACCOUNT_TYPE_MINT);
+        data.extend(1u16.to_le_bytes());
+        data.extend(108u16.to_le_bytes());
+        data.extend([0u8; 72]);
+        for (epoch, maximum_fee, basis_points) in [older, newer] {
+            data.extend(epoch.to_le_bytes());
+            data.extend(maximum_fee.to_le_bytes());
+            data.extend(basis_points.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn transfer_fee_of_epoch() {
+        let data = mint_data((0, 5_000, 100), (10, 1_000, 50));
+
+        let older = TransferFee::from_mint_data(&data, 9);
+        assert_eq!(older.basis_points, 100);
+        assert_eq!(older.fee(1_000_000), 5_000);
+        assert_eq!(older.fee(1_001), 11);
+
+        let newer = TransferFee::from_mint_data(&data, 10);
+        assert_eq!(newer.basis_points, 50);
+        assert_eq!(newer.fee(1_000_000), 1_000);
+
+        // Base mints have no fee
+        assert_eq!(
+            TransferFee::from_mint_data(&[0u8; MINT_LEN], 10),
+            TransferFee::default()
+        );
+    }
+
+    #[test]
+    fn transfer_fee_amount_to_send() {
+        let fee = TransferFee {
+            maximum_fee: 50_000,
+            basis_points: 100,
+        };
+        assert_eq!(fee.amount_to_send(990_000).unwrap(), 1_000_000);
+        assert_eq!(fee.amount_received(1_000_000), 990_000);
+        // Capped by the maximum fee
+        assert_eq!(fee.amount_to_send(10_000_000).unwrap(), 10_050_000);
+
+        for amount in [1, 99, 100, 101, 12_345, 1_000_000] {
+            let sent = fee.amount_to_send(amount).unwrap();
+            assert!(fee.amount_received(sent) >= amount);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod authority_tests {
+    use crate::shared::{self};
+    use amm::AmmError;
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::{AccountSharedData, ReadableAccount, WritableAccount},
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    struct Authorities {
+        update_authority: Pubkey,
+        pending_authority: Pubkey,
+    }
+
+    fn process_authority(
+        data: &[u8],
+        signer: impl FnOnce(&Authorities) -> Pubkey,
+        protocol_fees: u64,
+    ) -> (InstructionResult, Authorities, Pubkey) {
+        let (mollusk, program_id) = shared::setup();
+
+        let authorities = Authorities {
+            update_authority: Pubkey::new_unique(),
+            pending_authority: Pubkey::new_unique(),
+        };
+        let signer = signer(&authorities);
+        let config = Pubkey::new_unique();
+
+        let mut config_account = shared::create_config(
+            &mollusk,
+            0,
+            authorities.update_authority,
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            1_000u16,
+            u8::MAX,
+            program_id,
+        );
+        config_account.data_as_mut_slice()[320..352]
+            .copy_from_slice(authorities.pending_authority.as_ref());
+        config_account.data_as_mut_slice()[288..320].copy_from_slice(Pubkey::new_unique().as_ref());
+        shared::set_protocol_fees(&mut config_account, 1_000, protocol_fees, 0);
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            data,
+            vec![
+                AccountMeta::new(signer, true),
+                AccountMeta::new(config, false),
+            ],
+        );
+
+        let result: InstructionResult = mollusk.process_instruction(
+            &instruction,
+            &[
+                (
+                    signer,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (config, config_account),
+            ],
+        );
+
+        (result, authorities, config)
+    }
+
+    fn get_authorities(result: &InstructionResult, config: Pubkey) -> (Pubkey, Pubkey) {
+        let data = result.get_account(&config).unwrap().data();
+        (
+            Pubkey::try_from(&data[1..33]).unwrap(),
+            Pubkey::try_from(&data[320..352]).unwrap(),
+        )
+    }
+
+    #[test]
+    fn propose_authority() {
+        let new_authority = Pubkey::new_unique();
+        let data = [vec![13], new_authority.to_bytes().to_vec()].concat();
+        let (result, authorities, config) =
+            process_authority(&data, |authorities| authorities.update_authority, 0);
+
+        assert!(!result.program_result.is_err());
+        assert_eq!(
+            get_authorities(&result, config),
+            (authorities.update_authority, new_authority)
+        );
+    }
+
+    #[test]
+    fn propose_authority_fails_with_invalid_authority() {
+        let data = [vec![13], Pubkey::new_unique().to_bytes().to_vec()].concat();
+        let (result, ..) = process_authority(&data, |authorities| authorities.pending_authority, 0);
+
+        shared::expect_error(&result, AmmError::InvalidAuthority);
+    }
+
+    #[test]
+    fn accept_authority() {
+        let (result, authorities, config) =
+            process_authority(&[14], |authorities| authorities.pending_authority, 0);
+
+        assert!(!result.program_result.is_err());
+        assert_eq!(
+            get_authorities(&result, config),
+            (authorities.pending_authority, Pubkey::default())
+        );
+    }
+
+    #[test]
+    fn accept_authority_fails_with_invalid_authority() {
+        let (result, ..) = process_authority(&[14], |authorities| authorities.update_authority, 0);
+
+        shared::expect_error(&result, AmmError::InvalidAuthority);
+    }
+
+    #[test]
+    fn renounce_authority() {
+        let (result, _, config) =
+            process_authority(&[15], |authorities| authorities.update_authority, 0);
+
+        assert!(!result.program_result.is_err());
+        assert_eq!(
+            get_authorities(&result, config),
+            (Pubkey::default(), Pubkey::default())
+        );
+        let data = result.get_account(&config).unwrap().data();
+        assert_eq!(&data[288..320], Pubkey::default().as_ref());
+        assert_eq!(&data[196..198], &0u16.to_le_bytes());
+    }
+
+    #[test]
+    fn renounce_authority_fails_with_uncollected_protocol_fees() {
+        let (result, ..) =
+            process_authority(&[15], |authorities| authorities.update_authority, 100);
+
+        shared::expect_error(&result, AmmError::ProtocolFeesNotCollected);
+    }
+
+    #[test]
+    fn renounce_authority_fails_with_invalid_authority() {
+        let (result, ..) = process_authority(&[15], |_| Pubkey::new_unique(), 0);
+
+        shared::expect_error(&result, AmmError::InvalidAuthority);
+    }
+}
@@ -0,0 +1,706 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod batch_tests {
+    use crate::shared::{self, Pool};
+    use amm::{AmmError, Intent, Round, BATCH_SEED, BATCH_TIMEOUT_SLOTS, INTENT_SEED};
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    const RESERVE: u64 = 1_000_000_000;
+    // Rounds of 10 slots committing and 10 slots revealing, the last one
+    // opened at slot 100 revealing from slot 110 to slot 120
+    const COMMIT_SLOTS: u64 = 10;
+    const REVEAL_SLOTS: u64 = 10;
+    const INDEX: u64 = 5;
+    const COMMIT_END: u64 = 110;
+    const REVEAL_END: u64 = 120;
+    // The round sells 10_000_000 X against 4_000_000 Y, the user selling
+    // 6_000_000 of the X
+    const TOTAL_X: u64 = 10_000_000;
+    const TOTAL_Y: u64 = 4_000_000;
+    const AMOUNT: u64 = 6_000_000;
+    const SALT: [u8; 32] = [7; 32];
+
+    /// Round `index` of the pool and intent of the user in it
+    fn batch_addresses(pool: &Pool, index: u64) -> (Pubkey, Pubkey) {
+        let (round, _) = Pubkey::find_program_address(
+            &[BATCH_SEED, pool.config.as_ref(), &index.to_le_bytes()],
+            &pool.program_id,
+        );
+        let (intent, _) = Pubkey::find_program_address(
+            &[INTENT_SEED, round.as_ref(), pool.user.as_ref()],
+            &pool.program_id,
+        );
+        (round, intent)
+    }
+
+    /// Sets up a pool in batch mode with a 10% fee and even reserves, round
+    /// INDEX being the last one opened and holding `revealed` X and Y on top
+    /// of the reserves
+    fn setup_pool(slot: u64, revealed: (u64, u64)) -> Pool {
+        let mut pool = shared::setup_pool(Pubkey::new_unique(), RESERVE);
+        pool.mollusk.sysvars.clock.slot = slot;
+
+        let config = pool.config;
+        let config_account = pool.account_mut(&config);
+        shared::set_batch(config_account, COMMIT_SLOTS, REVEAL_SLOTS);
+        shared::set_batch_round(config_account, INDEX, COMMIT_END, REVEAL_END);
+        shared::set_batch_held(config_account, revealed.0, revealed.1);
+        for (vault, mint, amount) in [
+            (pool.vault_x, pool.mint_x, revealed.0),
+            (pool.vault_y, pool.mint_y, revealed.1),
+        ] {
+            let vault_account = shared::create_token_account(
+                &pool.mollusk,
+                mint,
+                pool.authority,
+                RESERVE + amount,
+                pool.token_program,
+            );
+            pool.set_account(vault, vault_account);
+        }
+
+        pool
+    }
+
+    /// Adds round INDEX, opened by the update authority, with `totals`
+    /// revealed, settled with `outs` if any, and `intents` still open on
+    /// what is `left` of it
+    fn add_round(
+        pool: &mut Pool,
+        totals: (u64, u64),
+        outs: Option<(u64, u64)>,
+        intents: u64,
+        left: (u64, u64),
+    ) {
+        let (round, _) = batch_addresses(pool, INDEX);
+        let mut round_account = AccountSharedData::new(
+            pool.mollusk.sysvars.rent.minimum_balance(Round::LEN),
+            Round::LEN,
+            &pool.program_id,
+        );
+        let (out_x, out_y) = outs.unwrap_or_default();
+        let data = [
+            pool.config.to_bytes().to_vec(),
+            COMMIT_END.to_le_bytes().to_vec(),
+            REVEAL_END.to_le_bytes().to_vec(),
+            totals.0.to_le_bytes().to_vec(),
+            totals.1.to_le_bytes().to_vec(),
+            out_x.to_le_bytes().to_vec(),
+            out_y.to_le_bytes().to_vec(),
+            vec![outs.is_some() as u8], // settled
+            pool.update_authority.to_bytes().to_vec(),
+            intents.to_le_bytes().to_vec(),
+            left.0.to_le_bytes().to_vec(),
+            left.1.to_le_bytes().to_vec(),
+        ]
+        .concat();
+        round_account.set_data_from_slice(&data);
+        pool.accounts.push((round, round_account));
+    }
+
+    /// Adds the intent of the user selling AMOUNT of X, revealed or not
+    fn add_intent(pool: &mut Pool, revealed: bool) {
+        let (round, intent) = batch_addresses(pool, INDEX);
+        let mut intent_account = AccountSharedData::new(
+            pool.mollusk.sysvars.rent.minimum_balance(Intent::LEN),
+            Intent::LEN,
+            &pool.program_id,
+        );
+        let amount = if revealed { AMOUNT } else { 0 };
+        let data = [
+            round.to_bytes().to_vec(),
+            pool.user.to_bytes().to_vec(),
+            Intent::hash(&pool.user.to_bytes(), true, AMOUNT, &SALT).to_vec(),
+            vec![revealed as u8], // in x
+            amount.to_le_bytes().to_vec(),
+        ]
+        .concat();
+        intent_account.set_data_from_slice(&data);
+        pool.accounts.push((intent, intent_account));
+    }
+
+    fn get_round(result: &InstructionResult, round: Pubkey) -> (u64, u64, u64, u64, bool) {
+        let data = result.get_account(&round).unwrap().data();
+        (
+            u64::from_le_bytes(data[48..56].try_into().unwrap()),
+            u64::from_le_bytes(data[56..64].try_into().unwrap()),
+            u64::from_le_bytes(data[64..72].try_into().unwrap()),
+            u64::from_le_bytes(data[72..80].try_into().unwrap()),
+            data[80] != 0,
+        )
+    }
+
+    /// Intents still open and what is left of the round
+    fn get_round_left(result: &InstructionResult, round: Pubkey) -> (u64, u64, u64) {
+        let data = result.get_account(&round).unwrap().data();
+        (
+            u64::from_le_bytes(data[113..121].try_into().unwrap()),
+            u64::from_le_bytes(data[121..129].try_into().unwrap()),
+            u64::from_le_bytes(data[129..137].try_into().unwrap()),
+        )
+    }
+
+    fn get_batch_held(result: &InstructionResult, config: Pubkey) -> (u64, u64) {
+        let data = result.get_account(&config).unwrap().data();
+        (
+            u64::from_le_bytes(data[522..530].try_into().unwrap()),
+            u64::from_le_bytes(data[530..538].try_into().unwrap()),
+        )
+    }
+
+    fn set_batch_instruction(pool: &Pool, commit_slots: u64, reveal_slots: u64) -> Instruction {
+        let data = [
+            vec![31],
+            commit_slots.to_le_bytes().to_vec(),
+            reveal_slots.to_le_bytes().to_vec(),
+        ]
+        .concat();
+        Instruction::new_with_bytes(
+            pool.program_id,
+            &data,
+            vec![
+                AccountMeta::new(pool.update_authority, true),
+                AccountMeta::new(pool.config, false),
+            ],
+        )
+    }
+
+    fn commit_swap_instruction(pool: &Pool, index: u64) -> Instruction {
+        let (round, intent) = batch_addresses(pool, index);
+        let data = [
+            vec![32],
+            Intent::hash(&pool.user.to_bytes(), true, AMOUNT, &SALT).to_vec(),
+        ]
+        .concat();
+        Instruction::new_with_bytes(
+            pool.program_id,
+            &data,
+            vec![
+                AccountMeta::new(pool.user, true),
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new(round, false),
+                AccountMeta::new(intent, false),
+                AccountMeta::new_readonly(pool.system_program, false),
+            ],
+        )
+    }
+
+    fn reveal_swap_instruction(pool: &Pool, amount: u64) -> Instruction {
+        let (round, intent) = batch_addresses(pool, INDEX);
+        let data = [vec![33], amount.to_le_bytes().to_vec(), SALT.to_vec()].concat();
+        Instruction::new_with_bytes(
+            pool.program_id,
+            &data,
+            vec![
+                AccountMeta::new(pool.user, true),
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new(round, false),
+                AccountMeta::new(intent, false),
+                AccountMeta::new(pool.user_x, false),
+                AccountMeta::new(pool.vault_x, false),
+                AccountMeta::new_readonly(pool.mint_x, false),
+                AccountMeta::new_readonly(pool.token_program, false),
+            ],
+        )
+    }
+
+    fn settle_batch_instruction(pool: &Pool) -> Instruction {
+        let (round, _) = batch_addresses(pool, INDEX);
+        Instruction::new_with_bytes(
+            pool.program_id,
+            &[34],
+            vec![
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new(round, false),
+            ],
+        )
+    }
+
+    /// Claims the Y bought by the intent, or the X it sold when `refund`
+    fn claim_batch_instruction(pool: &Pool, refund: bool) -> Instruction {
+        let (round, intent) = batch_addresses(pool, INDEX);
+        let (user_to, vault_to, mint_to) = if refund {
+            (pool.user_x, pool.vault_x, pool.mint_x)
+        } else {
+            (pool.user_y, pool.vault_y, pool.mint_y)
+        };
+        Instruction::new_with_bytes(
+            pool.program_id,
+            &[35],
+            vec![
+                AccountMeta::new(pool.user, true),
+                AccountMeta::new_readonly(pool.authority, false),
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new(round, false),
+                AccountMeta::new(pool.update_authority, false),
+                AccountMeta::new(intent, false),
+                AccountMeta::new(user_to, false),
+                AccountMeta::new(vault_to, false),
+                AccountMeta::new_readonly(mint_to, false),
+                AccountMeta::new_readonly(pool.token_program, false),
+            ],
+        )
+    }
+
+    #[test]
+    fn set_batch() {
+        let pool = setup_pool(100, (0, 0));
+        let instruction = set_batch_instruction(&pool, 20, 30);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        let config_data = result.get_account(&pool.config).unwrap().data();
+        assert_eq!(
+            u64::from_le_bytes(config_data[440..448].try_into().unwrap()),
+            20
+        );
+        assert_eq!(
+            u64::from_le_bytes(config_data[448..456].try_into().unwrap()),
+            30
+        );
+    }
+
+    #[test]
+    fn set_batch_fails_with_one_window() {
+        let pool = setup_pool(100, (0, 0));
+        let instruction = set_batch_instruction(&pool, 20, 0);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        shared::expect_error(&result, AmmError::InvalidBatch);
+    }
+
+    #[test]
+    fn commit_swap_opens_round() {
+        // Round INDEX is revealed, the commitment opens the next one
+        let mut pool = setup_pool(REVEAL_END, (0, 0));
+        let (round, intent) = batch_addresses(&pool, INDEX + 1);
+
+        pool.accounts.extend([
+            (round, AccountSharedData::default()),
+            (intent, AccountSharedData::default()),
+        ]);
+
+        let instruction = commit_swap_instruction(&pool, INDEX + 1);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        let round_data = result.get_account(&round).unwrap().data();
+        assert_eq!(round_data.len(), Round::LEN);
+        assert_eq!(&round_data[..32], pool.config.as_ref());
+        assert_eq!(
+            u64::from_le_bytes(round_data[32..40].try_into().unwrap()),
+            REVEAL_END + COMMIT_SLOTS
+        );
+        assert_eq!(
+            u64::from_le_bytes(round_data[40..48].try_into().unwrap()),
+            REVEAL_END + COMMIT_SLOTS + REVEAL_SLOTS
+        );
+        assert_eq!(get_round(&result, round), (0, 0, 0, 0, false));
+        assert_eq!(&round_data[81..113], pool.user.as_ref());
+        assert_eq!(get_round_left(&result, round), (1, 0, 0));
+
+        let config_data = result.get_account(&pool.config).unwrap().data();
+        assert_eq!(
+            u64::from_le_bytes(config_data[498..506].try_into().unwrap()),
+            INDEX + 1
+        );
+        assert_eq!(
+            u64::from_le_bytes(config_data[506..514].try_into().unwrap()),
+            REVEAL_END + COMMIT_SLOTS
+        );
+
+        let intent_data = result.get_account(&intent).unwrap().data();
+        assert_eq!(intent_data.len(), Intent::LEN);
+        assert_eq!(&intent_data[..32], round.as_ref());
+        assert_eq!(&intent_data[32..64], pool.user.as_ref());
+        assert_eq!(
+            intent_data[64..96],
+            Intent::hash(&pool.user.to_bytes(), true, AMOUNT, &SALT)
+        );
+        assert_eq!(&intent_data[96..], &[0; 9]);
+    }
+
+    #[test]
+    fn commit_swap_joins_round() {
+        let mut pool = setup_pool(105, (0, 0));
+        let (round, intent) = batch_addresses(&pool, INDEX);
+        add_round(&mut pool, (0, 0), None, 1, (0, 0));
+        pool.accounts.push((intent, AccountSharedData::default()));
+
+        let instruction = commit_swap_instruction(&pool, INDEX);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        // The round keeps its windows and opener
+        let round_data = result.get_account(&round).unwrap().data();
+        assert_eq!(
+            u64::from_le_bytes(round_data[32..40].try_into().unwrap()),
+            COMMIT_END
+        );
+        assert_eq!(&round_data[81..113], pool.update_authority.as_ref());
+        assert_eq!(get_round_left(&result, round), (2, 0, 0));
+        assert_eq!(
+            result.get_account(&intent).unwrap().data().len(),
+            Intent::LEN
+        );
+    }
+
+    #[test]
+    fn commit_swap_fails_in_reveal_window() {
+        let mut pool = setup_pool(COMMIT_END, (0, 0));
+        let (round, intent) = batch_addresses(&pool, INDEX + 1);
+
+        pool.accounts.extend([
+            (round, AccountSharedData::default()),
+            (intent, AccountSharedData::default()),
+        ]);
+
+        let instruction = commit_swap_instruction(&pool, INDEX + 1);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        shared::expect_error(&result, AmmError::InvalidBatch);
+    }
+
+    #[test]
+    fn reveal_swap() {
+        let mut pool = setup_pool(COMMIT_END, (0, TOTAL_Y));
+        let (round, intent) = batch_addresses(&pool, INDEX);
+        add_round(&mut pool, (0, TOTAL_Y), None, 2, (0, TOTAL_Y));
+        add_intent(&mut pool, false);
+
+        let instruction = reveal_swap_instruction(&pool, AMOUNT);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, pool.user_x, RESERVE - AMOUNT);
+        shared::expect_token_balance(&result, pool.vault_x, RESERVE + AMOUNT);
+        // The X waits on top of the reserves
+        assert_eq!(
+            shared::get_reserves(&result, pool.config),
+            (RESERVE, RESERVE)
+        );
+        assert_eq!(get_batch_held(&result, pool.config), (AMOUNT, TOTAL_Y));
+        assert_eq!(get_round(&result, round), (AMOUNT, TOTAL_Y, 0, 0, false));
+        assert_eq!(get_round_left(&result, round), (2, AMOUNT, TOTAL_Y));
+
+        let intent_data = result.get_account(&intent).unwrap().data();
+        assert_eq!(intent_data[96], 1);
+        assert_eq!(
+            u64::from_le_bytes(intent_data[97..105].try_into().unwrap()),
+            AMOUNT
+        );
+    }
+
+    #[test]
+    fn reveal_swap_fails_with_other_amount() {
+        let mut pool = setup_pool(COMMIT_END, (0, 0));
+        add_round(&mut pool, (0, 0), None, 1, (0, 0));
+        add_intent(&mut pool, false);
+
+        let instruction = reveal_swap_instruction(&pool, AMOUNT + 1);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        shared::expect_error(&result, AmmError::InvalidIntent);
+    }
+
+    #[test]
+    fn reveal_swap_fails_after_reveal_window() {
+        let mut pool = setup_pool(REVEAL_END, (0, 0));
+        add_round(&mut pool, (0, 0), None, 1, (0, 0));
+        add_intent(&mut pool, false);
+
+        let instruction = reveal_swap_instruction(&pool, AMOUNT);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        shared::expect_error(&result, AmmError::InvalidBatch);
+    }
+
+    #[test]
+    fn settle_batch() {
+        let mut pool = setup_pool(REVEAL_END, (TOTAL_X, TOTAL_Y));
+        let (round, _) = batch_addresses(&pool, INDEX);
+        let config = pool.config;
+        shared::set_max_price_impact(pool.account_mut(&config), 105);
+        add_round(&mut pool, (TOTAL_X, TOTAL_Y), None, 2, (TOTAL_X, TOTAL_Y));
+
+        let instruction = settle_batch_instruction(&pool);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        // 5_530_974 of the X is swapped for 4_950_495 Y, the rest of it going
+        // to the sellers of Y at the same price
+        let outs = (TOTAL_X - 5_530_974, TOTAL_Y + 4_950_495);
+        assert_eq!(
+            shared::get_reserves(&result, pool.config),
+            (RESERVE + 5_530_974, RESERVE - 4_950_495)
+        );
+        assert_eq!(
+            get_round(&result, round),
+            (TOTAL_X, TOTAL_Y, outs.0, outs.1, true)
+        );
+        assert_eq!(get_round_left(&result, round), (2, outs.0, outs.1));
+        assert_eq!(get_batch_held(&result, pool.config), outs);
+    }
+
+    #[test]
+    fn settle_batch_fails_when_price_impact_exceeded() {
+        // The net swap moves the price of Y from 1 to 1.0105 X, 105 bps
+        let mut pool = setup_pool(REVEAL_END, (TOTAL_X, TOTAL_Y));
+        let config = pool.config;
+        shared::set_max_price_impact(pool.account_mut(&config), 104);
+        add_round(&mut pool, (TOTAL_X, TOTAL_Y), None, 2, (TOTAL_X, TOTAL_Y));
+
+        let instruction = settle_batch_instruction(&pool);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        shared::expect_error(&result, AmmError::PriceImpactExceeded);
+    }
+
+    #[test]
+    fn settle_batch_fails_in_reveal_window() {
+        let mut pool = setup_pool(REVEAL_END - 1, (TOTAL_X, TOTAL_Y));
+        add_round(&mut pool, (TOTAL_X, TOTAL_Y), None, 2, (TOTAL_X, TOTAL_Y));
+
+        let instruction = settle_batch_instruction(&pool);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        shared::expect_error(&result, AmmError::InvalidBatch);
+    }
+
+    #[test]
+    fn settle_batch_fails_after_timeout() {
+        let mut pool = setup_pool(REVEAL_END + BATCH_TIMEOUT_SLOTS, (TOTAL_X, TOTAL_Y));
+        add_round(&mut pool, (TOTAL_X, TOTAL_Y), None, 2, (TOTAL_X, TOTAL_Y));
+
+        let instruction = settle_batch_instruction(&pool);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        shared::expect_error(&result, AmmError::InvalidBatch);
+    }
+
+    #[test]
+    fn claim_batch() {
+        let outs = (TOTAL_X - 5_530_974, TOTAL_Y + 4_950_495);
+        let mut pool = setup_pool(REVEAL_END, outs);
+        let (round, intent) = batch_addresses(&pool, INDEX);
+        add_round(&mut pool, (TOTAL_X, TOTAL_Y), Some(outs), 2, outs);
+        add_intent(&mut pool, true);
+
+        let instruction = claim_batch_instruction(&pool, false);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        // 6_000_000 of the 10_000_000 X sold
+        shared::expect_token_balance(&result, pool.user_y, 5_370_297);
+        shared::expect_token_balance(&result, pool.vault_y, RESERVE + outs.1 - 5_370_297);
+        let intent_account = result.get_account(&intent).unwrap();
+        assert_eq!(intent_account.lamports(), 0);
+        assert_eq!(intent_account.data().len(), 0);
+
+        // The round stays open for the other seller
+        assert_eq!(
+            get_round_left(&result, round),
+            (1, outs.0, outs.1 - 5_370_297)
+        );
+        assert_eq!(
+            get_batch_held(&result, pool.config),
+            (outs.0, outs.1 - 5_370_297)
+        );
+    }
+
+    #[test]
+    fn claim_batch_closes_round() {
+        // The other intents are claimed, leaving the share of the user and a
+        // unit of rounding dust
+        let outs = (TOTAL_X - 5_530_974, TOTAL_Y + 4_950_495);
+        let left = (0, 5_370_298);
+        let mut pool = setup_pool(REVEAL_END, left);
+        let (round, _) = batch_addresses(&pool, INDEX);
+        add_round(&mut pool, (TOTAL_X, TOTAL_Y), Some(outs), 1, left);
+        add_intent(&mut pool, true);
+        let round_lamports = pool.account(&round).lamports();
+
+        let instruction = claim_batch_instruction(&pool, false);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, pool.user_y, 5_370_297);
+        let round_account = result.get_account(&round).unwrap();
+        assert_eq!(round_account.lamports(), 0);
+        assert_eq!(round_account.data().len(), 0);
+        assert_eq!(
+            result
+                .get_account(&pool.update_authority)
+                .unwrap()
+                .lamports(),
+            1_000_000_000 + round_lamports
+        );
+        // The dust goes to the reserves
+        assert_eq!(
+            shared::get_reserves(&result, pool.config),
+            (RESERVE, RESERVE + 1)
+        );
+        assert_eq!(get_batch_held(&result, pool.config), (0, 0));
+    }
+
+    #[test]
+    fn claim_batch_fails_before_settlement() {
+        let mut pool = setup_pool(REVEAL_END, (TOTAL_X, TOTAL_Y));
+        add_round(&mut pool, (TOTAL_X, TOTAL_Y), None, 2, (TOTAL_X, TOTAL_Y));
+        add_intent(&mut pool, true);
+
+        let instruction = claim_batch_instruction(&pool, false);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        shared::expect_error(&result, AmmError::InvalidBatch);
+    }
+
+    #[test]
+    fn claim_batch_refunds_timed_out_round() {
+        let mut pool = setup_pool(REVEAL_END + BATCH_TIMEOUT_SLOTS, (TOTAL_X, TOTAL_Y));
+        let (round, _) = batch_addresses(&pool, INDEX);
+        add_round(&mut pool, (TOTAL_X, TOTAL_Y), None, 2, (TOTAL_X, TOTAL_Y));
+        add_intent(&mut pool, true);
+
+        let instruction = claim_batch_instruction(&pool, true);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        // The X sold comes back untouched
+        shared::expect_token_balance(&result, pool.user_x, RESERVE + AMOUNT);
+        shared::expect_token_balance(&result, pool.vault_x, RESERVE + TOTAL_X - AMOUNT);
+        assert_eq!(
+            shared::get_reserves(&result, pool.config),
+            (RESERVE, RESERVE)
+        );
+        assert_eq!(
+            get_round_left(&result, round),
+            (1, TOTAL_X - AMOUNT, TOTAL_Y)
+        );
+        assert_eq!(
+            get_batch_held(&result, pool.config),
+            (TOTAL_X - AMOUNT, TOTAL_Y)
+        );
+    }
+
+    #[test]
+    fn claim_batch_closes_unrevealed_intent() {
+        let mut pool = setup_pool(REVEAL_END, (0, 0));
+        let (_, intent) = batch_addresses(&pool, INDEX);
+        add_round(&mut pool, (0, 0), None, 2, (0, 0));
+        add_intent(&mut pool, false);
+
+        let instruction = claim_batch_instruction(&pool, false);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, pool.user_y, 0);
+        let intent_account = result.get_account(&intent).unwrap();
+        assert_eq!(intent_account.lamports(), 0);
+        assert_eq!(intent_account.data().len(), 0);
+    }
+
+    #[test]
+    fn swap_fails_in_batch_mode() {
+        let pool = setup_pool(100, (0, 0));
+        let data = [
+            vec![3],
+            1_000_000u64.to_le_bytes().to_vec(), // amount
+            0u64.to_le_bytes().to_vec(),         // min amount
+            i64::MAX.to_le_bytes().to_vec(),     // expiration
+        ]
+        .concat();
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &data,
+            vec![
+                AccountMeta::new(pool.user, true),
+                AccountMeta::new_readonly(pool.authority, false),
+                AccountMeta::new(pool.user_x, false),
+                AccountMeta::new(pool.user_y, false),
+                AccountMeta::new(pool.vault_x, false),
+                AccountMeta::new(pool.vault_y, false),
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new_readonly(pool.mint_x, false),
+                AccountMeta::new_readonly(pool.mint_y, false),
+                AccountMeta::new_readonly(pool.token_program, false),
+                AccountMeta::new_readonly(pool.token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        shared::expect_error(&result, AmmError::BatchOnly);
+    }
+}
@@ -0,0 +1,421 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod close_pool_tests {
+    use crate::shared::{self, Pool};
+    use amm::{AmmError, Farm, Registry, Twamm, FARM_SEED, MINIMUM_LIQUIDITY, TWAMM_SEED};
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    const RESERVE: u64 = 1_000_000;
+
+    #[derive(Default)]
+    struct CloseArgs {
+        signer: Option<Pubkey>,
+        supply: u64,
+        vault_balance: u64,
+        foreign_registry: bool,
+        // Rewards left in the vault of an initialized farm
+        farm_rewards: Option<u64>,
+        // LP tokens left in the stake vault of the farm
+        farm_staked: u64,
+        // Orders left open in the TWAMM of the pool
+        twamm_orders: Option<u64>,
+        // X held by batch rounds of a pool back out of batch mode
+        batch_x: u64,
+    }
+
+    struct CloseAccounts {
+        registry: Pubkey,
+        farm: Pubkey,
+        update_authority_x: Pubkey,
+        update_authority_y: Pubkey,
+        remaining: Vec<Pubkey>,
+    }
+
+    /// Adds the registry of the pool, its farm PDA left uninitialized and the
+    /// token accounts of the update authority
+    fn add_close_accounts(pool: &mut Pool, foreign_registry: bool) -> CloseAccounts {
+        let registry = Pubkey::new_unique();
+        let (farm, _) =
+            Pubkey::find_program_address(&[FARM_SEED, pool.config.as_ref()], &pool.program_id);
+        let update_authority_x = Pubkey::new_unique();
+        let update_authority_y = Pubkey::new_unique();
+
+        let mut registry_account = AccountSharedData::new(
+            pool.mollusk.sysvars.rent.minimum_balance(Registry::LEN),
+            Registry::LEN,
+            &pool.program_id,
+        );
+        let registered = if foreign_registry {
+            Pubkey::new_unique()
+        } else {
+            pool.config
+        };
+        registry_account.set_data_from_slice(registered.as_ref());
+
+        pool.accounts.extend([
+            (registry, registry_account),
+            (farm, AccountSharedData::default()),
+            (
+                update_authority_x,
+                shared::create_token_account(
+                    &pool.mollusk,
+                    pool.mint_x,
+                    pool.update_authority,
+                    0,
+                    pool.token_program,
+                ),
+            ),
+            (
+                update_authority_y,
+                shared::create_token_account(
+                    &pool.mollusk,
+                    pool.mint_y,
+                    pool.update_authority,
+                    0,
+                    pool.token_program,
+                ),
+            ),
+        ]);
+
+        CloseAccounts {
+            registry,
+            farm,
+            update_authority_x,
+            update_authority_y,
+            remaining: vec![],
+        }
+    }
+
+    fn close_pool_instruction(pool: &Pool, signer: Pubkey, close: &CloseAccounts) -> Instruction {
+        let mut metas = vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(pool.authority, false),
+            AccountMeta::new(pool.config, false),
+            AccountMeta::new_readonly(pool.mint_lp, false),
+            AccountMeta::new_readonly(pool.mint_x, false),
+            AccountMeta::new_readonly(pool.mint_y, false),
+            AccountMeta::new(pool.vault_x, false),
+            AccountMeta::new(pool.vault_y, false),
+            AccountMeta::new(close.update_authority_x, false),
+            AccountMeta::new(close.update_authority_y, false),
+            AccountMeta::new(close.registry, false),
+            AccountMeta::new(close.farm, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+        ];
+        metas.extend(
+            close
+                .remaining
+                .iter()
+                .map(|key| AccountMeta::new(*key, false)),
+        );
+        Instruction::new_with_bytes(pool.program_id, &[25], metas)
+    }
+
+    fn process_close_pool(args: CloseArgs) -> (InstructionResult, Pool, CloseAccounts, u64) {
+        // The first deposit locked MINIMUM_LIQUIDITY in the reserves
+        let mut pool = shared::setup_pool(Pubkey::new_unique(), MINIMUM_LIQUIDITY);
+        let signer = args.signer.unwrap_or(pool.update_authority);
+        let mut close = add_close_accounts(&mut pool, args.foreign_registry);
+
+        let mint_lp = shared::create_mint_account(
+            &pool.mollusk,
+            pool.authority,
+            args.supply,
+            6,
+            true,
+            pool.token_program,
+        );
+        pool.set_account(pool.mint_lp, mint_lp);
+        let vault_x = shared::create_token_account(
+            &pool.mollusk,
+            pool.mint_x,
+            pool.authority,
+            MINIMUM_LIQUIDITY + args.vault_balance,
+            pool.token_program,
+        );
+        pool.set_account(pool.vault_x, vault_x);
+        pool.set_account(
+            signer,
+            AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+        );
+        let config = pool.config;
+        shared::set_batch_held(pool.account_mut(&config), args.batch_x, 0);
+
+        if let Some(rewards) = args.farm_rewards {
+            let stake_vault = Pubkey::new_unique();
+            let reward_vault = Pubkey::new_unique();
+            let mut farm_account = AccountSharedData::new(
+                pool.mollusk.sysvars.rent.minimum_balance(Farm::LEN),
+                Farm::LEN,
+                &pool.program_id,
+            );
+            let mut data = [
+                pool.config.to_bytes().to_vec(),
+                pool.mint_y.to_bytes().to_vec(),
+                reward_vault.to_bytes().to_vec(),
+                stake_vault.to_bytes().to_vec(),
+            ]
+            .concat();
+            data.resize(Farm::LEN, 0);
+            farm_account.set_data_from_slice(&data);
+            let reward_vault_account = shared::create_token_account(
+                &pool.mollusk,
+                pool.mint_y,
+                pool.authority,
+                rewards,
+                pool.token_program,
+            );
+            let stake_vault_account = shared::create_token_account(
+                &pool.mollusk,
+                pool.mint_lp,
+                pool.authority,
+                args.farm_staked,
+                pool.token_program,
+            );
+            pool.set_account(close.farm, farm_account);
+            pool.set_account(stake_vault, stake_vault_account);
+            pool.set_account(reward_vault, reward_vault_account);
+            close.remaining.extend([stake_vault, reward_vault]);
+        }
+
+        if let Some(orders) = args.twamm_orders {
+            let (twamm, _) =
+                Pubkey::find_program_address(&[TWAMM_SEED, pool.config.as_ref()], &pool.program_id);
+            shared::set_twamm(pool.account_mut(&config), twamm);
+            let mut twamm_account = AccountSharedData::new(
+                pool.mollusk.sysvars.rent.minimum_balance(Twamm::LEN),
+                Twamm::LEN,
+                &pool.program_id,
+            );
+            let mut data = pool.config.to_bytes().to_vec();
+            data.resize(Twamm::LEN, 0);
+            let offset = Twamm::HEADER_LEN + 32;
+            data[offset..offset + 8].copy_from_slice(&orders.to_le_bytes());
+            twamm_account.set_data_from_slice(&data);
+            pool.set_account(twamm, twamm_account);
+            close.remaining.push(twamm);
+        }
+
+        let rent = [
+            pool.config,
+            close.registry,
+            close.farm,
+            pool.vault_x,
+            pool.vault_y,
+        ]
+        .into_iter()
+        .chain(close.remaining.iter().copied())
+        .map(|key| pool.account(&key).lamports())
+        .sum();
+
+        let instruction = close_pool_instruction(&pool, signer, &close);
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        (result, pool, close, rent)
+    }
+
+    #[test]
+    fn close_pool() {
+        let (result, pool, close, rent) = process_close_pool(CloseArgs {
+            vault_balance: 500,
+            twamm_orders: Some(0),
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        for account in [pool.config, pool.vault_x, pool.vault_y, close.registry]
+            .into_iter()
+            .chain(close.remaining)
+        {
+            let account = result.get_account(&account).unwrap();
+            assert_eq!(account.lamports(), 0);
+            assert!(account.data().is_empty());
+        }
+        assert_eq!(
+            result
+                .get_account(&pool.update_authority)
+                .unwrap()
+                .lamports(),
+            1_000_000_000 + rent
+        );
+        // The locked liquidity and the donation are swept
+        shared::expect_token_balance(&result, close.update_authority_x, MINIMUM_LIQUIDITY + 500);
+        shared::expect_token_balance(&result, close.update_authority_y, MINIMUM_LIQUIDITY);
+    }
+
+    #[test]
+    fn close_pool_after_withdrawing_everything() {
+        let mut pool = shared::setup_pool(Pubkey::new_unique(), 0);
+        let close = add_close_accounts(&mut pool, false);
+        for (user_token, mint) in [(pool.user_x, pool.mint_x), (pool.user_y, pool.mint_y)] {
+            let account = shared::create_token_account(
+                &pool.mollusk,
+                mint,
+                pool.user,
+                RESERVE,
+                pool.token_program,
+            );
+            pool.set_account(user_token, account);
+        }
+
+        let liquidity_metas = vec![
+            AccountMeta::new(pool.user, true),
+            AccountMeta::new(pool.authority, false),
+            AccountMeta::new(pool.mint_lp, false),
+            AccountMeta::new(pool.user_x, false),
+            AccountMeta::new(pool.user_y, false),
+            AccountMeta::new(pool.user_lp, false),
+            AccountMeta::new(pool.vault_x, false),
+            AccountMeta::new(pool.vault_y, false),
+            AccountMeta::new(pool.config, false),
+            AccountMeta::new(pool.token_program, false),
+            AccountMeta::new_readonly(pool.mint_x, false),
+            AccountMeta::new_readonly(pool.mint_y, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+        ];
+        let deposit = [
+            vec![1],
+            1u64.to_le_bytes().to_vec(),     // amount
+            RESERVE.to_le_bytes().to_vec(),  // max_x
+            RESERVE.to_le_bytes().to_vec(),  // max_y
+            i64::MAX.to_le_bytes().to_vec(), // expiration
+        ]
+        .concat();
+        let withdraw = [
+            vec![2],
+            (RESERVE - MINIMUM_LIQUIDITY).to_le_bytes().to_vec(), // amount
+            0u64.to_le_bytes().to_vec(),                          // min_x
+            0u64.to_le_bytes().to_vec(),                          // min_y
+            i64::MAX.to_le_bytes().to_vec(),                      // expiration
+        ]
+        .concat();
+
+        let instructions = [
+            Instruction::new_with_bytes(pool.program_id, &deposit, liquidity_metas.clone()),
+            Instruction::new_with_bytes(pool.program_id, &withdraw, liquidity_metas),
+            close_pool_instruction(&pool, pool.update_authority, &close),
+        ];
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction_chain(&instructions, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, pool.user_x, RESERVE - MINIMUM_LIQUIDITY);
+        shared::expect_token_balance(&result, pool.user_y, RESERVE - MINIMUM_LIQUIDITY);
+        shared::expect_token_balance(&result, close.update_authority_x, MINIMUM_LIQUIDITY);
+        shared::expect_token_balance(&result, close.update_authority_y, MINIMUM_LIQUIDITY);
+        assert_eq!(result.get_account(&pool.config).unwrap().lamports(), 0);
+    }
+
+    #[test]
+    fn close_pool_fails_with_lp_supply() {
+        let (result, ..) = process_close_pool(CloseArgs {
+            supply: 1,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::PoolNotEmpty);
+    }
+
+    #[test]
+    fn close_pool_fails_with_farm_rewards() {
+        let (result, ..) = process_close_pool(CloseArgs {
+            farm_rewards: Some(1),
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::PoolNotEmpty);
+    }
+
+    #[test]
+    fn close_pool_with_empty_farm() {
+        let (result, pool, close, rent) = process_close_pool(CloseArgs {
+            farm_rewards: Some(0),
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        // The farm and both of its vaults are closed along with the pool
+        for account in [close.farm].into_iter().chain(close.remaining) {
+            let account = result.get_account(&account).unwrap();
+            assert_eq!(account.lamports(), 0);
+            assert!(account.data().is_empty());
+        }
+        assert_eq!(
+            result
+                .get_account(&pool.update_authority)
+                .unwrap()
+                .lamports(),
+            1_000_000_000 + rent
+        );
+    }
+
+    #[test]
+    fn close_pool_fails_with_staked_lp() {
+        let (result, ..) = process_close_pool(CloseArgs {
+            farm_rewards: Some(0),
+            farm_staked: 1,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::PoolNotEmpty);
+    }
+
+    #[test]
+    fn close_pool_fails_with_open_orders() {
+        let (result, ..) = process_close_pool(CloseArgs {
+            twamm_orders: Some(1),
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::PoolNotEmpty);
+    }
+
+    #[test]
+    fn close_pool_fails_with_batch_funds() {
+        // The authority switched batch mode off with a round left unclaimed
+        let (result, ..) = process_close_pool(CloseArgs {
+            vault_balance: 500,
+            batch_x: 500,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::PoolNotEmpty);
+    }
+
+    #[test]
+    fn close_pool_fails_with_invalid_authority() {
+        let (result, ..) = process_close_pool(CloseArgs {
+            signer: Some(Pubkey::new_unique()),
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::InvalidAuthority);
+    }
+
+    #[test]
+    fn close_pool_fails_with_foreign_registry() {
+        let (result, ..) = process_close_pool(CloseArgs {
+            foreign_registry: true,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::InvalidRegistry);
+    }
+}
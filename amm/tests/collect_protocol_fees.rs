@@ -0,0 +1,140 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod collect_protocol_fees_tests {
+    use crate::shared::{self};
+    use amm::AmmError;
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::AccountSharedData,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    const PROTOCOL_FEES_X: u64 = 1_000;
+    const PROTOCOL_FEES_Y: u64 = 2_000;
+
+    fn process_collect(signer: Option<Pubkey>) -> (InstructionResult, [Pubkey; 5]) {
+        let (mollusk, program_id) = shared::setup();
+        let (token_program, token_program_account) =
+            mollusk_svm_programs_token::token::keyed_account();
+
+        let update_authority = Pubkey::new_unique();
+        let signer = signer.unwrap_or(update_authority);
+        let config = Pubkey::new_unique();
+        let (authority, bump) = Pubkey::find_program_address(&[config.as_ref()], &program_id);
+        let mint_x = Pubkey::new_unique();
+        let mint_y = Pubkey::new_unique();
+        let vault_x = Pubkey::new_unique();
+        let vault_y = Pubkey::new_unique();
+        let treasury_x = Pubkey::new_unique();
+        let treasury_y = Pubkey::new_unique();
+
+        let mint_x_account =
+            shared::create_mint_account(&mollusk, update_authority, 0, 6, true, token_program);
+
+        let mint_y_account =
+            shared::create_mint_account(&mollusk, update_authority, 0, 6, true, token_program);
+
+        let vault_x_account = shared::create_token_account(
+            &mollusk,
+            mint_x,
+            authority,
+            1_000_000_000 + PROTOCOL_FEES_X,
+            token_program,
+        );
+
+        let vault_y_account = shared::create_token_account(
+            &mollusk,
+            mint_y,
+            authority,
+            1_000_000_000 + PROTOCOL_FEES_Y,
+            token_program,
+        );
+
+        let treasury_x_account =
+            shared::create_token_account(&mollusk, mint_x, update_authority, 0, token_program);
+
+        let treasury_y_account =
+            shared::create_token_account(&mollusk, mint_y, update_authority, 0, token_program);
+
+        let mut config_account = shared::create_config(
+            &mollusk,
+            0,
+            update_authority,
+            mint_x,
+            mint_y,
+            Pubkey::new_unique(),
+            vault_x,
+            vault_y,
+            1_000u16,
+            bump,
+            program_id,
+        );
+        shared::set_protocol_fees(&mut config_account, 5_000, PROTOCOL_FEES_X, PROTOCOL_FEES_Y);
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[6],
+            vec![
+                AccountMeta::new(signer, true),
+                AccountMeta::new(authority, false),
+                AccountMeta::new(vault_x, false),
+                AccountMeta::new(vault_y, false),
+                AccountMeta::new(treasury_x, false),
+                AccountMeta::new(treasury_y, false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(mint_x, false),
+                AccountMeta::new_readonly(mint_y, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = mollusk.process_instruction(
+            &instruction,
+            &[
+                (
+                    signer,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (
+                    authority,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (vault_x, vault_x_account),
+                (vault_y, vault_y_account),
+                (treasury_x, treasury_x_account),
+                (treasury_y, treasury_y_account),
+                (config, config_account),
+                (mint_x, mint_x_account),
+                (mint_y, mint_y_account),
+                (token_program, token_program_account),
+            ],
+        );
+
+        (result, [vault_x, vault_y, treasury_x, treasury_y, config])
+    }
+
+    #[test]
+    fn collect_protocol_fees() {
+        let (result, [vault_x, vault_y, treasury_x, treasury_y, config]) = process_collect(None);
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, vault_x, 1_000_000_000);
+        shared::expect_token_balance(&result, vault_y, 1_000_000_000);
+        shared::expect_token_balance(&result, treasury_x, PROTOCOL_FEES_X);
+        shared::expect_token_balance(&result, treasury_y, PROTOCOL_FEES_Y);
+        assert_eq!(shared::get_protocol_fees(&result, config), (0, 0));
+    }
+
+    #[test]
+    fn collect_protocol_fees_fails_with_invalid_authority() {
+        let (result, _) = process_collect(Some(Pubkey::new_unique()));
+
+        shared::expect_error(&result, AmmError::InvalidAuthority);
+    }
+}
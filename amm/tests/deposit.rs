@@ -4,6 +4,8 @@ mod shared;
 #[cfg(test)]
 mod deposit_tests {
     use crate::shared::{self};
+    use amm::{AmmError, EMERGENCY_MODE, MINIMUM_LIQUIDITY, PAUSE_DEPOSITS, PAUSE_SWAPS};
+    use mollusk_svm::result::InstructionResult;
 
     use solana_sdk::{
         account::AccountSharedData,
@@ -11,8 +13,43 @@ mod deposit_tests {
         pubkey::Pubkey,
     };
 
-    #[test]
-    fn deposit() {
+    const TRANSFER_AMOUNT: u64 = 1_000_000_000;
+
+    struct DepositArgs {
+        status: u8,
+        amount: u64,
+        max_x: u64,
+        max_y: u64,
+        expiration: i64,
+        // LP supply, locked liquidity included, and reserves of both vaults before the deposit
+        pool: u64,
+        // Tokens sent straight to both vaults on top of the reserves
+        donation: u64,
+        wrong_mint: bool,
+        wrong_vault: bool,
+        // Allowlist of the pool holding the user, and whether the user
+        // sends its proof
+        allowlist: Option<bool>,
+    }
+
+    impl Default for DepositArgs {
+        fn default() -> Self {
+            Self {
+                status: 0,
+                amount: TRANSFER_AMOUNT - MINIMUM_LIQUIDITY,
+                max_x: TRANSFER_AMOUNT,
+                max_y: TRANSFER_AMOUNT,
+                expiration: i64::MAX,
+                pool: 0,
+                donation: 0,
+                wrong_mint: false,
+                wrong_vault: false,
+                allowlist: None,
+            }
+        }
+    }
+
+    fn process_deposit(args: DepositArgs) -> (InstructionResult, [Pubkey; 5]) {
         let (mollusk, program_id) = shared::setup();
         let (token_program, token_program_account) =
             mollusk_svm_programs_token::token::keyed_account();
@@ -29,39 +66,82 @@ mod deposit_tests {
         let vault_x = Pubkey::new_unique();
         let vault_y = Pubkey::new_unique();
 
-        let transfer_amount = 1_000_000_000u64;
-
-        let data = [
+        let mut data = [
             vec![1],
-            transfer_amount.to_le_bytes().to_vec(),  // amount
-            1_000_000_000u64.to_le_bytes().to_vec(), // max_x
-            1_000_000_000u64.to_le_bytes().to_vec(), // max_y
-            i64::MIN.to_le_bytes().to_vec(),         // expiration
+            args.amount.to_le_bytes().to_vec(),     // amount
+            args.max_x.to_le_bytes().to_vec(),      // max_x
+            args.max_y.to_le_bytes().to_vec(),      // max_y
+            args.expiration.to_le_bytes().to_vec(), // expiration
         ]
         .concat();
+        let (root, proof) = shared::allowlist(user);
+        if args.allowlist == Some(true) {
+            data.extend(proof); // proof
+        }
+
+        let mint_lp_account = shared::create_mint_account(
+            &mollusk,
+            authority,
+            args.pool.saturating_sub(MINIMUM_LIQUIDITY),
+            6,
+            true,
+            token_program,
+        );
 
-        let mint_lp_account =
-            shared::create_mint_account(&mollusk, authority, 0, 6, true, token_program);
+        let mint_x_account = shared::create_mint_account(&mollusk, user, 0, 6, true, token_program);
+
+        let mint_y_account = shared::create_mint_account(&mollusk, user, 0, 6, true, token_program);
 
         let user_x_account =
-            shared::create_token_account(&mollusk, mint_x, user, 1_000_000_000, token_program);
+            shared::create_token_account(&mollusk, mint_x, user, TRANSFER_AMOUNT, token_program);
 
         let user_y_account =
-            shared::create_token_account(&mollusk, mint_y, user, 1_000_000_000, token_program);
+            shared::create_token_account(&mollusk, mint_y, user, TRANSFER_AMOUNT, token_program);
 
-        let vault_x_account =
-            shared::create_token_account(&mollusk, mint_x, authority, 0, token_program);
+        let vault_x_account = shared::create_token_account(
+            &mollusk,
+            mint_x,
+            authority,
+            args.pool + args.donation,
+            token_program,
+        );
 
-        let vault_y_account =
-            shared::create_token_account(&mollusk, mint_y, authority, 0, token_program);
+        let vault_y_account = shared::create_token_account(
+            &mollusk,
+            mint_y,
+            authority,
+            args.pool + args.donation,
+            token_program,
+        );
 
         let user_lp_account =
             shared::create_token_account(&mollusk, mint_lp, user, 0, token_program);
 
-        let config_account = shared::create_config(
-            &mollusk, 0, authority, mint_x, mint_y, mint_lp, vault_x, vault_y, 1_000u16, bump,
+        let mut config_account = shared::create_config(
+            &mollusk,
+            args.status,
+            authority,
+            mint_x,
+            mint_y,
+            if args.wrong_mint {
+                Pubkey::new_unique()
+            } else {
+                mint_lp
+            },
+            vault_x,
+            if args.wrong_vault {
+                Pubkey::new_unique()
+            } else {
+                vault_y
+            },
+            1_000u16,
+            bump,
             program_id,
         );
+        shared::set_reserves(&mut config_account, args.pool, args.pool);
+        if args.allowlist.is_some() {
+            shared::set_allowlist_root(&mut config_account, root);
+        }
 
         let instruction = Instruction::new_with_bytes(
             program_id,
@@ -77,12 +157,16 @@ mod deposit_tests {
                 AccountMeta::new(vault_y, false),
                 AccountMeta::new(config, false),
                 AccountMeta::new(token_program, false),
+                AccountMeta::new_readonly(mint_x, false),
+                AccountMeta::new_readonly(mint_y, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(token_program, false),
             ],
         );
 
-        let result: mollusk_svm::result::InstructionResult = mollusk.process_instruction(
+        let result: InstructionResult = mollusk.process_instruction(
             &instruction,
-            &vec![
+            &[
                 (
                     user,
                     AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
@@ -99,14 +183,168 @@ mod deposit_tests {
                 (vault_y, vault_y_account),
                 (config, config_account),
                 (token_program, token_program_account),
+                (mint_x, mint_x_account),
+                (mint_y, mint_y_account),
             ],
         );
 
+        (result, [user_x, user_lp, vault_x, vault_y, config])
+    }
+
+    #[test]
+    fn deposit() {
+        let (result, [user_x, user_lp, vault_x, vault_y, config]) =
+            process_deposit(DepositArgs::default());
+
         assert!(!result.program_result.is_err());
 
+        // √(x·y) minus the locked liquidity
         shared::expect_token_balance(&result, user_x, 0u64);
-        shared::expect_token_balance(&result, vault_x, transfer_amount);
-        shared::expect_token_balance(&result, vault_y, transfer_amount);
-        shared::expect_token_balance(&result, user_lp, transfer_amount);
+        shared::expect_token_balance(&result, vault_x, TRANSFER_AMOUNT);
+        shared::expect_token_balance(&result, vault_y, TRANSFER_AMOUNT);
+        shared::expect_token_balance(&result, user_lp, TRANSFER_AMOUNT - MINIMUM_LIQUIDITY);
+        assert_eq!(
+            shared::get_reserves(&result, config),
+            (TRANSFER_AMOUNT, TRANSFER_AMOUNT)
+        );
+    }
+
+    #[test]
+    fn deposit_ignores_donations() {
+        // Donating a full vault does not make the LP tokens more expensive
+        let (result, [user_x, user_lp, vault_x, _, config]) = process_deposit(DepositArgs {
+            amount: TRANSFER_AMOUNT,
+            pool: TRANSFER_AMOUNT,
+            donation: TRANSFER_AMOUNT,
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_x, 0u64);
+        shared::expect_token_balance(&result, vault_x, 3 * TRANSFER_AMOUNT);
+        shared::expect_token_balance(&result, user_lp, TRANSFER_AMOUNT);
+        assert_eq!(
+            shared::get_reserves(&result, config),
+            (2 * TRANSFER_AMOUNT, 2 * TRANSFER_AMOUNT)
+        );
+    }
+
+    #[test]
+    fn deposit_fails_with_insufficient_liquidity() {
+        // √(1_000 · 1_000) leaves nothing once the minimum liquidity is locked
+        let (result, _) = process_deposit(DepositArgs {
+            amount: 0,
+            max_x: MINIMUM_LIQUIDITY,
+            max_y: MINIMUM_LIQUIDITY,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::InsufficientLiquidity);
+    }
+
+    #[test]
+    fn deposit_fails_when_first_deposit_mints_less_than_amount() {
+        let (result, _) = process_deposit(DepositArgs {
+            amount: TRANSFER_AMOUNT,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::SlippageExceeded);
+    }
+
+    #[test]
+    fn deposit_fails_when_locked() {
+        let (result, _) = process_deposit(DepositArgs {
+            status: PAUSE_DEPOSITS,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::Locked);
+    }
+
+    #[test]
+    fn deposit_ignores_other_pauses() {
+        let (result, _) = process_deposit(DepositArgs {
+            status: PAUSE_SWAPS,
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+    }
+
+    #[test]
+    fn deposit_fails_in_emergency_mode() {
+        let (result, _) = process_deposit(DepositArgs {
+            status: EMERGENCY_MODE,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::Locked);
+    }
+
+    #[test]
+    fn deposit_fails_when_expired() {
+        let (result, _) = process_deposit(DepositArgs {
+            expiration: i64::MIN,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::Expired);
+    }
+
+    #[test]
+    fn deposit_fails_with_invalid_mint() {
+        let (result, _) = process_deposit(DepositArgs {
+            wrong_mint: true,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::InvalidMint);
+    }
+
+    #[test]
+    fn deposit_fails_with_invalid_vault() {
+        let (result, _) = process_deposit(DepositArgs {
+            wrong_vault: true,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::InvalidVault);
+    }
+
+    #[test]
+    fn deposit_fails_when_slippage_exceeded() {
+        // Minting as much LP as the current supply requires depositing a full vault of X
+        let (result, _) = process_deposit(DepositArgs {
+            amount: TRANSFER_AMOUNT,
+            pool: TRANSFER_AMOUNT,
+            max_x: TRANSFER_AMOUNT - 1,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::SlippageExceeded);
+    }
+
+    #[test]
+    fn deposit_allowlisted() {
+        let (result, [_, user_lp, ..]) = process_deposit(DepositArgs {
+            allowlist: Some(true),
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_lp, TRANSFER_AMOUNT - MINIMUM_LIQUIDITY);
+    }
+
+    #[test]
+    fn deposit_fails_without_allowlist_proof() {
+        let (result, _) = process_deposit(DepositArgs {
+            allowlist: Some(false),
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::NotAllowlisted);
     }
 }
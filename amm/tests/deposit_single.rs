@@ -0,0 +1,215 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod deposit_single_tests {
+    use crate::shared::{self};
+    use amm::{AmmError, CURVE_CONSTANT_PRODUCT, CURVE_STABLE, MINIMUM_LIQUIDITY, PAUSE_SWAPS};
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::AccountSharedData,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    const POOL: u64 = 1_000_000_000;
+    const AMOUNT: u64 = 10_000_000;
+    // 5_251_407 of X swapped for 4_701_576 of Y, then deposited
+    const LP: u64 = 4_723_785;
+
+    fn process_deposit_single(
+        status: u8,
+        min_lp: u64,
+        pool: u64,
+        oracle: Option<(i64, i32, u64, u64)>,
+        curve: (u8, u64),
+    ) -> (InstructionResult, [Pubkey; 5]) {
+        let (mut mollusk, program_id) = shared::setup();
+        let (token_program, token_program_account) =
+            mollusk_svm_programs_token::token::keyed_account();
+
+        let user = Pubkey::new_unique();
+        let config = Pubkey::new_unique();
+        let (authority, bump) = Pubkey::find_program_address(&[config.as_ref()], &program_id);
+        let mint_lp = Pubkey::new_unique();
+        let mint_x = Pubkey::new_unique();
+        let mint_y = Pubkey::new_unique();
+        let user_x = Pubkey::new_unique();
+        let user_lp = Pubkey::new_unique();
+        let vault_x = Pubkey::new_unique();
+        let vault_y = Pubkey::new_unique();
+
+        let data = [
+            vec![16],
+            AMOUNT.to_le_bytes().to_vec(),   // amount
+            min_lp.to_le_bytes().to_vec(),   // min lp
+            i64::MAX.to_le_bytes().to_vec(), // expiration
+        ]
+        .concat();
+
+        let mut config_account = shared::create_config(
+            &mollusk, status, authority, mint_x, mint_y, mint_lp, vault_x, vault_y, 1_000u16, bump,
+            program_id,
+        );
+        shared::set_reserves(&mut config_account, pool, pool);
+        shared::set_curve(&mut config_account, curve.0, curve.1);
+        let price_feed = Pubkey::new_unique();
+        if oracle.is_some() {
+            shared::set_oracle_guard(&mut config_account, price_feed, 50, 100);
+        }
+
+        let mut metas = vec![
+            AccountMeta::new(user, true),
+            AccountMeta::new(authority, false),
+            AccountMeta::new(mint_lp, false),
+            AccountMeta::new(user_x, false),
+            AccountMeta::new(user_lp, false),
+            AccountMeta::new(vault_x, false),
+            AccountMeta::new(vault_y, false),
+            AccountMeta::new(config, false),
+            AccountMeta::new(token_program, false),
+            AccountMeta::new_readonly(mint_x, false),
+            AccountMeta::new_readonly(token_program, false),
+        ];
+        let mut accounts = vec![
+            (
+                user,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                authority,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                mint_lp,
+                shared::create_mint_account(
+                    &mollusk,
+                    authority,
+                    pool.saturating_sub(MINIMUM_LIQUIDITY),
+                    6,
+                    true,
+                    token_program,
+                ),
+            ),
+            (
+                user_x,
+                shared::create_token_account(&mollusk, mint_x, user, AMOUNT, token_program),
+            ),
+            (
+                user_lp,
+                shared::create_token_account(&mollusk, mint_lp, user, 0, token_program),
+            ),
+            (
+                vault_x,
+                shared::create_token_account(&mollusk, mint_x, authority, pool, token_program),
+            ),
+            (
+                vault_y,
+                shared::create_token_account(&mollusk, mint_y, authority, pool, token_program),
+            ),
+            (config, config_account),
+            (token_program, token_program_account),
+            (
+                mint_x,
+                shared::create_mint_account(&mollusk, user, 0, 6, true, token_program),
+            ),
+        ];
+        if let Some((price, exponent, confidence, publish_slot)) = oracle {
+            mollusk.sysvars.clock.slot = 1_000;
+            metas.push(AccountMeta::new_readonly(price_feed, false));
+            accounts.push((
+                price_feed,
+                shared::create_price_feed(&mollusk, price, exponent, confidence, publish_slot),
+            ));
+        }
+
+        let instruction = Instruction::new_with_bytes(program_id, &data, metas);
+
+        let result: InstructionResult = mollusk.process_instruction(&instruction, &accounts);
+
+        (result, [user_x, user_lp, vault_x, vault_y, config])
+    }
+
+    #[test]
+    fn deposit_single() {
+        let (result, [user_x, user_lp, vault_x, vault_y, config]) =
+            process_deposit_single(0, LP, POOL, None, (CURVE_CONSTANT_PRODUCT, 0));
+
+        assert!(!result.program_result.is_err());
+
+        // Only X moves, the swapped Y is deposited back into its vault
+        shared::expect_token_balance(&result, user_x, 0);
+        shared::expect_token_balance(&result, user_lp, LP);
+        shared::expect_token_balance(&result, vault_x, POOL + AMOUNT);
+        shared::expect_token_balance(&result, vault_y, POOL);
+        assert_eq!(shared::get_reserves(&result, config), (POOL + AMOUNT, POOL));
+    }
+
+    #[test]
+    fn deposit_single_stable() {
+        // 5_238_411 of X swapped for 4_714_446 of Y on the StableSwap curve,
+        // where the constant product amount would leave Y unpaired
+        let (result, [user_x, user_lp, _, _, config]) =
+            process_deposit_single(0, 4_736_775, POOL, None, (CURVE_STABLE, 100));
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_x, 0);
+        shared::expect_token_balance(&result, user_lp, 4_736_775);
+        assert_eq!(shared::get_reserves(&result, config), (POOL + AMOUNT, POOL));
+    }
+
+    #[test]
+    fn deposit_single_fails_when_slippage_exceeded() {
+        let (result, _) =
+            process_deposit_single(0, LP + 1, POOL, None, (CURVE_CONSTANT_PRODUCT, 0));
+
+        shared::expect_error(&result, AmmError::SlippageExceeded);
+    }
+
+    #[test]
+    fn deposit_single_fails_on_empty_pool() {
+        let (result, _) = process_deposit_single(0, 0, 0, None, (CURVE_CONSTANT_PRODUCT, 0));
+
+        shared::expect_error(&result, AmmError::InsufficientLiquidity);
+    }
+
+    #[test]
+    fn deposit_single_fails_when_swaps_paused() {
+        let (result, _) =
+            process_deposit_single(PAUSE_SWAPS, LP, POOL, None, (CURVE_CONSTANT_PRODUCT, 0));
+
+        shared::expect_error(&result, AmmError::Locked);
+    }
+
+    #[test]
+    fn deposit_single_within_oracle_deviation() {
+        // The swap moves the price of X from 1 to 0.9901 Y, 1 bp from the feed
+        let (result, [_, user_lp, ..]) = process_deposit_single(
+            0,
+            LP,
+            POOL,
+            Some((990, -3, 0, 1_000)),
+            (CURVE_CONSTANT_PRODUCT, 0),
+        );
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_lp, LP);
+    }
+
+    #[test]
+    fn deposit_single_fails_when_oracle_deviation_exceeded() {
+        // Moves the price away from 1 Y, from 0 to 99 bps
+        let (result, _) = process_deposit_single(
+            0,
+            LP,
+            POOL,
+            Some((1, 0, 0, 1_000)),
+            (CURVE_CONSTANT_PRODUCT, 0),
+        );
+
+        shared::expect_error(&result, AmmError::OracleDeviation);
+    }
+}
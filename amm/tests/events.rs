@@ -0,0 +1,165 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod events_tests {
+    use crate::shared::{self};
+    use amm_events::{AmmEvent, SwapEvent};
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::AccountSharedData,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    // Mollusk hands the program logs to the `log` crate, so they are read
+    // back from a file logger. Being global, a single test uses it.
+    #[test]
+    fn swap_event() {
+        let (mollusk, program_id) = shared::setup();
+        let log_path = std::env::temp_dir().join(format!("amm-events-{}.log", std::process::id()));
+        solana_logger::setup_file_with_default(
+            log_path.to_str().unwrap(),
+            "solana_runtime::message_processor::stable_log=debug",
+        );
+
+        let (token_program, token_program_account) =
+            mollusk_svm_programs_token::token::keyed_account();
+
+        let user = Pubkey::new_unique();
+        let config = Pubkey::new_unique();
+        let (authority, bump) = Pubkey::find_program_address(&[config.as_ref()], &program_id);
+        let mint_x = Pubkey::new_unique();
+        let mint_y = Pubkey::new_unique();
+        let user_x = Pubkey::new_unique();
+        let user_y = Pubkey::new_unique();
+        let vault_x = Pubkey::new_unique();
+        let vault_y = Pubkey::new_unique();
+
+        let data = [
+            vec![3],
+            1_000_000u64.to_le_bytes().to_vec(), // amount
+            1_000u64.to_le_bytes().to_vec(),     // min amount
+            i64::MAX.to_le_bytes().to_vec(),     // expiration
+        ]
+        .concat();
+
+        let mut config_account = shared::create_config(
+            &mollusk,
+            0,
+            authority,
+            mint_x,
+            mint_y,
+            Pubkey::new_unique(),
+            vault_x,
+            vault_y,
+            1_000u16,
+            bump,
+            program_id,
+        );
+        shared::set_reserves(&mut config_account, 1_000_000_000, 1_000_000_000);
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(user, true),
+                AccountMeta::new(authority, false),
+                AccountMeta::new(user_x, false),
+                AccountMeta::new(user_y, false),
+                AccountMeta::new(vault_x, false),
+                AccountMeta::new(vault_y, false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(mint_x, false),
+                AccountMeta::new_readonly(mint_y, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = mollusk.process_instruction(
+            &instruction,
+            &[
+                (
+                    user,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (
+                    authority,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (
+                    user_x,
+                    shared::create_token_account(
+                        &mollusk,
+                        mint_x,
+                        user,
+                        1_000_000_000,
+                        token_program,
+                    ),
+                ),
+                (
+                    user_y,
+                    shared::create_token_account(&mollusk, mint_y, user, 0, token_program),
+                ),
+                (
+                    vault_x,
+                    shared::create_token_account(
+                        &mollusk,
+                        mint_x,
+                        authority,
+                        1_000_000_000,
+                        token_program,
+                    ),
+                ),
+                (
+                    vault_y,
+                    shared::create_token_account(
+                        &mollusk,
+                        mint_y,
+                        authority,
+                        1_000_000_000,
+                        token_program,
+                    ),
+                ),
+                (config, config_account),
+                (
+                    mint_x,
+                    shared::create_mint_account(&mollusk, user, 0, 6, true, token_program),
+                ),
+                (
+                    mint_y,
+                    shared::create_mint_account(&mollusk, user, 0, 6, true, token_program),
+                ),
+                (token_program, token_program_account),
+            ],
+        );
+
+        assert!(!result.program_result.is_err());
+
+        // Lines are prefixed by the logger with "[<time> DEBUG <target>] "
+        let logs = std::fs::read_to_string(&log_path).unwrap();
+        let _ = std::fs::remove_file(&log_path);
+        let events = amm_events::decode_logs(
+            &program_id.to_string(),
+            logs.lines()
+                .filter_map(|line| line.split_once("] ").map(|(_, log)| log)),
+        )
+        .unwrap();
+
+        assert_eq!(
+            events,
+            vec![AmmEvent::Swap(SwapEvent {
+                pool: config.to_bytes(),
+                user: user.to_bytes(),
+                in_x: true,
+                amount_in: 1_000_000,
+                amount_out: 899_100,
+                fee: 99_901,
+                reserve_x: 1_001_000_000,
+                reserve_y: 999_100_900,
+            })]
+        );
+    }
+}
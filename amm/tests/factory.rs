@@ -0,0 +1,144 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod factory_tests {
+    use crate::shared::{self};
+    use amm::{AmmError, Factory};
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    #[test]
+    fn initialize_factory() {
+        let (mollusk, program_id) = shared::setup();
+        let (system_program, system_program_account) =
+            mollusk_svm::program::keyed_account_for_system_program();
+
+        let payer = Pubkey::new_unique();
+        let admin = Pubkey::new_unique();
+        let factory = shared::factory_address(&program_id);
+
+        let data = [vec![18], admin.to_bytes().to_vec()].concat();
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(payer, true),
+                AccountMeta::new(factory, false),
+                AccountMeta::new_readonly(system_program, false),
+            ],
+        );
+
+        let result: InstructionResult = mollusk.process_instruction(
+            &instruction,
+            &[
+                (
+                    payer,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (factory, AccountSharedData::default()),
+                (system_program, system_program_account),
+            ],
+        );
+
+        assert!(!result.program_result.is_err());
+
+        let factory_account = result.get_account(&factory).unwrap();
+        assert_eq!(factory_account.owner(), &program_id);
+        assert_eq!(factory_account.data().len(), Factory::LEN);
+        assert_eq!(&factory_account.data()[..32], admin.as_ref());
+        assert_eq!(factory_account.data()[32], 0);
+    }
+
+    fn process_set_fee_tier(
+        fee: u16,
+        enabled: bool,
+        fee_tiers: &[u16],
+        signer: Option<Pubkey>,
+    ) -> (InstructionResult, Pubkey) {
+        let (mollusk, program_id) = shared::setup();
+
+        let admin = Pubkey::new_unique();
+        let signer = signer.unwrap_or(admin);
+        let factory = shared::factory_address(&program_id);
+
+        let data = [vec![19], fee.to_le_bytes().to_vec(), vec![enabled as u8]].concat();
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(signer, true),
+                AccountMeta::new(factory, false),
+            ],
+        );
+
+        let result: InstructionResult = mollusk.process_instruction(
+            &instruction,
+            &[
+                (
+                    signer,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (
+                    factory,
+                    shared::create_factory(&mollusk, admin, fee_tiers, program_id),
+                ),
+            ],
+        );
+
+        (result, factory)
+    }
+
+    fn get_fee_tiers(result: &InstructionResult, factory: Pubkey) -> Vec<u16> {
+        let data = result.get_account(&factory).unwrap().data();
+        (0..data[32] as usize)
+            .map(|slot| u16::from_le_bytes(data[33 + 2 * slot..35 + 2 * slot].try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn set_fee_tier_adds_tier() {
+        let (result, factory) = process_set_fee_tier(100, true, &[5, 30], None);
+
+        assert!(!result.program_result.is_err());
+        assert_eq!(get_fee_tiers(&result, factory), vec![5, 30, 100]);
+    }
+
+    #[test]
+    fn set_fee_tier_removes_tier() {
+        let (result, factory) = process_set_fee_tier(5, false, &[5, 30, 100], None);
+
+        assert!(!result.program_result.is_err());
+        // The last tier takes the freed slot
+        assert_eq!(get_fee_tiers(&result, factory), vec![100, 30]);
+    }
+
+    #[test]
+    fn set_fee_tier_fails_when_full() {
+        let fee_tiers: Vec<u16> = (1..=amm::MAX_FEE_TIERS as u16).collect();
+        let (result, _) = process_set_fee_tier(100, true, &fee_tiers, None);
+
+        shared::expect_error(&result, AmmError::InvalidFeeTier);
+    }
+
+    #[test]
+    fn set_fee_tier_fails_with_invalid_admin() {
+        let (result, _) = process_set_fee_tier(100, true, &[], Some(Pubkey::new_unique()));
+
+        shared::expect_error(&result, AmmError::InvalidAuthority);
+    }
+
+    #[test]
+    fn set_fee_tier_fails_with_invalid_fee() {
+        let (result, _) = process_set_fee_tier(1_001, true, &[], None);
+
+        shared::expect_error(&result, AmmError::InvalidFee);
+    }
+}
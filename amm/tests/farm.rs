@@ -0,0 +1,587 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod farm_tests {
+    use crate::shared::{self, Pool};
+    use amm::{AmmError, Farm, Stake, FARM_SEED, STAKE_SEED, VAULT_SEED};
+    use mollusk_svm::result::{InstructionResult, ProgramResult};
+
+    use solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    };
+
+    const NOW: i64 = 1_000;
+    const RATE: u64 = 10_000;
+    const STAKED: u64 = 1_000;
+    // 10 seconds at 10_000 per second shared by 1_000 staked LP tokens
+    const REWARDS: u64 = 100_000;
+
+    /// Farm of the pool rewarding Y tokens, and its stake and reward vaults
+    fn farm_addresses(pool: &Pool) -> (Pubkey, Pubkey, Pubkey) {
+        let (farm, _) =
+            Pubkey::find_program_address(&[FARM_SEED, pool.config.as_ref()], &pool.program_id);
+        let (stake_vault, _) = Pubkey::find_program_address(
+            &[VAULT_SEED, farm.as_ref(), pool.mint_lp.as_ref()],
+            &pool.program_id,
+        );
+        let (reward_vault, _) = Pubkey::find_program_address(
+            &[VAULT_SEED, farm.as_ref(), pool.mint_y.as_ref()],
+            &pool.program_id,
+        );
+        (farm, stake_vault, reward_vault)
+    }
+
+    fn setup_pool() -> Pool {
+        let mut pool = shared::setup_pool(Pubkey::new_unique(), 1_000_000_000);
+        pool.mollusk.sysvars.clock.unix_timestamp = NOW;
+        pool
+    }
+
+    /// Adds the farm, staked for `staked` LP tokens at RATE since
+    /// `last_update` and owing `owed` rewards, and both of its vaults
+    fn add_farm(pool: &mut Pool, last_update: i64, staked: u64, owed: u64) {
+        let (farm, stake_vault, reward_vault) = farm_addresses(pool);
+        let mut farm_account = AccountSharedData::new(
+            pool.mollusk.sysvars.rent.minimum_balance(Farm::LEN),
+            Farm::LEN,
+            &pool.program_id,
+        );
+        let data = [
+            pool.config.to_bytes().to_vec(),
+            pool.mint_y.to_bytes().to_vec(),
+            reward_vault.to_bytes().to_vec(),
+            stake_vault.to_bytes().to_vec(),
+            RATE.to_le_bytes().to_vec(),        // reward rate
+            (NOW + 100).to_le_bytes().to_vec(), // reward end
+            last_update.to_le_bytes().to_vec(), // last update
+            0u128.to_le_bytes().to_vec(),       // reward per share
+            staked.to_le_bytes().to_vec(),      // total staked
+            0u64.to_le_bytes().to_vec(),        // unallocated
+            owed.to_le_bytes().to_vec(),        // owed rewards
+        ]
+        .concat();
+        farm_account.set_data_from_slice(&data);
+
+        let token_program = mollusk_svm_programs_token::token::ID;
+        pool.accounts.extend([
+            (farm, farm_account),
+            (
+                stake_vault,
+                shared::create_token_account(
+                    &pool.mollusk,
+                    pool.mint_lp,
+                    pool.authority,
+                    staked,
+                    token_program,
+                ),
+            ),
+            (
+                reward_vault,
+                shared::create_token_account(
+                    &pool.mollusk,
+                    pool.mint_y,
+                    pool.authority,
+                    1_000_000,
+                    token_program,
+                ),
+            ),
+        ]);
+    }
+
+    /// Adds the stake of `owner`, holding every staked LP token
+    fn add_stake(pool: &mut Pool, owner: Pubkey) -> Pubkey {
+        let (farm, _, _) = farm_addresses(pool);
+        let (stake, _) = Pubkey::find_program_address(
+            &[STAKE_SEED, farm.as_ref(), owner.as_ref()],
+            &pool.program_id,
+        );
+        let mut stake_account = AccountSharedData::new(
+            pool.mollusk.sysvars.rent.minimum_balance(Stake::LEN),
+            Stake::LEN,
+            &pool.program_id,
+        );
+        let data = [
+            farm.to_bytes().to_vec(),
+            owner.to_bytes().to_vec(),
+            STAKED.to_le_bytes().to_vec(), // amount
+            0u128.to_le_bytes().to_vec(),  // reward per share paid
+            0u64.to_le_bytes().to_vec(),   // pending rewards
+        ]
+        .concat();
+        stake_account.set_data_from_slice(&data);
+        pool.accounts.push((stake, stake_account));
+
+        stake
+    }
+
+    fn get_farm(result: &InstructionResult, farm: Pubkey) -> (u64, i64, i64, u128, u64) {
+        let data = result.get_account(&farm).unwrap().data();
+        (
+            u64::from_le_bytes(data[128..136].try_into().unwrap()),
+            i64::from_le_bytes(data[136..144].try_into().unwrap()),
+            i64::from_le_bytes(data[144..152].try_into().unwrap()),
+            u128::from_le_bytes(data[152..168].try_into().unwrap()),
+            u64::from_le_bytes(data[168..176].try_into().unwrap()),
+        )
+    }
+
+    /// Unallocated and owed rewards of the farm
+    fn get_farm_rewards(result: &InstructionResult, farm: Pubkey) -> (u64, u64) {
+        let data = result.get_account(&farm).unwrap().data();
+        (
+            u64::from_le_bytes(data[176..184].try_into().unwrap()),
+            u64::from_le_bytes(data[184..192].try_into().unwrap()),
+        )
+    }
+
+    fn get_stake(result: &InstructionResult, stake: Pubkey) -> (u64, u128, u64) {
+        let data = result.get_account(&stake).unwrap().data();
+        (
+            u64::from_le_bytes(data[64..72].try_into().unwrap()),
+            u128::from_le_bytes(data[72..88].try_into().unwrap()),
+            u64::from_le_bytes(data[88..96].try_into().unwrap()),
+        )
+    }
+
+    #[test]
+    fn initialize_farm() {
+        let mut pool = setup_pool();
+        let (farm, stake_vault, reward_vault) = farm_addresses(&pool);
+        let token_program = mollusk_svm_programs_token::token::ID;
+
+        pool.accounts.extend([
+            (farm, AccountSharedData::default()),
+            (stake_vault, AccountSharedData::default()),
+            (reward_vault, AccountSharedData::default()),
+        ]);
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &[20],
+            vec![
+                AccountMeta::new(pool.update_authority, true),
+                AccountMeta::new_readonly(pool.authority, false),
+                AccountMeta::new_readonly(pool.config, false),
+                AccountMeta::new(farm, false),
+                AccountMeta::new_readonly(pool.mint_lp, false),
+                AccountMeta::new_readonly(pool.mint_y, false),
+                AccountMeta::new(stake_vault, false),
+                AccountMeta::new(reward_vault, false),
+                AccountMeta::new_readonly(pool.system_program, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        let farm_data = result.get_account(&farm).unwrap().data();
+        assert_eq!(farm_data.len(), Farm::LEN);
+        assert_eq!(&farm_data[..32], pool.config.as_ref());
+        assert_eq!(&farm_data[32..64], pool.mint_y.as_ref());
+        assert_eq!(&farm_data[64..96], reward_vault.as_ref());
+        assert_eq!(&farm_data[96..128], stake_vault.as_ref());
+        assert_eq!(get_farm(&result, farm), (0, 0, 0, 0, 0));
+
+        shared::expect_token_balance(&result, stake_vault, 0);
+        shared::expect_token_balance(&result, reward_vault, 0);
+    }
+
+    fn process_fund_farm(signer: Option<Pubkey>, staked: u64) -> (InstructionResult, Pool, Pubkey) {
+        let mut pool = setup_pool();
+        let (farm, _, reward_vault) = farm_addresses(&pool);
+        // Half of the current emission is left
+        add_farm(&mut pool, NOW - 50, staked, 0);
+        pool.mollusk.sysvars.clock.unix_timestamp = NOW + 50;
+
+        let signer = signer.unwrap_or(pool.update_authority);
+        let funder_token = Pubkey::new_unique();
+        pool.accounts.extend([
+            (
+                signer,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                funder_token,
+                shared::create_token_account(
+                    &pool.mollusk,
+                    pool.mint_y,
+                    signer,
+                    1_000_000,
+                    mollusk_svm_programs_token::token::ID,
+                ),
+            ),
+        ]);
+
+        let data = [
+            vec![21],
+            1_000_000u64.to_le_bytes().to_vec(), // amount
+            1_000i64.to_le_bytes().to_vec(),     // duration
+        ]
+        .concat();
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &data,
+            vec![
+                AccountMeta::new(signer, true),
+                AccountMeta::new(funder_token, false),
+                AccountMeta::new(reward_vault, false),
+                AccountMeta::new(farm, false),
+                AccountMeta::new_readonly(pool.config, false),
+                AccountMeta::new_readonly(pool.mint_y, false),
+                AccountMeta::new_readonly(mollusk_svm_programs_token::token::ID, false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        (result, pool, funder_token)
+    }
+
+    #[test]
+    fn fund_farm() {
+        let (result, pool, funder_token) = process_fund_farm(None, STAKED);
+        let (farm, _, reward_vault) = farm_addresses(&pool);
+
+        assert!(!result.program_result.is_err());
+
+        // 500_000 left from the current emission plus the 1_000_000 funded,
+        // over 1_000 seconds, the 100 seconds elapsed being accumulated first
+        assert_eq!(
+            get_farm(&result, farm),
+            (1_500, NOW + 1_050, NOW + 50, 1_000_000_000_000_000, STAKED)
+        );
+        assert_eq!(get_farm_rewards(&result, farm), (0, 0));
+        shared::expect_token_balance(&result, funder_token, 0);
+        shared::expect_token_balance(&result, reward_vault, 2_000_000);
+    }
+
+    #[test]
+    fn fund_farm_with_unallocated_rewards() {
+        let (result, pool, _) = process_fund_farm(None, 0);
+        let (farm, ..) = farm_addresses(&pool);
+
+        assert!(!result.program_result.is_err());
+
+        // The 1_000_000 emitted over the 100 seconds without any stake are
+        // spread along with the 500_000 left and the 1_000_000 funded
+        assert_eq!(
+            get_farm(&result, farm),
+            (2_500, NOW + 1_050, NOW + 50, 0, 0)
+        );
+        assert_eq!(get_farm_rewards(&result, farm), (0, 0));
+    }
+
+    #[test]
+    fn fund_farm_fails_with_invalid_authority() {
+        let (result, ..) = process_fund_farm(Some(Pubkey::new_unique()), STAKED);
+
+        shared::expect_error(&result, AmmError::InvalidAuthority);
+    }
+
+    fn process_stake_lp(stake_lamports: u64) -> (InstructionResult, Pool, Pubkey, Pubkey, Pubkey) {
+        let mut pool = setup_pool();
+        let (farm, stake_vault, _) = farm_addresses(&pool);
+        add_farm(&mut pool, NOW, STAKED, 0);
+        let token_program = mollusk_svm_programs_token::token::ID;
+
+        let user = Pubkey::new_unique();
+        let user_lp = Pubkey::new_unique();
+        let (stake, _) = Pubkey::find_program_address(
+            &[STAKE_SEED, farm.as_ref(), user.as_ref()],
+            &pool.program_id,
+        );
+        pool.accounts.extend([
+            (
+                user,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                user_lp,
+                shared::create_token_account(
+                    &pool.mollusk,
+                    pool.mint_lp,
+                    user,
+                    STAKED,
+                    token_program,
+                ),
+            ),
+            (
+                stake,
+                AccountSharedData::new(stake_lamports, 0, &Pubkey::default()),
+            ),
+        ]);
+        // Rewards accrued by the other stakers since the last update
+        pool.mollusk.sysvars.clock.unix_timestamp = NOW + 10;
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &[vec![22], STAKED.to_le_bytes().to_vec()].concat(),
+            vec![
+                AccountMeta::new(user, true),
+                AccountMeta::new(stake, false),
+                AccountMeta::new(farm, false),
+                AccountMeta::new(user_lp, false),
+                AccountMeta::new(stake_vault, false),
+                AccountMeta::new_readonly(pool.system_program, false),
+                AccountMeta::new_readonly(token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        (result, pool, user, user_lp, stake)
+    }
+
+    #[test]
+    fn stake_lp() {
+        let (result, pool, user, user_lp, stake) = process_stake_lp(0);
+        let (farm, stake_vault, _) = farm_addresses(&pool);
+
+        assert!(!result.program_result.is_err());
+
+        let reward_per_share = 100_000_000_000_000;
+        assert_eq!(
+            get_farm(&result, farm),
+            (RATE, NOW + 100, NOW + 10, reward_per_share, 2 * STAKED)
+        );
+        // The new stake starts from the current reward per share
+        let stake_data = result.get_account(&stake).unwrap().data();
+        assert_eq!(&stake_data[..32], farm.as_ref());
+        assert_eq!(&stake_data[32..64], user.as_ref());
+        assert_eq!(get_stake(&result, stake), (STAKED, reward_per_share, 0));
+        shared::expect_token_balance(&result, user_lp, 0);
+        shared::expect_token_balance(&result, stake_vault, 2 * STAKED);
+    }
+
+    #[test]
+    fn stake_lp_with_prefunded_stake() {
+        let (result, _, _, _, stake) = process_stake_lp(1_000);
+
+        assert!(!result.program_result.is_err());
+
+        let stake_account = result.get_account(&stake).unwrap();
+        assert_eq!(stake_account.owner(), &Pubkey::new_from_array(amm::ID));
+        assert_eq!(stake_account.data().len(), Stake::LEN);
+        assert_eq!(get_stake(&result, stake).0, STAKED);
+    }
+
+    fn process_unstake_lp(amount: u64) -> (InstructionResult, Pool, Pubkey, Pubkey, Pubkey) {
+        let mut pool = setup_pool();
+        let (farm, stake_vault, _) = farm_addresses(&pool);
+        add_farm(&mut pool, NOW, STAKED, 0);
+        let user = Pubkey::new_unique();
+        let stake = add_stake(&mut pool, user);
+        let token_program = mollusk_svm_programs_token::token::ID;
+
+        let user_lp = Pubkey::new_unique();
+        pool.accounts.extend([
+            (
+                user,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                user_lp,
+                shared::create_token_account(&pool.mollusk, pool.mint_lp, user, 0, token_program),
+            ),
+        ]);
+        pool.mollusk.sysvars.clock.unix_timestamp = NOW + 10;
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &[vec![23], amount.to_le_bytes().to_vec()].concat(),
+            vec![
+                AccountMeta::new(user, true),
+                AccountMeta::new_readonly(pool.authority, false),
+                AccountMeta::new(stake, false),
+                AccountMeta::new(farm, false),
+                AccountMeta::new_readonly(pool.config, false),
+                AccountMeta::new(user_lp, false),
+                AccountMeta::new(stake_vault, false),
+                AccountMeta::new_readonly(token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        (result, pool, stake, user_lp, stake_vault)
+    }
+
+    #[test]
+    fn unstake_lp() {
+        let (result, pool, stake, user_lp, stake_vault) = process_unstake_lp(STAKED);
+        let (farm, ..) = farm_addresses(&pool);
+
+        assert!(!result.program_result.is_err());
+
+        // Rewards are settled and stay claimable
+        assert_eq!(get_stake(&result, stake), (0, 100_000_000_000_000, REWARDS));
+        assert_eq!(get_farm_rewards(&result, farm), (0, REWARDS));
+        shared::expect_token_balance(&result, user_lp, STAKED);
+        shared::expect_token_balance(&result, stake_vault, 0);
+    }
+
+    #[test]
+    fn unstake_lp_fails_above_stake() {
+        let (result, ..) = process_unstake_lp(STAKED + 1);
+
+        assert_eq!(
+            result.program_result,
+            ProgramResult::Failure(ProgramError::InsufficientFunds)
+        );
+    }
+
+    fn process_claim_rewards(foreign: bool) -> (InstructionResult, Pool, Pubkey, Pubkey, Pubkey) {
+        let mut pool = setup_pool();
+        let (farm, _, reward_vault) = farm_addresses(&pool);
+        add_farm(&mut pool, NOW, STAKED, 0);
+        let owner = Pubkey::new_unique();
+        let stake = add_stake(&mut pool, owner);
+        let user = if foreign { Pubkey::new_unique() } else { owner };
+        let token_program = mollusk_svm_programs_token::token::ID;
+
+        let user_reward = Pubkey::new_unique();
+        pool.accounts.extend([
+            (
+                user,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                user_reward,
+                shared::create_token_account(&pool.mollusk, pool.mint_y, user, 0, token_program),
+            ),
+        ]);
+        pool.mollusk.sysvars.clock.unix_timestamp = NOW + 10;
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &[24],
+            vec![
+                AccountMeta::new(user, true),
+                AccountMeta::new_readonly(pool.authority, false),
+                AccountMeta::new(stake, false),
+                AccountMeta::new(farm, false),
+                AccountMeta::new_readonly(pool.config, false),
+                AccountMeta::new(user_reward, false),
+                AccountMeta::new(reward_vault, false),
+                AccountMeta::new_readonly(pool.mint_y, false),
+                AccountMeta::new_readonly(token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        (result, pool, stake, user_reward, reward_vault)
+    }
+
+    #[test]
+    fn claim_rewards() {
+        let (result, pool, stake, user_reward, reward_vault) = process_claim_rewards(false);
+        let (farm, ..) = farm_addresses(&pool);
+
+        assert!(!result.program_result.is_err());
+
+        assert_eq!(get_stake(&result, stake), (STAKED, 100_000_000_000_000, 0));
+        assert_eq!(get_farm_rewards(&result, farm), (0, 0));
+        shared::expect_token_balance(&result, user_reward, REWARDS);
+        shared::expect_token_balance(&result, reward_vault, 1_000_000 - REWARDS);
+    }
+
+    #[test]
+    fn claim_rewards_fails_with_foreign_stake() {
+        let (result, ..) = process_claim_rewards(true);
+
+        shared::expect_error(&result, AmmError::InvalidStake);
+    }
+
+    fn process_sweep_rewards(now: i64, staked: u64) -> (InstructionResult, Pool, Pubkey) {
+        let mut pool = setup_pool();
+        let (farm, _, reward_vault) = farm_addresses(&pool);
+        add_farm(&mut pool, NOW, staked, 30_000);
+        let token_program = mollusk_svm_programs_token::token::ID;
+
+        let authority_reward = Pubkey::new_unique();
+        pool.accounts.extend([
+            (
+                pool.update_authority,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                authority_reward,
+                shared::create_token_account(
+                    &pool.mollusk,
+                    pool.mint_y,
+                    pool.update_authority,
+                    0,
+                    token_program,
+                ),
+            ),
+        ]);
+        pool.mollusk.sysvars.clock.unix_timestamp = now;
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &[37],
+            vec![
+                AccountMeta::new(pool.update_authority, true),
+                AccountMeta::new_readonly(pool.authority, false),
+                AccountMeta::new(farm, false),
+                AccountMeta::new_readonly(pool.config, false),
+                AccountMeta::new(authority_reward, false),
+                AccountMeta::new(reward_vault, false),
+                AccountMeta::new_readonly(pool.mint_y, false),
+                AccountMeta::new_readonly(token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        (result, pool, authority_reward)
+    }
+
+    #[test]
+    fn sweep_rewards() {
+        let (result, pool, authority_reward) = process_sweep_rewards(NOW + 200, 0);
+        let (farm, _, reward_vault) = farm_addresses(&pool);
+
+        assert!(!result.program_result.is_err());
+
+        // Everything but the rewards owed to the stakes is swept
+        assert_eq!(get_farm_rewards(&result, farm), (0, 30_000));
+        shared::expect_token_balance(&result, authority_reward, 970_000);
+        shared::expect_token_balance(&result, reward_vault, 30_000);
+    }
+
+    #[test]
+    fn sweep_rewards_fails_before_reward_end() {
+        let (result, ..) = process_sweep_rewards(NOW + 50, 0);
+
+        shared::expect_error(&result, AmmError::FarmActive);
+    }
+
+    #[test]
+    fn sweep_rewards_fails_with_staked_lp() {
+        let (result, ..) = process_sweep_rewards(NOW + 200, STAKED);
+
+        shared::expect_error(&result, AmmError::FarmActive);
+    }
+}
@@ -0,0 +1,184 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod flash_loan_tests {
+    use crate::shared::{self};
+    use amm::AmmError;
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::AccountSharedData,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        sysvar::{
+            self,
+            instructions::{construct_instructions_data, BorrowedAccountMeta, BorrowedInstruction},
+        },
+    };
+
+    const AMOUNT: u64 = 1_000_000;
+    // 10% pool fee on the amount lent
+    const FEE: u64 = 100_000;
+
+    fn instructions_sysvar(instructions: &[Instruction]) -> AccountSharedData {
+        let borrowed = instructions
+            .iter()
+            .map(|instruction| BorrowedInstruction {
+                program_id: &instruction.program_id,
+                accounts: instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| BorrowedAccountMeta {
+                        pubkey: &meta.pubkey,
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable,
+                    })
+                    .collect(),
+                data: &instruction.data,
+            })
+            .collect::<Vec<_>>();
+
+        let mut account = AccountSharedData::new(1_000_000_000u64, 0, &sysvar::ID);
+        account.set_data_from_slice(&construct_instructions_data(&borrowed));
+        account
+    }
+
+    fn process_flash_loan(loans: usize, repaid: bool) -> (InstructionResult, [Pubkey; 3]) {
+        let (mollusk, program_id) = shared::setup();
+        let (token_program, token_program_account) =
+            mollusk_svm_programs_token::token::keyed_account();
+
+        let borrower = Pubkey::new_unique();
+        let config = Pubkey::new_unique();
+        let (authority, bump) = Pubkey::find_program_address(&[config.as_ref()], &program_id);
+        let mint_x = Pubkey::new_unique();
+        let mint_y = Pubkey::new_unique();
+        let borrower_x = Pubkey::new_unique();
+        let vault_x = Pubkey::new_unique();
+        let vault_y = Pubkey::new_unique();
+
+        let loan = Instruction::new_with_bytes(
+            program_id,
+            &[vec![10], AMOUNT.to_le_bytes().to_vec()].concat(),
+            vec![
+                AccountMeta::new(borrower, true),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new(vault_x, false),
+                AccountMeta::new(borrower_x, false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(sysvar::instructions::ID, false),
+                AccountMeta::new_readonly(mint_x, false),
+                AccountMeta::new_readonly(token_program, false),
+            ],
+        );
+
+        let repay = Instruction::new_with_bytes(
+            program_id,
+            &[11],
+            vec![
+                AccountMeta::new(borrower, true),
+                AccountMeta::new(borrower_x, false),
+                AccountMeta::new(vault_x, false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(mint_x, false),
+                AccountMeta::new_readonly(token_program, false),
+            ],
+        );
+
+        let mut config_account = shared::create_config(
+            &mollusk,
+            0,
+            authority,
+            mint_x,
+            mint_y,
+            Pubkey::new_unique(),
+            vault_x,
+            vault_y,
+            1_000u16,
+            bump,
+            program_id,
+        );
+        shared::set_reserves(&mut config_account, 1_000_000_000, 1_000_000_000);
+
+        let mut instructions = vec![loan; loans];
+        if repaid {
+            instructions.push(repay);
+        }
+
+        let accounts = vec![
+            (
+                borrower,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                authority,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                vault_x,
+                shared::create_token_account(
+                    &mollusk,
+                    mint_x,
+                    authority,
+                    1_000_000_000,
+                    token_program,
+                ),
+            ),
+            (
+                vault_y,
+                shared::create_token_account(
+                    &mollusk,
+                    mint_y,
+                    authority,
+                    1_000_000_000,
+                    token_program,
+                ),
+            ),
+            (
+                borrower_x,
+                shared::create_token_account(&mollusk, mint_x, borrower, FEE, token_program),
+            ),
+            (
+                mint_x,
+                shared::create_mint_account(&mollusk, borrower, 0, 6, true, token_program),
+            ),
+            (config, config_account),
+            (sysvar::instructions::ID, instructions_sysvar(&instructions)),
+            (token_program, token_program_account),
+        ];
+
+        let result: InstructionResult = mollusk.process_instruction_chain(&instructions, &accounts);
+
+        (result, [borrower_x, vault_x, config])
+    }
+
+    #[test]
+    fn flash_loan() {
+        let (result, [borrower_x, vault_x, config]) = process_flash_loan(1, true);
+
+        assert!(!result.program_result.is_err());
+
+        // The fee stays in the vault for the LPs
+        shared::expect_token_balance(&result, borrower_x, 0);
+        shared::expect_token_balance(&result, vault_x, 1_000_000_000 + FEE);
+        assert_eq!(
+            shared::get_reserves(&result, config),
+            (1_000_000_000 + FEE, 1_000_000_000)
+        );
+    }
+
+    #[test]
+    fn flash_loan_fails_when_not_repaid() {
+        let (result, _) = process_flash_loan(1, false);
+
+        shared::expect_error(&result, AmmError::FlashLoanNotRepaid);
+    }
+
+    #[test]
+    fn flash_loan_fails_when_reentered() {
+        let (result, _) = process_flash_loan(2, true);
+
+        shared::expect_error(&result, AmmError::FlashLoanActive);
+    }
+}
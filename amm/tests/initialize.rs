@@ -3,50 +3,330 @@ mod shared;
 
 #[cfg(test)]
 mod initialize_tests {
-    use crate::shared;
-    use amm::Config;
+    use crate::shared::{self, PoolAddresses};
+    use amm::{AmmError, Config, CURVE_STABLE, CURVE_WEIGHTED, LP_DECIMALS};
+    use mollusk_svm::result::InstructionResult;
 
     use solana_sdk::{
-        account::AccountSharedData,
+        account::{AccountSharedData, ReadableAccount},
         instruction::{AccountMeta, Instruction},
+        program_pack::Pack,
         pubkey::Pubkey,
     };
 
-    #[test]
-    fn initialize() {
+    const SEED: u16 = 42;
+
+    struct InitializeAccounts {
+        pool: PoolAddresses,
+        mint_x: Pubkey,
+        mint_y: Pubkey,
+        registry: Pubkey,
+        registry_lamports: u64,
+    }
+
+    // The curve parameter is the amp, or the start weight of weighted pools
+    // moving to 50/50 between timestamps 100 and 1_100
+    fn process_initialize(
+        fee: u16,
+        curve: (u8, u64),
+        transfer_fee: bool,
+        tamper: impl FnOnce(&mut InitializeAccounts),
+    ) -> (
+        InstructionResult,
+        PoolAddresses,
+        Pubkey,
+        Pubkey,
+        Pubkey,
+        Pubkey,
+    ) {
         let (mollusk, program_id) = shared::setup();
+        let (token_program, token_program_account) =
+            mollusk_svm_programs_token::token::keyed_account();
+        let (token_2022_program, token_2022_program_account) =
+            mollusk_svm_programs_token::token2022::keyed_account();
+        let (system_program, system_program_account) =
+            mollusk_svm::program::keyed_account_for_system_program();
 
-        let config = Pubkey::new_unique();
+        let initializer = Pubkey::new_unique();
+        let update_authority = Pubkey::new_unique();
+        let pause_guardian = Pubkey::new_unique();
+        let (mint_x, mint_y) = shared::sorted_mints();
+        let factory = shared::factory_address(&program_id);
+        let mut accounts = InitializeAccounts {
+            pool: shared::pool_addresses(&program_id, SEED, &mint_x, &mint_y),
+            mint_x,
+            mint_y,
+            registry: shared::registry_address(&program_id, &mint_x, &mint_y, fee),
+            registry_lamports: 0,
+        };
+        tamper(&mut accounts);
+        let InitializeAccounts {
+            pool,
+            mint_x,
+            mint_y,
+            registry,
+            registry_lamports,
+        } = accounts;
+
+        let (token_program_x, mint_x_account) = if transfer_fee {
+            (
+                token_2022_program,
+                shared::create_transfer_fee_mint_account(&mollusk, initializer, 6, 100, u64::MAX),
+            )
+        } else {
+            (
+                token_program,
+                shared::create_mint_account(&mollusk, initializer, 0, 6, true, token_program),
+            )
+        };
 
         let data = [
-            vec![0],                               // Instruction
-            vec![0],                               // status
-            Pubkey::default().to_bytes().to_vec(), // authority
-            Pubkey::default().to_bytes().to_vec(), // mint x
-            Pubkey::default().to_bytes().to_vec(), // mint y
-            Pubkey::default().to_bytes().to_vec(), // mint lp
-            Pubkey::default().to_bytes().to_vec(), // vault x
-            Pubkey::default().to_bytes().to_vec(), // vault y
-            u16::MAX.to_le_bytes().to_vec(),       // fee
-            u8::MAX.to_le_bytes().to_vec(),        // authority bump
+            vec![0],                                 // Instruction
+            SEED.to_le_bytes().to_vec(),             // seed
+            update_authority.to_bytes().to_vec(),    // authority
+            fee.to_le_bytes().to_vec(),              // fee
+            2_000u16.to_le_bytes().to_vec(),         // protocol fee
+            vec![curve.0],                           // curve
+            curve.1.to_le_bytes().to_vec(),          // amp
+            pause_guardian.to_bytes().to_vec(),      // pause guardian
+            1_000u16.to_le_bytes().to_vec(),         // max referral fee
+            500u16.to_le_bytes().to_vec(),           // max price impact
+            (curve.1 as u16).to_le_bytes().to_vec(), // start weight
+            5_000u16.to_le_bytes().to_vec(),         // end weight
+            100i64.to_le_bytes().to_vec(),           // start time
+            1_100i64.to_le_bytes().to_vec(),         // end time
         ]
         .concat();
 
-        let instruction =
-            Instruction::new_with_bytes(program_id, &data, vec![AccountMeta::new(config, true)]);
-
-        let lamports = mollusk.sysvars.rent.minimum_balance(Config::LEN);
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(initializer, true),
+                AccountMeta::new_readonly(pool.authority, false),
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new_readonly(mint_x, false),
+                AccountMeta::new_readonly(mint_y, false),
+                AccountMeta::new(pool.mint_lp, false),
+                AccountMeta::new(pool.vault_x, false),
+                AccountMeta::new(pool.vault_y, false),
+                AccountMeta::new_readonly(system_program, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(token_program_x, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(factory, false),
+                AccountMeta::new(registry, false),
+            ],
+        );
 
-        let result: mollusk_svm::result::InstructionResult = mollusk.process_instruction(
+        let result: InstructionResult = mollusk.process_instruction(
             &instruction,
-            &vec![(
-                config,
-                AccountSharedData::new(lamports, Config::LEN, &program_id),
-            )],
+            &vec![
+                (
+                    initializer,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (pool.authority, AccountSharedData::default()),
+                (pool.config, AccountSharedData::default()),
+                (mint_x, mint_x_account),
+                (
+                    mint_y,
+                    shared::create_mint_account(&mollusk, initializer, 0, 6, true, token_program),
+                ),
+                (pool.mint_lp, AccountSharedData::default()),
+                (pool.vault_x, AccountSharedData::default()),
+                (pool.vault_y, AccountSharedData::default()),
+                (system_program, system_program_account),
+                (token_program, token_program_account),
+                (token_2022_program, token_2022_program_account),
+                (
+                    factory,
+                    shared::create_factory(&mollusk, Pubkey::new_unique(), &[5, 30], program_id),
+                ),
+                (
+                    registry,
+                    AccountSharedData::new(registry_lamports, 0, &Pubkey::default()),
+                ),
+            ],
         );
 
+        (
+            result,
+            pool,
+            update_authority,
+            mint_x,
+            mint_y,
+            pause_guardian,
+        )
+    }
+
+    #[test]
+    fn initialize() {
+        let (result, pool, update_authority, mint_x, mint_y, pause_guardian) =
+            process_initialize(30, (0, 0), false, |_| {});
+
+        assert!(!result.program_result.is_err());
+
+        let config_data = result.get_account(&pool.config).unwrap().data();
+        assert_eq!(config_data.len(), Config::LEN);
+        assert_eq!(config_data[0], 0);
+        assert_eq!(&config_data[1..33], update_authority.as_ref());
+        assert_eq!(&config_data[33..65], mint_x.as_ref());
+        assert_eq!(&config_data[65..97], mint_y.as_ref());
+        assert_eq!(&config_data[97..129], pool.mint_lp.as_ref());
+        assert_eq!(&config_data[129..161], pool.vault_x.as_ref());
+        assert_eq!(&config_data[161..193], pool.vault_y.as_ref());
+        assert_eq!(&config_data[193..195], &30u16.to_le_bytes());
+        assert_eq!(config_data[195], pool.authority_bump);
+        assert_eq!(&config_data[196..198], &2_000u16.to_le_bytes());
+        assert_eq!(&config_data[198..214], &[0u8; 16]);
+        assert_eq!(&config_data[272..288], &[0u8; 16]);
+        assert_eq!(&config_data[288..320], pause_guardian.as_ref());
+        assert_eq!(&config_data[352..354], &1_000u16.to_le_bytes());
+        assert_eq!(&config_data[354..356], &500u16.to_le_bytes());
+
+        let registry =
+            shared::registry_address(&Pubkey::new_from_array(amm::ID), &mint_x, &mint_y, 30);
+        let registry_data = result.get_account(&registry).unwrap().data();
+        assert_eq!(registry_data, pool.config.as_ref());
+
+        let mint_lp =
+            spl_token::state::Mint::unpack(result.get_account(&pool.mint_lp).unwrap().data())
+                .unwrap();
+        assert_eq!(mint_lp.mint_authority.unwrap(), pool.authority);
+        assert_eq!(mint_lp.decimals, LP_DECIMALS);
+        assert_eq!(mint_lp.supply, 0);
+
+        for (vault, mint) in [(pool.vault_x, mint_x), (pool.vault_y, mint_y)] {
+            let vault =
+                spl_token::state::Account::unpack(result.get_account(&vault).unwrap().data())
+                    .unwrap();
+            assert_eq!(vault.owner, pool.authority);
+            assert_eq!(vault.mint, mint);
+        }
+    }
+
+    #[test]
+    fn initialize_with_prefunded_registry() {
+        let (result, _, _, mint_x, mint_y, _) = process_initialize(30, (0, 0), false, |accounts| {
+            accounts.registry_lamports = 1_000;
+        });
+
+        assert!(!result.program_result.is_err());
+
+        let registry =
+            shared::registry_address(&Pubkey::new_from_array(amm::ID), &mint_x, &mint_y, 30);
+        let registry_account = result.get_account(&registry).unwrap();
+        assert_eq!(registry_account.owner(), &Pubkey::new_from_array(amm::ID));
+        assert_eq!(registry_account.data().len(), amm::Registry::LEN);
+    }
+
+    #[test]
+    fn initialize_fails_with_invalid_vault() {
+        let (result, ..) = process_initialize(30, (0, 0), false, |accounts| {
+            accounts.pool.vault_x = Pubkey::new_unique()
+        });
+
+        shared::expect_error(&result, AmmError::InvalidVault);
+    }
+
+    #[test]
+    fn initialize_fails_with_invalid_config() {
+        let (result, ..) = process_initialize(30, (0, 0), false, |accounts| {
+            accounts.pool.config = Pubkey::new_unique()
+        });
+
+        shared::expect_error(&result, AmmError::InvalidConfig);
+    }
+
+    #[test]
+    fn initialize_fails_with_invalid_fee() {
+        let (result, ..) = process_initialize(1_001, (0, 0), false, |_| {});
+
+        shared::expect_error(&result, AmmError::InvalidFee);
+    }
+
+    #[test]
+    fn initialize_stable() {
+        let (result, pool, ..) = process_initialize(30, (CURVE_STABLE, 100), false, |_| {});
+
+        assert!(!result.program_result.is_err());
+
+        let config_data = result.get_account(&pool.config).unwrap().data();
+        assert_eq!(config_data[263], CURVE_STABLE);
+        assert_eq!(&config_data[264..272], &100u64.to_le_bytes());
+    }
+
+    #[test]
+    fn initialize_weighted() {
+        let (result, pool, ..) = process_initialize(30, (CURVE_WEIGHTED, 9_000), false, |_| {});
+
         assert!(!result.program_result.is_err());
 
-        // We could add some tests to the config account created
+        let config_data = result.get_account(&pool.config).unwrap().data();
+        assert_eq!(config_data[263], CURVE_WEIGHTED);
+        assert_eq!(&config_data[356..358], &9_000u16.to_le_bytes());
+        assert_eq!(&config_data[358..360], &5_000u16.to_le_bytes());
+        assert_eq!(&config_data[360..368], &100i64.to_le_bytes());
+        assert_eq!(&config_data[368..376], &1_100i64.to_le_bytes());
+    }
+
+    #[test]
+    fn initialize_fails_with_invalid_curve() {
+        let (result, ..) = process_initialize(30, (3, 100), false, |_| {});
+
+        shared::expect_error(&result, AmmError::InvalidCurve);
+
+        let (result, ..) = process_initialize(30, (CURVE_STABLE, 0), false, |_| {});
+
+        shared::expect_error(&result, AmmError::InvalidCurve);
+
+        let (result, ..) = process_initialize(30, (CURVE_WEIGHTED, 0), false, |_| {});
+
+        shared::expect_error(&result, AmmError::InvalidCurve);
+
+        let (result, ..) = process_initialize(30, (CURVE_WEIGHTED, 10_000), false, |_| {});
+
+        shared::expect_error(&result, AmmError::InvalidCurve);
+    }
+
+    #[test]
+    fn initialize_with_transfer_fee_mint() {
+        let (result, pool, _, mint_x, ..) = process_initialize(30, (0, 0), true, |_| {});
+
+        assert!(!result.program_result.is_err());
+
+        // Token-2022 vault with room for the TransferFeeAmount extension
+        let vault_x = result.get_account(&pool.vault_x).unwrap();
+        assert_eq!(vault_x.owner(), &mollusk_svm_programs_token::token2022::ID);
+        assert_eq!(vault_x.data().len(), 178);
+        let vault_x = spl_token::state::Account::unpack_from_slice(&vault_x.data()[..165]).unwrap();
+        assert_eq!(vault_x.owner, pool.authority);
+        assert_eq!(vault_x.mint, mint_x);
+    }
+
+    #[test]
+    fn initialize_fails_with_fee_tier_not_whitelisted() {
+        let (result, ..) = process_initialize(50, (0, 0), false, |_| {});
+
+        shared::expect_error(&result, AmmError::InvalidFeeTier);
+    }
+
+    #[test]
+    fn initialize_fails_with_unsorted_mints() {
+        let (result, ..) = process_initialize(30, (0, 0), false, |accounts| {
+            std::mem::swap(&mut accounts.mint_x, &mut accounts.mint_y)
+        });
+
+        shared::expect_error(&result, AmmError::InvalidMint);
+    }
+
+    #[test]
+    fn initialize_fails_with_invalid_registry() {
+        let (result, ..) = process_initialize(30, (0, 0), false, |accounts| {
+            accounts.registry = Pubkey::new_unique()
+        });
+
+        shared::expect_error(&result, AmmError::InvalidRegistry);
     }
 }
@@ -0,0 +1,341 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod native_tests {
+    use crate::shared::{self, Pool};
+    use amm::{AmmError, NATIVE_SEED};
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    const RESERVE: u64 = 1_000_000_000;
+    const LAMPORTS: u64 = 10_000_000_000;
+    // LP tokens held by the user, a tenth of the supply
+    const USER_LP: u64 = 100_000_000;
+
+    fn native_address(pool: &Pool) -> Pubkey {
+        Pubkey::find_program_address(&[NATIVE_SEED, pool.config.as_ref()], &pool.program_id).0
+    }
+
+    /// Pool of wrapped SOL as X and a token as Y, with a 10% fee and even
+    /// reserves. The user holds SOL in its wallet, Y and LP tokens.
+    fn setup_pool() -> Pool {
+        let mut pool = shared::setup_pool(spl_token::native_mint::ID, RESERVE);
+
+        let user_y = shared::create_token_account(
+            &pool.mollusk,
+            pool.mint_y,
+            pool.user,
+            RESERVE,
+            pool.token_program,
+        );
+        let user_lp = shared::create_token_account(
+            &pool.mollusk,
+            pool.mint_lp,
+            pool.user,
+            USER_LP,
+            pool.token_program,
+        );
+        pool.set_account(
+            pool.user,
+            AccountSharedData::new(LAMPORTS, 0, &Pubkey::default()),
+        );
+        pool.set_account(pool.user_y, user_y);
+        pool.set_account(pool.user_lp, user_lp);
+        let native_account = native_address(&pool);
+        pool.accounts
+            .push((native_account, AccountSharedData::default()));
+
+        pool
+    }
+
+    fn lamports(result: &InstructionResult, account: Pubkey) -> u64 {
+        result.get_account(&account).unwrap().lamports()
+    }
+
+    /// Accounts of Swap and SwapExactOut, the user wallet standing in for its
+    /// X token account
+    fn swap_metas(pool: &Pool, in_x: bool, native_account: Pubkey) -> Vec<AccountMeta> {
+        let (vault_from, vault_to) = if in_x {
+            (pool.vault_x, pool.vault_y)
+        } else {
+            (pool.vault_y, pool.vault_x)
+        };
+
+        vec![
+            AccountMeta::new(pool.user, true),
+            AccountMeta::new(pool.authority, false),
+            AccountMeta::new(pool.user, true),
+            AccountMeta::new(pool.user_y, false),
+            AccountMeta::new(vault_from, false),
+            AccountMeta::new(vault_to, false),
+            AccountMeta::new(pool.config, false),
+            AccountMeta::new_readonly(pool.mint_x, false),
+            AccountMeta::new_readonly(pool.mint_y, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+            AccountMeta::new_readonly(pool.system_program, false),
+            AccountMeta::new(native_account, false),
+        ]
+    }
+
+    /// Swaps 1_000_000 of X for Y, or of Y for X
+    fn process_swap(pool: &Pool, in_x: bool, native_account: Pubkey) -> InstructionResult {
+        let data = [
+            vec![3],
+            1_000_000u64.to_le_bytes().to_vec(), // amount
+            1_000u64.to_le_bytes().to_vec(),     // min amount
+            i64::MAX.to_le_bytes().to_vec(),     // expiration
+        ]
+        .concat();
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &data,
+            swap_metas(pool, in_x, native_account),
+        );
+
+        pool.mollusk
+            .process_instruction(&instruction, &pool.accounts)
+    }
+
+    /// Swaps X for exactly 899_100 of Y, or Y for exactly 899_100 of X
+    fn process_swap_exact_out(pool: &Pool, in_x: bool) -> InstructionResult {
+        let data = [
+            vec![5],
+            899_100u64.to_le_bytes().to_vec(),   // amount
+            1_000_000u64.to_le_bytes().to_vec(), // max amount
+            i64::MAX.to_le_bytes().to_vec(),     // expiration
+        ]
+        .concat();
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &data,
+            swap_metas(pool, in_x, native_address(pool)),
+        );
+
+        pool.mollusk
+            .process_instruction(&instruction, &pool.accounts)
+    }
+
+    #[test]
+    fn swap_wraps_sol() {
+        let pool = setup_pool();
+        let result = process_swap(&pool, true, native_address(&pool));
+
+        assert!(!result.program_result.is_err());
+
+        assert_eq!(lamports(&result, pool.user), LAMPORTS - 1_000_000);
+        shared::expect_token_balance(&result, pool.vault_x, RESERVE + 1_000_000);
+        shared::expect_token_balance(&result, pool.user_y, RESERVE + 899_100);
+        assert_eq!(
+            shared::get_reserves(&result, pool.config),
+            (RESERVE + 1_000_000, RESERVE - 899_100)
+        );
+    }
+
+    #[test]
+    fn swap_unwraps_sol() {
+        let pool = setup_pool();
+        let result = process_swap(&pool, false, native_address(&pool));
+
+        assert!(!result.program_result.is_err());
+
+        // The rent of the native account is paid back when it is closed
+        assert_eq!(lamports(&result, pool.user), LAMPORTS + 899_100);
+        assert_eq!(lamports(&result, native_address(&pool)), 0);
+        shared::expect_token_balance(&result, pool.vault_x, RESERVE - 899_100);
+        shared::expect_token_balance(&result, pool.user_y, RESERVE - 1_000_000);
+    }
+
+    #[test]
+    fn swap_unwraps_sol_with_lamports_sent_to_native_account() {
+        let mut pool = setup_pool();
+        let native_account = native_address(&pool);
+        pool.set_account(
+            native_account,
+            AccountSharedData::new(1_000, 0, &Pubkey::default()),
+        );
+
+        let result = process_swap(&pool, false, native_account);
+
+        assert!(!result.program_result.is_err());
+
+        // The lamports sent beforehand end up in the wallet
+        assert_eq!(lamports(&result, pool.user), LAMPORTS + 899_100 + 1_000);
+        assert_eq!(lamports(&result, native_address(&pool)), 0);
+    }
+
+    #[test]
+    fn swap_exact_out_wraps_sol() {
+        let pool = setup_pool();
+        let result = process_swap_exact_out(&pool, true);
+
+        assert!(!result.program_result.is_err());
+
+        assert_eq!(lamports(&result, pool.user), LAMPORTS - 999_999);
+        shared::expect_token_balance(&result, pool.vault_x, RESERVE + 999_999);
+        shared::expect_token_balance(&result, pool.user_y, RESERVE + 899_100);
+        assert_eq!(
+            shared::get_reserves(&result, pool.config),
+            (RESERVE + 999_999, RESERVE - 899_100)
+        );
+    }
+
+    #[test]
+    fn swap_exact_out_unwraps_sol() {
+        let pool = setup_pool();
+        let result = process_swap_exact_out(&pool, false);
+
+        assert!(!result.program_result.is_err());
+
+        assert_eq!(lamports(&result, pool.user), LAMPORTS + 899_100);
+        assert_eq!(lamports(&result, native_address(&pool)), 0);
+        shared::expect_token_balance(&result, pool.vault_x, RESERVE - 899_100);
+        shared::expect_token_balance(&result, pool.user_y, RESERVE - 999_999);
+    }
+
+    #[test]
+    fn swap_fails_with_invalid_native_account() {
+        let mut pool = setup_pool();
+        let native_account = Pubkey::new_unique();
+        pool.accounts
+            .push((native_account, AccountSharedData::default()));
+
+        let result = process_swap(&pool, false, native_account);
+
+        shared::expect_error(&result, AmmError::InvalidNativeAccount);
+    }
+
+    #[test]
+    fn swap_fails_with_wallet_for_other_mint() {
+        let pool = setup_pool();
+
+        let data = [
+            vec![3],
+            1_000_000u64.to_le_bytes().to_vec(), // amount
+            1_000u64.to_le_bytes().to_vec(),     // min amount
+            i64::MAX.to_le_bytes().to_vec(),     // expiration
+        ]
+        .concat();
+
+        // The wallet stands in for the Y token account
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &data,
+            vec![
+                AccountMeta::new(pool.user, true),
+                AccountMeta::new(pool.authority, false),
+                AccountMeta::new(pool.vault_x, false),
+                AccountMeta::new(pool.user, true),
+                AccountMeta::new(pool.vault_x, false),
+                AccountMeta::new(pool.vault_y, false),
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new_readonly(pool.mint_x, false),
+                AccountMeta::new_readonly(pool.mint_y, false),
+                AccountMeta::new_readonly(pool.token_program, false),
+                AccountMeta::new_readonly(pool.token_program, false),
+                AccountMeta::new_readonly(pool.system_program, false),
+                AccountMeta::new(native_address(&pool), false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        shared::expect_error(&result, AmmError::InvalidMint);
+    }
+
+    fn liquidity_metas(pool: &Pool) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(pool.user, true),
+            AccountMeta::new(pool.authority, false),
+            AccountMeta::new(pool.mint_lp, false),
+            AccountMeta::new(pool.user, true),
+            AccountMeta::new(pool.user_y, false),
+            AccountMeta::new(pool.user_lp, false),
+            AccountMeta::new(pool.vault_x, false),
+            AccountMeta::new(pool.vault_y, false),
+            AccountMeta::new(pool.config, false),
+            AccountMeta::new(pool.token_program, false),
+            AccountMeta::new_readonly(pool.mint_x, false),
+            AccountMeta::new_readonly(pool.mint_y, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+            AccountMeta::new_readonly(pool.system_program, false),
+        ]
+    }
+
+    #[test]
+    fn deposit_wraps_sol() {
+        let pool = setup_pool();
+
+        let data = [
+            vec![1],
+            USER_LP.to_le_bytes().to_vec(),  // amount
+            USER_LP.to_le_bytes().to_vec(),  // max_x
+            USER_LP.to_le_bytes().to_vec(),  // max_y
+            i64::MAX.to_le_bytes().to_vec(), // expiration
+        ]
+        .concat();
+
+        let instruction =
+            Instruction::new_with_bytes(pool.program_id, &data, liquidity_metas(&pool));
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        assert_eq!(lamports(&result, pool.user), LAMPORTS - USER_LP);
+        shared::expect_token_balance(&result, pool.vault_x, RESERVE + USER_LP);
+        shared::expect_token_balance(&result, pool.user_y, RESERVE - USER_LP);
+        shared::expect_token_balance(&result, pool.user_lp, 2 * USER_LP);
+        assert_eq!(
+            shared::get_reserves(&result, pool.config),
+            (RESERVE + USER_LP, RESERVE + USER_LP)
+        );
+    }
+
+    #[test]
+    fn withdraw_unwraps_sol() {
+        let pool = setup_pool();
+
+        let data = [
+            vec![2],
+            USER_LP.to_le_bytes().to_vec(),  // amount
+            USER_LP.to_le_bytes().to_vec(),  // min_x
+            USER_LP.to_le_bytes().to_vec(),  // min_y
+            i64::MAX.to_le_bytes().to_vec(), // expiration
+        ]
+        .concat();
+
+        let mut metas = liquidity_metas(&pool);
+        metas.push(AccountMeta::new(native_address(&pool), false));
+        let instruction = Instruction::new_with_bytes(pool.program_id, &data, metas);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        assert_eq!(lamports(&result, pool.user), LAMPORTS + USER_LP);
+        assert_eq!(lamports(&result, native_address(&pool)), 0);
+        shared::expect_token_balance(&result, pool.vault_x, RESERVE - USER_LP);
+        shared::expect_token_balance(&result, pool.user_y, RESERVE + USER_LP);
+        shared::expect_token_balance(&result, pool.user_lp, 0);
+        assert_eq!(
+            shared::get_reserves(&result, pool.config),
+            (RESERVE - USER_LP, RESERVE - USER_LP)
+        );
+    }
+}
@@ -0,0 +1,177 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod oracle_tests {
+    use crate::shared::{self};
+    use amm::{AmmError, Observations, OBSERVATIONS_SEED};
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::{AccountSharedData, ReadableAccount, WritableAccount},
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    const CAPACITY: u16 = 4;
+    const LAST_UPDATE: i64 = 100;
+    const NOW: i64 = 110;
+
+    fn process_observe(foreign: bool) -> (InstructionResult, Pubkey, Pubkey) {
+        let (mut mollusk, program_id) = shared::setup();
+        mollusk.sysvars.clock.unix_timestamp = NOW;
+
+        let config = Pubkey::new_unique();
+        let (authority, bump) = Pubkey::find_program_address(&[config.as_ref()], &program_id);
+        let observations = Pubkey::new_unique();
+
+        let mut config_account = shared::create_config(
+            &mollusk,
+            0,
+            authority,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1_000u16,
+            bump,
+            program_id,
+        );
+        config_account.data_as_mut_slice()[214..222].copy_from_slice(&LAST_UPDATE.to_le_bytes());
+        shared::set_reserves(&mut config_account, 1_000, 2_000);
+
+        let space = Observations::space(CAPACITY);
+        let mut observations_account = AccountSharedData::new(
+            mollusk.sysvars.rent.minimum_balance(space),
+            space,
+            &program_id,
+        );
+        let owner = if foreign {
+            Pubkey::new_unique()
+        } else {
+            config
+        };
+        observations_account.data_as_mut_slice()[..32].copy_from_slice(owner.as_ref());
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[9],
+            vec![
+                AccountMeta::new(config, false),
+                AccountMeta::new(observations, false),
+            ],
+        );
+
+        let result: InstructionResult = mollusk.process_instruction(
+            &instruction,
+            &[
+                (config, config_account),
+                (observations, observations_account),
+            ],
+        );
+
+        (result, config, observations)
+    }
+
+    #[test]
+    fn observe() {
+        let (result, config, observations) = process_observe(false);
+
+        assert!(!result.program_result.is_err());
+
+        // 10 seconds at a price of 2 Y per X and 0.5 X per Y
+        let config_data = result.get_account(&config).unwrap().data();
+        assert_eq!(
+            i64::from_le_bytes(config_data[214..222].try_into().unwrap()),
+            NOW
+        );
+        assert_eq!(
+            u128::from_le_bytes(config_data[222..238].try_into().unwrap()),
+            20 << 64
+        );
+        assert_eq!(
+            u128::from_le_bytes(config_data[238..254].try_into().unwrap()),
+            5 << 64
+        );
+
+        let data = result.get_account(&observations).unwrap().data();
+        assert_eq!(u16::from_le_bytes(data[32..34].try_into().unwrap()), 1);
+        assert_eq!(u16::from_le_bytes(data[34..36].try_into().unwrap()), 1);
+        assert_eq!(i64::from_le_bytes(data[36..44].try_into().unwrap()), NOW);
+        assert_eq!(
+            u128::from_le_bytes(data[44..60].try_into().unwrap()),
+            20 << 64
+        );
+        assert_eq!(
+            u128::from_le_bytes(data[60..76].try_into().unwrap()),
+            5 << 64
+        );
+    }
+
+    #[test]
+    fn observe_fails_with_foreign_observations() {
+        let (result, _, _) = process_observe(true);
+
+        shared::expect_error(&result, AmmError::InvalidObservations);
+    }
+
+    #[test]
+    fn initialize_observations() {
+        let (mollusk, program_id) = shared::setup();
+        let (system_program, system_program_account) =
+            mollusk_svm::program::keyed_account_for_system_program();
+
+        let payer = Pubkey::new_unique();
+        let config = Pubkey::new_unique();
+        let (observations, _) =
+            Pubkey::find_program_address(&[OBSERVATIONS_SEED, config.as_ref()], &program_id);
+
+        let config_account = shared::create_config(
+            &mollusk,
+            0,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1_000u16,
+            255,
+            program_id,
+        );
+
+        let data = [vec![8], CAPACITY.to_le_bytes().to_vec()].concat();
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(payer, true),
+                AccountMeta::new_readonly(config, false),
+                AccountMeta::new(observations, false),
+                AccountMeta::new_readonly(system_program, false),
+            ],
+        );
+
+        let result: InstructionResult = mollusk.process_instruction(
+            &instruction,
+            &[
+                (
+                    payer,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (config, config_account),
+                (observations, AccountSharedData::default()),
+                (system_program, system_program_account),
+            ],
+        );
+
+        assert!(!result.program_result.is_err());
+
+        let account = result.get_account(&observations).unwrap();
+        assert_eq!(account.owner(), &program_id);
+        assert_eq!(account.data().len(), Observations::space(CAPACITY));
+        assert_eq!(&account.data()[..32], config.as_ref());
+    }
+}
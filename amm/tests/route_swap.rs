@@ -0,0 +1,214 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod route_swap_tests {
+    use crate::shared::{self};
+    use amm::{AmmError, PAUSE_SWAPS};
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::AccountSharedData,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    const AMOUNT_IN: u64 = 1_000_000;
+    // X -> Y through the first pool
+    const AMOUNT_MID: u64 = 899_100;
+    // Y -> Z through the second pool
+    const AMOUNT_OUT: u64 = 808_463;
+
+    /// Routes X -> Y -> Z, the first pool being guarded by a price feed of
+    /// `oracle` (price, exponent, confidence, publish slot) at slot 1_000
+    fn process_route_swap(
+        min: u64,
+        second_status: u8,
+        oracle: Option<(i64, i32, u64, u64)>,
+    ) -> (InstructionResult, [Pubkey; 6]) {
+        let (mut mollusk, program_id) = shared::setup();
+        mollusk.sysvars.clock.slot = 1_000;
+        let (token_program, token_program_account) =
+            mollusk_svm_programs_token::token::keyed_account();
+
+        let user = Pubkey::new_unique();
+        let mint_x = Pubkey::new_unique();
+        let mint_y = Pubkey::new_unique();
+        let mint_z = Pubkey::new_unique();
+        let user_x = Pubkey::new_unique();
+        let user_z = Pubkey::new_unique();
+
+        let data = [
+            vec![7],
+            AMOUNT_IN.to_le_bytes().to_vec(), // amount
+            min.to_le_bytes().to_vec(),       // min amount
+            i64::MAX.to_le_bytes().to_vec(),  // expiration
+        ]
+        .concat();
+
+        let mut metas = vec![
+            AccountMeta::new(user, true),
+            AccountMeta::new(user_x, false),
+            AccountMeta::new(user_z, false),
+            AccountMeta::new_readonly(mint_x, false),
+            AccountMeta::new_readonly(token_program, false),
+        ];
+        let mut accounts = vec![
+            (
+                user,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                user_x,
+                shared::create_token_account(&mollusk, mint_x, user, AMOUNT_IN, token_program),
+            ),
+            (
+                user_z,
+                shared::create_token_account(&mollusk, mint_z, user, 0, token_program),
+            ),
+            (token_program, token_program_account),
+        ];
+        for mint in [mint_x, mint_y, mint_z] {
+            accounts.push((
+                mint,
+                shared::create_mint_account(&mollusk, user, 0, 6, true, token_program),
+            ));
+        }
+
+        let mut vaults = vec![];
+        for (mint_from, mint_to, status, oracle) in [
+            (mint_x, mint_y, 0, oracle),
+            (mint_y, mint_z, second_status, None),
+        ] {
+            let config = Pubkey::new_unique();
+            let (authority, bump) = Pubkey::find_program_address(&[config.as_ref()], &program_id);
+            let vault_from = Pubkey::new_unique();
+            let vault_to = Pubkey::new_unique();
+
+            let mut config_account = shared::create_config(
+                &mollusk,
+                status,
+                authority,
+                mint_from,
+                mint_to,
+                Pubkey::new_unique(),
+                vault_from,
+                vault_to,
+                1_000u16,
+                bump,
+                program_id,
+            );
+            shared::set_reserves(&mut config_account, 1_000_000_000, 1_000_000_000);
+
+            metas.extend([
+                AccountMeta::new(authority, false),
+                AccountMeta::new(vault_from, false),
+                AccountMeta::new(vault_to, false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(mint_to, false),
+                AccountMeta::new_readonly(token_program, false),
+            ]);
+            accounts.extend([
+                (
+                    authority,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (
+                    vault_from,
+                    shared::create_token_account(
+                        &mollusk,
+                        mint_from,
+                        authority,
+                        1_000_000_000,
+                        token_program,
+                    ),
+                ),
+                (
+                    vault_to,
+                    shared::create_token_account(
+                        &mollusk,
+                        mint_to,
+                        authority,
+                        1_000_000_000,
+                        token_program,
+                    ),
+                ),
+                (config, config_account),
+            ]);
+            if let Some((price, exponent, confidence, publish_slot)) = oracle {
+                let price_feed = Pubkey::new_unique();
+                let config_account = &mut accounts.last_mut().unwrap().1;
+                shared::set_oracle_guard(config_account, price_feed, 50, 100);
+                metas.push(AccountMeta::new_readonly(price_feed, false));
+                accounts.push((
+                    price_feed,
+                    shared::create_price_feed(&mollusk, price, exponent, confidence, publish_slot),
+                ));
+            }
+            vaults.extend([vault_from, vault_to]);
+        }
+
+        let instruction = Instruction::new_with_bytes(program_id, &data, metas);
+
+        let result: InstructionResult = mollusk.process_instruction(&instruction, &accounts);
+
+        (
+            result,
+            [user_x, user_z, vaults[0], vaults[1], vaults[2], vaults[3]],
+        )
+    }
+
+    #[test]
+    fn route_swap() {
+        let (result, [user_x, user_z, vault_a_x, vault_a_y, vault_b_y, vault_b_z]) =
+            process_route_swap(AMOUNT_OUT, 0, None);
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_x, 0);
+        shared::expect_token_balance(&result, user_z, AMOUNT_OUT);
+        shared::expect_token_balance(&result, vault_a_x, 1_000_000_000 + AMOUNT_IN);
+        shared::expect_token_balance(&result, vault_a_y, 1_000_000_000 - AMOUNT_MID);
+        shared::expect_token_balance(&result, vault_b_y, 1_000_000_000 + AMOUNT_MID);
+        shared::expect_token_balance(&result, vault_b_z, 1_000_000_000 - AMOUNT_OUT);
+    }
+
+    #[test]
+    fn route_swap_fails_when_slippage_exceeded() {
+        let (result, _) = process_route_swap(AMOUNT_OUT + 1, 0, None);
+
+        shared::expect_error(&result, AmmError::SlippageExceeded);
+    }
+
+    #[test]
+    fn route_swap_fails_when_a_hop_is_locked() {
+        let (result, _) = process_route_swap(AMOUNT_OUT, PAUSE_SWAPS, None);
+
+        shared::expect_error(&result, AmmError::Locked);
+    }
+
+    #[test]
+    fn route_swap_within_oracle_deviation() {
+        // The price of X moves from 1 to 0.9981 Y, 19 bps from the feed
+        let (result, [_, user_z, ..]) = process_route_swap(AMOUNT_OUT, 0, Some((1, 0, 0, 950)));
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_z, AMOUNT_OUT);
+    }
+
+    #[test]
+    fn route_swap_fails_when_oracle_deviation_exceeded() {
+        // Moves the price away from 1.01 Y, from 99 to 118 bps
+        let (result, _) = process_route_swap(AMOUNT_OUT, 0, Some((1_010, -3, 0, 1_000)));
+
+        shared::expect_error(&result, AmmError::OracleDeviation);
+    }
+
+    #[test]
+    fn route_swap_fails_with_stale_oracle() {
+        let (result, _) = process_route_swap(AMOUNT_OUT, 0, Some((1, 0, 0, 899)));
+
+        shared::expect_error(&result, AmmError::StaleOracle);
+    }
+}
@@ -0,0 +1,79 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod set_allowlist_tests {
+    use crate::shared::{self};
+    use amm::AmmError;
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    fn process_set_allowlist(signer: Option<Pubkey>) -> (InstructionResult, Pubkey) {
+        let (mollusk, program_id) = shared::setup();
+
+        let authority = Pubkey::new_unique();
+        let signer = signer.unwrap_or(authority);
+        let config = Pubkey::new_unique();
+
+        let mut config_account = shared::create_config(
+            &mollusk,
+            0,
+            authority,
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            1_000u16,
+            u8::MAX,
+            program_id,
+        );
+        shared::set_allowlist_root(&mut config_account, [1; 32]);
+
+        let data = [vec![30], [2u8; 32].to_vec()].concat();
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(signer, true),
+                AccountMeta::new(config, false),
+            ],
+        );
+
+        let result: InstructionResult = mollusk.process_instruction(
+            &instruction,
+            &[
+                (
+                    signer,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (config, config_account),
+            ],
+        );
+
+        (result, config)
+    }
+
+    #[test]
+    fn set_allowlist() {
+        let (result, config) = process_set_allowlist(None);
+
+        assert!(!result.program_result.is_err());
+
+        let config_data = result.get_account(&config).unwrap().data();
+        assert_eq!(&config_data[408..440], &[2u8; 32]);
+    }
+
+    #[test]
+    fn set_allowlist_fails_with_invalid_authority() {
+        let (result, _) = process_set_allowlist(Some(Pubkey::new_unique()));
+
+        shared::expect_error(&result, AmmError::InvalidAuthority);
+    }
+}
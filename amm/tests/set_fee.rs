@@ -0,0 +1,165 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod set_fee_tests {
+    use crate::shared::{self};
+    use amm::{AmmError, Registry};
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    // The pool starts at the 5 bps tier, the factory whitelists 5 and 30
+    fn process_set_fee(
+        fee: u16,
+        signer: Option<Pubkey>,
+        foreign_registry: bool,
+    ) -> (InstructionResult, Pubkey, Pubkey, Pubkey) {
+        let (mollusk, program_id) = shared::setup();
+        let (system_program, system_program_account) =
+            mollusk_svm::program::keyed_account_for_system_program();
+
+        let authority = Pubkey::new_unique();
+        let signer = signer.unwrap_or(authority);
+        let config = Pubkey::new_unique();
+        let (mint_x, mint_y) = shared::sorted_mints();
+        let factory = shared::factory_address(&program_id);
+        let registry = shared::registry_address(&program_id, &mint_x, &mint_y, 5);
+        let new_registry = shared::registry_address(&program_id, &mint_x, &mint_y, fee);
+
+        let config_account = shared::create_config(
+            &mollusk,
+            0,
+            authority,
+            mint_x,
+            mint_y,
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            5u16,
+            u8::MAX,
+            program_id,
+        );
+        let mut registry_account = AccountSharedData::new(
+            mollusk.sysvars.rent.minimum_balance(Registry::LEN),
+            Registry::LEN,
+            &program_id,
+        );
+        let registered = if foreign_registry {
+            Pubkey::new_unique()
+        } else {
+            config
+        };
+        registry_account.set_data_from_slice(registered.as_ref());
+
+        let data = [
+            vec![12],
+            fee.to_le_bytes().to_vec(),      // fee
+            2_500u16.to_le_bytes().to_vec(), // protocol fee
+            1_500u16.to_le_bytes().to_vec(), // max referral fee
+            300u16.to_le_bytes().to_vec(),   // max price impact
+        ]
+        .concat();
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(signer, true),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(factory, false),
+                AccountMeta::new(registry, false),
+                AccountMeta::new(new_registry, false),
+                AccountMeta::new_readonly(system_program, false),
+            ],
+        );
+
+        let mut accounts = vec![
+            (
+                signer,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (config, config_account),
+            (
+                factory,
+                shared::create_factory(&mollusk, Pubkey::new_unique(), &[5, 30], program_id),
+            ),
+            (registry, registry_account),
+            (system_program, system_program_account),
+        ];
+        if new_registry != registry {
+            accounts.push((new_registry, AccountSharedData::default()));
+        }
+
+        let result: InstructionResult = mollusk.process_instruction(&instruction, &accounts);
+
+        (result, config, registry, new_registry)
+    }
+
+    #[test]
+    fn set_fee() {
+        let (result, config, registry, new_registry) = process_set_fee(30, None, false);
+
+        assert!(!result.program_result.is_err());
+
+        let config_data = result.get_account(&config).unwrap().data();
+        assert_eq!(&config_data[193..195], &30u16.to_le_bytes());
+        assert_eq!(&config_data[196..198], &2_500u16.to_le_bytes());
+        assert_eq!(&config_data[352..354], &1_500u16.to_le_bytes());
+        assert_eq!(&config_data[354..356], &300u16.to_le_bytes());
+
+        let registry_account = result.get_account(&registry).unwrap();
+        assert_eq!(registry_account.lamports(), 0);
+        assert_eq!(registry_account.data().len(), 0);
+        assert_eq!(
+            result.get_account(&new_registry).unwrap().data(),
+            config.as_ref()
+        );
+    }
+
+    #[test]
+    fn set_fee_keeps_registry_of_same_tier() {
+        let (result, config, registry, _) = process_set_fee(5, None, false);
+
+        assert!(!result.program_result.is_err());
+
+        let config_data = result.get_account(&config).unwrap().data();
+        assert_eq!(&config_data[196..198], &2_500u16.to_le_bytes());
+        assert_eq!(
+            result.get_account(&registry).unwrap().data(),
+            config.as_ref()
+        );
+    }
+
+    #[test]
+    fn set_fee_fails_with_invalid_fee() {
+        let (result, ..) = process_set_fee(1_001, None, false);
+
+        shared::expect_error(&result, AmmError::InvalidFee);
+    }
+
+    #[test]
+    fn set_fee_fails_with_invalid_fee_tier() {
+        let (result, ..) = process_set_fee(50, None, false);
+
+        shared::expect_error(&result, AmmError::InvalidFeeTier);
+    }
+
+    #[test]
+    fn set_fee_fails_with_foreign_registry() {
+        let (result, ..) = process_set_fee(30, None, true);
+
+        shared::expect_error(&result, AmmError::InvalidRegistry);
+    }
+
+    #[test]
+    fn set_fee_fails_with_invalid_authority() {
+        let (result, ..) = process_set_fee(30, Some(Pubkey::new_unique()), false);
+
+        shared::expect_error(&result, AmmError::InvalidAuthority);
+    }
+}
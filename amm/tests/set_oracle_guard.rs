@@ -0,0 +1,97 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod set_oracle_guard_tests {
+    use crate::shared::{self};
+    use amm::AmmError;
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    fn process_set_oracle_guard(
+        signer: Option<Pubkey>,
+        max_deviation: u16,
+    ) -> (InstructionResult, Pubkey, Pubkey) {
+        let (mollusk, program_id) = shared::setup();
+
+        let authority = Pubkey::new_unique();
+        let signer = signer.unwrap_or(authority);
+        let config = Pubkey::new_unique();
+
+        let config_account = shared::create_config(
+            &mollusk,
+            0,
+            authority,
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            1_000u16,
+            u8::MAX,
+            program_id,
+        );
+        let price_feed = Pubkey::new_unique();
+
+        let data = [
+            vec![36],
+            price_feed.to_bytes().to_vec(),
+            max_deviation.to_le_bytes().to_vec(), // max deviation
+            100u64.to_le_bytes().to_vec(),        // max staleness
+        ]
+        .concat();
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(signer, true),
+                AccountMeta::new(config, false),
+            ],
+        );
+
+        let result: InstructionResult = mollusk.process_instruction(
+            &instruction,
+            &[
+                (
+                    signer,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (config, config_account),
+            ],
+        );
+
+        (result, config, price_feed)
+    }
+
+    #[test]
+    fn set_oracle_guard() {
+        let (result, config, price_feed) = process_set_oracle_guard(None, 50);
+
+        assert!(!result.program_result.is_err());
+
+        let config_data = result.get_account(&config).unwrap().data();
+        assert_eq!(&config_data[456..488], price_feed.as_ref());
+        assert_eq!(&config_data[488..490], &50u16.to_le_bytes());
+        assert_eq!(&config_data[490..498], &100u64.to_le_bytes());
+    }
+
+    #[test]
+    fn set_oracle_guard_fails_without_deviation() {
+        let (result, ..) = process_set_oracle_guard(None, 0);
+
+        shared::expect_error(&result, AmmError::InvalidOracle);
+    }
+
+    #[test]
+    fn set_oracle_guard_fails_with_invalid_authority() {
+        let (result, ..) = process_set_oracle_guard(Some(Pubkey::new_unique()), 50);
+
+        shared::expect_error(&result, AmmError::InvalidAuthority);
+    }
+}
@@ -0,0 +1,120 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod set_pause_tests {
+    use crate::shared::{self};
+    use amm::{AmmError, EMERGENCY_MODE, PAUSE_DEPOSITS, PAUSE_SWAPS, PAUSE_WITHDRAWALS};
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::{AccountSharedData, ReadableAccount, WritableAccount},
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    enum Signer {
+        Authority,
+        Guardian,
+        Other,
+    }
+
+    fn process_set_pause(status: u8, signer: Signer) -> (InstructionResult, Pubkey) {
+        let (mollusk, program_id) = shared::setup();
+
+        let authority = Pubkey::new_unique();
+        let pause_guardian = Pubkey::new_unique();
+        let signer = match signer {
+            Signer::Authority => authority,
+            Signer::Guardian => pause_guardian,
+            Signer::Other => Pubkey::new_unique(),
+        };
+        let config = Pubkey::new_unique();
+
+        let mut config_account = shared::create_config(
+            &mollusk,
+            PAUSE_SWAPS,
+            authority,
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            1_000u16,
+            u8::MAX,
+            program_id,
+        );
+        config_account.data_as_mut_slice()[288..320].copy_from_slice(pause_guardian.as_ref());
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[4, status],
+            vec![
+                AccountMeta::new(signer, true),
+                AccountMeta::new(config, false),
+            ],
+        );
+
+        let result: InstructionResult = mollusk.process_instruction(
+            &instruction,
+            &[
+                (
+                    signer,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (config, config_account),
+            ],
+        );
+
+        (result, config)
+    }
+
+    #[test]
+    fn set_pause() {
+        let status = PAUSE_DEPOSITS | PAUSE_WITHDRAWALS;
+        let (result, config) = process_set_pause(status, Signer::Authority);
+
+        assert!(!result.program_result.is_err());
+        assert_eq!(result.get_account(&config).unwrap().data()[0], status);
+    }
+
+    #[test]
+    fn unpause() {
+        let (result, config) = process_set_pause(0, Signer::Authority);
+
+        assert!(!result.program_result.is_err());
+        assert_eq!(result.get_account(&config).unwrap().data()[0], 0);
+    }
+
+    #[test]
+    fn set_pause_by_guardian() {
+        let (result, config) = process_set_pause(EMERGENCY_MODE, Signer::Guardian);
+
+        assert!(!result.program_result.is_err());
+        assert_eq!(
+            result.get_account(&config).unwrap().data()[0],
+            EMERGENCY_MODE
+        );
+    }
+
+    #[test]
+    fn set_pause_fails_when_guardian_pauses_withdrawals() {
+        let (result, _) = process_set_pause(PAUSE_WITHDRAWALS, Signer::Guardian);
+
+        shared::expect_error(&result, AmmError::InvalidAuthority);
+    }
+
+    #[test]
+    fn set_pause_fails_when_pausing_withdrawals_in_emergency_mode() {
+        let (result, _) = process_set_pause(EMERGENCY_MODE | PAUSE_WITHDRAWALS, Signer::Authority);
+
+        shared::expect_error(&result, AmmError::InvalidStatus);
+    }
+
+    #[test]
+    fn set_pause_fails_with_invalid_authority() {
+        let (result, _) = process_set_pause(PAUSE_SWAPS, Signer::Other);
+
+        shared::expect_error(&result, AmmError::InvalidAuthority);
+    }
+}
@@ -1,9 +1,18 @@
+#![allow(dead_code)]
+
 use std::mem;
 
-use amm::Config;
-use mollusk_svm::{result::InstructionResult, Mollusk};
+use amm::{
+    merkle, AmmError, Config, Factory, PriceFeed, CONFIG_SEED, FACTORY_SEED, MINIMUM_LIQUIDITY,
+    MINT_LP_SEED, REGISTRY_SEED, VAULT_SEED,
+};
+use mollusk_svm::{
+    result::{InstructionResult, ProgramResult},
+    Mollusk,
+};
 use solana_sdk::{
     account::{AccountSharedData, ReadableAccount, WritableAccount},
+    program_error::ProgramError,
     program_option::COption,
     program_pack::Pack,
     pubkey::Pubkey,
@@ -19,9 +28,99 @@ pub fn setup() -> (Mollusk, Pubkey) {
     let mut mollusk = Mollusk::new(&program_id, &project_name);
 
     mollusk_svm_programs_token::token::add_program(&mut mollusk);
+    mollusk_svm_programs_token::token2022::add_program(&mut mollusk);
     (mollusk, program_id)
 }
 
+pub struct PoolAddresses {
+    pub config: Pubkey,
+    pub authority: Pubkey,
+    pub authority_bump: u8,
+    pub mint_lp: Pubkey,
+    pub vault_x: Pubkey,
+    pub vault_y: Pubkey,
+}
+
+pub fn pool_addresses(
+    program_id: &Pubkey,
+    seed: u16,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+) -> PoolAddresses {
+    let (config, _) = Pubkey::find_program_address(
+        &[
+            CONFIG_SEED,
+            &seed.to_le_bytes(),
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+        ],
+        program_id,
+    );
+    let (authority, authority_bump) = Pubkey::find_program_address(&[config.as_ref()], program_id);
+    let (mint_lp, _) = Pubkey::find_program_address(&[MINT_LP_SEED, config.as_ref()], program_id);
+    let (vault_x, _) =
+        Pubkey::find_program_address(&[VAULT_SEED, config.as_ref(), mint_x.as_ref()], program_id);
+    let (vault_y, _) =
+        Pubkey::find_program_address(&[VAULT_SEED, config.as_ref(), mint_y.as_ref()], program_id);
+
+    PoolAddresses {
+        config,
+        authority,
+        authority_bump,
+        mint_lp,
+        vault_x,
+        vault_y,
+    }
+}
+
+/// Two mint keys, sorted as Initialize expects them
+pub fn sorted_mints() -> (Pubkey, Pubkey) {
+    let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+pub fn factory_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[FACTORY_SEED], program_id).0
+}
+
+pub fn registry_address(program_id: &Pubkey, mint_x: &Pubkey, mint_y: &Pubkey, fee: u16) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            REGISTRY_SEED,
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            &fee.to_le_bytes(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn create_factory(
+    mollusk: &Mollusk,
+    admin: Pubkey,
+    fee_tiers: &[u16],
+    program_id: Pubkey,
+) -> AccountSharedData {
+    let mut account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(Factory::LEN),
+        Factory::LEN,
+        &program_id,
+    );
+    let mut data = [admin.to_bytes().to_vec(), vec![fee_tiers.len() as u8]].concat();
+    for fee_tier in fee_tiers {
+        data.extend(fee_tier.to_le_bytes());
+    }
+    data.resize(Factory::LEN, 0);
+    account.set_data_from_slice(&data);
+
+    account
+}
+
 pub fn create_mint_account(
     mollusk: &Mollusk,
     authority: Pubkey,
@@ -51,6 +150,75 @@ pub fn create_mint_account(
     account
 }
 
+/// Token-2022 mint with the TransferFeeConfig extension, the same fee for
+/// every epoch
+pub fn create_transfer_fee_mint_account(
+    mollusk: &Mollusk,
+    authority: Pubkey,
+    decimals: u8,
+    basis_points: u16,
+    maximum_fee: u64,
+) -> AccountSharedData {
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::Some(authority),
+        supply: 0,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data[..spl_token::state::Mint::LEN]);
+
+    // Account type, then the TransferFeeConfig TLV entry
+    data.push(1);
+    data.extend(1u16.to_le_bytes());
+    data.extend(108u16.to_le_bytes());
+    data.extend(authority.to_bytes());
+    data.extend(authority.to_bytes());
+    data.extend(0u64.to_le_bytes());
+    for _ in 0..2 {
+        data.extend(0u64.to_le_bytes());
+        data.extend(maximum_fee.to_le_bytes());
+        data.extend(basis_points.to_le_bytes());
+    }
+
+    let mut account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(data.len()),
+        data.len(),
+        &mollusk_svm_programs_token::token2022::ID,
+    );
+    account.set_data_from_slice(&data);
+
+    account
+}
+
+/// Token-2022 account of a transfer fee mint, with the TransferFeeAmount extension
+pub fn create_transfer_fee_token_account(
+    mollusk: &Mollusk,
+    mint: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+) -> AccountSharedData {
+    let mut account = create_token_account(
+        mollusk,
+        mint,
+        owner,
+        amount,
+        mollusk_svm_programs_token::token2022::ID,
+    );
+
+    // Account type, then the TransferFeeAmount TLV entry
+    let mut data = account.data().to_vec();
+    data.push(2);
+    data.extend(2u16.to_le_bytes());
+    data.extend(8u16.to_le_bytes());
+    data.extend(0u64.to_le_bytes());
+    account.set_data_from_slice(&data);
+    account.set_lamports(mollusk.sysvars.rent.minimum_balance(data.len()));
+
+    account
+}
+
 pub fn create_token_account(
     mollusk: &Mollusk,
     mint: Pubkey,
@@ -85,6 +253,40 @@ pub fn create_token_account(
     account
 }
 
+/// Wrapped SOL token account, its lamports holding the amount on top of rent
+pub fn create_native_token_account(
+    mollusk: &Mollusk,
+    owner: Pubkey,
+    amount: u64,
+) -> AccountSharedData {
+    let rent = mollusk
+        .sysvars
+        .rent
+        .minimum_balance(spl_token::state::Account::LEN);
+    let mut account = AccountSharedData::new(
+        rent + amount,
+        spl_token::state::Account::LEN,
+        &mollusk_svm_programs_token::token::ID,
+    );
+
+    spl_token::state::Account::pack(
+        spl_token::state::Account {
+            mint: spl_token::native_mint::ID,
+            owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::Some(rent),
+            delegated_amount: 0,
+            close_authority: COption::None,
+        },
+        account.data_as_mut_slice(),
+    )
+    .unwrap();
+
+    account
+}
+
 pub fn create_config(
     mollusk: &Mollusk,
     status: u8,
@@ -106,33 +308,373 @@ pub fn create_config(
         mem::size_of::<Config>(),
         &program_id,
     );
-    account.set_data_from_slice(
-        &[
-            status.to_le_bytes().to_vec(),
-            authority.to_bytes().to_vec(),
-            mint_x.to_bytes().to_vec(),
-            mint_y.to_bytes().to_vec(),
-            mint_lp.to_bytes().to_vec(),
-            vault_x.to_bytes().to_vec(),
-            vault_y.to_bytes().to_vec(),
-            fee.to_le_bytes().to_vec(),
-            authority_bump.to_le_bytes().to_vec(),
-        ]
-        .concat(),
+    let mut data = [
+        status.to_le_bytes().to_vec(),
+        authority.to_bytes().to_vec(),
+        mint_x.to_bytes().to_vec(),
+        mint_y.to_bytes().to_vec(),
+        mint_lp.to_bytes().to_vec(),
+        vault_x.to_bytes().to_vec(),
+        vault_y.to_bytes().to_vec(),
+        fee.to_le_bytes().to_vec(),
+        authority_bump.to_le_bytes().to_vec(),
+    ]
+    .concat();
+    // Remaining fields start zeroed
+    data.resize(Config::LEN, 0);
+    account.set_data_from_slice(&data);
+
+    account
+}
+
+pub fn set_protocol_fees(
+    account: &mut AccountSharedData,
+    protocol_fee: u16,
+    protocol_fees_x: u64,
+    protocol_fees_y: u64,
+) {
+    let data = account.data_as_mut_slice();
+    data[196..198].copy_from_slice(&protocol_fee.to_le_bytes());
+    data[198..206].copy_from_slice(&protocol_fees_x.to_le_bytes());
+    data[206..214].copy_from_slice(&protocol_fees_y.to_le_bytes());
+}
+
+pub fn set_curve(account: &mut AccountSharedData, curve: u8, amp: u64) {
+    let data = account.data_as_mut_slice();
+    data[263] = curve;
+    data[264..272].copy_from_slice(&amp.to_le_bytes());
+}
+
+pub fn set_reserves(account: &mut AccountSharedData, reserve_x: u64, reserve_y: u64) {
+    let data = account.data_as_mut_slice();
+    data[272..280].copy_from_slice(&reserve_x.to_le_bytes());
+    data[280..288].copy_from_slice(&reserve_y.to_le_bytes());
+}
+
+pub fn set_max_referral_fee(account: &mut AccountSharedData, max_referral_fee: u16) {
+    account.data_as_mut_slice()[352..354].copy_from_slice(&max_referral_fee.to_le_bytes());
+}
+
+pub fn set_max_price_impact(account: &mut AccountSharedData, max_price_impact: u16) {
+    account.data_as_mut_slice()[354..356].copy_from_slice(&max_price_impact.to_le_bytes());
+}
+
+pub fn set_weights(
+    account: &mut AccountSharedData,
+    start_weight: u16,
+    end_weight: u16,
+    start_time: i64,
+    end_time: i64,
+) {
+    let data = account.data_as_mut_slice();
+    data[356..358].copy_from_slice(&start_weight.to_le_bytes());
+    data[358..360].copy_from_slice(&end_weight.to_le_bytes());
+    data[360..368].copy_from_slice(&start_time.to_le_bytes());
+    data[368..376].copy_from_slice(&end_time.to_le_bytes());
+}
+
+pub fn set_twamm(account: &mut AccountSharedData, twamm: Pubkey) {
+    account.data_as_mut_slice()[376..408].copy_from_slice(twamm.as_ref());
+}
+
+pub fn set_allowlist_root(account: &mut AccountSharedData, root: [u8; 32]) {
+    account.data_as_mut_slice()[408..440].copy_from_slice(&root);
+}
+
+pub fn set_batch(account: &mut AccountSharedData, commit_slots: u64, reveal_slots: u64) {
+    let data = account.data_as_mut_slice();
+    data[440..448].copy_from_slice(&commit_slots.to_le_bytes());
+    data[448..456].copy_from_slice(&reveal_slots.to_le_bytes());
+}
+
+pub fn set_batch_round(
+    account: &mut AccountSharedData,
+    index: u64,
+    commit_end: u64,
+    reveal_end: u64,
+) {
+    let data = account.data_as_mut_slice();
+    data[498..506].copy_from_slice(&index.to_le_bytes());
+    data[506..514].copy_from_slice(&commit_end.to_le_bytes());
+    data[514..522].copy_from_slice(&reveal_end.to_le_bytes());
+}
+
+pub fn set_batch_held(account: &mut AccountSharedData, batch_x: u64, batch_y: u64) {
+    let data = account.data_as_mut_slice();
+    data[522..530].copy_from_slice(&batch_x.to_le_bytes());
+    data[530..538].copy_from_slice(&batch_y.to_le_bytes());
+}
+
+pub fn set_oracle_guard(
+    account: &mut AccountSharedData,
+    price_feed: Pubkey,
+    max_deviation: u16,
+    max_staleness: u64,
+) {
+    let data = account.data_as_mut_slice();
+    data[456..488].copy_from_slice(price_feed.as_ref());
+    data[488..490].copy_from_slice(&max_deviation.to_le_bytes());
+    data[490..498].copy_from_slice(&max_staleness.to_le_bytes());
+}
+
+/// Price feed account as an oracle program would publish it, the price of X
+/// in Y being `price` · 10^`exponent` give or take `confidence`
+pub fn create_price_feed(
+    mollusk: &Mollusk,
+    price: i64,
+    exponent: i32,
+    confidence: u64,
+    publish_slot: u64,
+) -> AccountSharedData {
+    let mut account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(PriceFeed::LEN),
+        PriceFeed::LEN,
+        &Pubkey::new_unique(),
     );
+    let data = [
+        price.to_le_bytes().to_vec(),
+        exponent.to_le_bytes().to_vec(),
+        confidence.to_le_bytes().to_vec(),
+        publish_slot.to_le_bytes().to_vec(),
+    ]
+    .concat();
+    account.set_data_from_slice(&data);
 
     account
 }
 
+/// A pool with `reserve` of both mints, at keys that are not derived. The
+/// user holds `reserve` X tokens and no Y or LP token, the LP supply is the
+/// one of a first deposit of `reserve` of both.
+pub struct Pool {
+    pub mollusk: Mollusk,
+    pub program_id: Pubkey,
+    pub update_authority: Pubkey,
+    pub user: Pubkey,
+    pub config: Pubkey,
+    pub authority: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub mint_lp: Pubkey,
+    pub user_x: Pubkey,
+    pub user_y: Pubkey,
+    pub user_lp: Pubkey,
+    pub vault_x: Pubkey,
+    pub vault_y: Pubkey,
+    pub system_program: Pubkey,
+    pub token_program: Pubkey,
+    pub accounts: Vec<(Pubkey, AccountSharedData)>,
+}
+
+impl Pool {
+    /// Account of `key`, which the pool must hold
+    pub fn account(&self, key: &Pubkey) -> &AccountSharedData {
+        &self
+            .accounts
+            .iter()
+            .find(|(account_key, _)| account_key == key)
+            .unwrap()
+            .1
+    }
+
+    /// Account of `key`, which the pool must hold
+    pub fn account_mut(&mut self, key: &Pubkey) -> &mut AccountSharedData {
+        &mut self
+            .accounts
+            .iter_mut()
+            .find(|(account_key, _)| account_key == key)
+            .unwrap()
+            .1
+    }
+
+    /// Replaces the account of `key`, or adds it
+    pub fn set_account(&mut self, key: Pubkey, account: AccountSharedData) {
+        match self
+            .accounts
+            .iter_mut()
+            .find(|(account_key, _)| *account_key == key)
+        {
+            Some(entry) => entry.1 = account,
+            None => self.accounts.push((key, account)),
+        }
+    }
+}
+
+/// Sets up a Pool of `mint_x`, whose vault is a native account when it is
+/// the wrapped SOL mint
+pub fn setup_pool(mint_x: Pubkey, reserve: u64) -> Pool {
+    let (mollusk, program_id) = setup();
+    let (token_program, token_program_account) = mollusk_svm_programs_token::token::keyed_account();
+    let (system_program, system_program_account) =
+        mollusk_svm::program::keyed_account_for_system_program();
+
+    let update_authority = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let config = Pubkey::new_unique();
+    let (authority, bump) = Pubkey::find_program_address(&[config.as_ref()], &program_id);
+    let mint_y = Pubkey::new_unique();
+    let mint_lp = Pubkey::new_unique();
+    let user_x = Pubkey::new_unique();
+    let user_y = Pubkey::new_unique();
+    let user_lp = Pubkey::new_unique();
+    let vault_x = Pubkey::new_unique();
+    let vault_y = Pubkey::new_unique();
+
+    let mut config_account = create_config(
+        &mollusk,
+        0,
+        update_authority,
+        mint_x,
+        mint_y,
+        mint_lp,
+        vault_x,
+        vault_y,
+        1_000u16,
+        bump,
+        program_id,
+    );
+    set_reserves(&mut config_account, reserve, reserve);
+
+    let native = mint_x == spl_token::native_mint::ID;
+    let vault_x_account = if native {
+        create_native_token_account(&mollusk, authority, reserve)
+    } else {
+        create_token_account(&mollusk, mint_x, authority, reserve, token_program)
+    };
+
+    let accounts = vec![
+        (
+            update_authority,
+            AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+        ),
+        (
+            user,
+            AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+        ),
+        (
+            authority,
+            AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+        ),
+        (config, config_account),
+        (
+            mint_x,
+            create_mint_account(
+                &mollusk,
+                user,
+                0,
+                if native { 9 } else { 6 },
+                true,
+                token_program,
+            ),
+        ),
+        (
+            mint_y,
+            create_mint_account(&mollusk, user, 0, 6, true, token_program),
+        ),
+        (
+            mint_lp,
+            create_mint_account(
+                &mollusk,
+                authority,
+                reserve.saturating_sub(MINIMUM_LIQUIDITY),
+                6,
+                true,
+                token_program,
+            ),
+        ),
+        (
+            user_x,
+            create_token_account(&mollusk, mint_x, user, reserve, token_program),
+        ),
+        (
+            user_y,
+            create_token_account(&mollusk, mint_y, user, 0, token_program),
+        ),
+        (
+            user_lp,
+            create_token_account(&mollusk, mint_lp, user, 0, token_program),
+        ),
+        (vault_x, vault_x_account),
+        (
+            vault_y,
+            create_token_account(&mollusk, mint_y, authority, reserve, token_program),
+        ),
+        (system_program, system_program_account),
+        (token_program, token_program_account),
+    ];
+
+    Pool {
+        mollusk,
+        program_id,
+        update_authority,
+        user,
+        config,
+        authority,
+        mint_x,
+        mint_y,
+        mint_lp,
+        user_x,
+        user_y,
+        user_lp,
+        vault_x,
+        vault_y,
+        system_program,
+        token_program,
+        accounts,
+    }
+}
+
+/// Root of an allowlist of `user` and three other keys, and the proof of `user`
+pub fn allowlist(user: Pubkey) -> ([u8; 32], Vec<u8>) {
+    let leaves = [
+        user,
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ]
+    .map(|key| merkle::leaf(&key.to_bytes()));
+    let (left, right) = (
+        merkle::parent(&leaves[0], &leaves[1]),
+        merkle::parent(&leaves[2], &leaves[3]),
+    );
+    (merkle::parent(&left, &right), [leaves[1], right].concat())
+}
+
+pub fn get_reserves(result: &InstructionResult, config: Pubkey) -> (u64, u64) {
+    let data = result.get_account(&config).unwrap().data();
+    (
+        u64::from_le_bytes(data[272..280].try_into().unwrap()),
+        u64::from_le_bytes(data[280..288].try_into().unwrap()),
+    )
+}
+
+pub fn get_protocol_fees(result: &InstructionResult, config: Pubkey) -> (u64, u64) {
+    let data = result.get_account(&config).unwrap().data();
+    (
+        u64::from_le_bytes(data[198..206].try_into().unwrap()),
+        u64::from_le_bytes(data[206..214].try_into().unwrap()),
+    )
+}
+
 #[inline]
 pub fn expect_token_balance(result: &InstructionResult, account: Pubkey, expected_balance: u64) {
     let account_shared_data = result
         .get_account(&account)
         .expect("Failed to find contributor token account");
 
-    let account_data: spl_token::state::Account =
-        solana_sdk::program_pack::Pack::unpack(&account_shared_data.data())
-            .expect("Failed to unpack contributor token account");
+    // Token-2022 extensions follow the base account
+    let account_data = spl_token::state::Account::unpack_from_slice(
+        &account_shared_data.data()[..spl_token::state::Account::LEN],
+    )
+    .expect("Failed to unpack contributor token account");
 
     assert_eq!(account_data.amount, expected_balance);
 }
+
+#[inline]
+pub fn expect_error(result: &InstructionResult, error: AmmError) {
+    assert_eq!(
+        result.program_result,
+        ProgramResult::Failure(ProgramError::Custom(error as u32))
+    );
+}
@@ -4,7 +4,8 @@ mod shared;
 #[cfg(test)]
 mod swap_tests {
     use crate::shared::{self};
-    use mollusk_svm_programs_token::token::keyed_account;
+    use amm::{AmmError, CURVE_CONSTANT_PRODUCT, CURVE_STABLE, CURVE_WEIGHTED, PAUSE_SWAPS};
+    use mollusk_svm::result::InstructionResult;
 
     use solana_sdk::{
         account::AccountSharedData,
@@ -12,11 +13,58 @@ mod swap_tests {
         pubkey::Pubkey,
     };
 
-    #[test]
-    fn swap() {
-        let (mollusk, program_id) = shared::setup();
+    struct SwapArgs {
+        status: u8,
+        min: u64,
+        expiration: i64,
+        wrong_vault: bool,
+        protocol_fee: u16,
+        // Protocol fees already accrued in X and Y, held on top of the reserves
+        protocol_fees: (u64, u64),
+        // Curve and amp, or start weight of X for weighted pools moving to
+        // 20% between timestamps 0 and 1_000
+        curve: (u8, u64),
+        now: i64,
+        // Makes X a Token-2022 mint with a 1% transfer fee
+        transfer_fee: bool,
+        // Referral fee asked by a referrer, the pool allowing up to 20%
+        referral_fee: Option<u16>,
+        max_price_impact: u16,
+        // Allowlist of the pool holding the user, and whether the user
+        // sends its proof
+        allowlist: Option<bool>,
+        // Price, exponent, confidence and publish slot of the price feed
+        // guarding the pool at slot 1_000, allowing 50 bps over 100 slots
+        oracle: Option<(i64, i32, u64, u64)>,
+    }
+
+    impl Default for SwapArgs {
+        fn default() -> Self {
+            Self {
+                status: 0,
+                min: 1_000,
+                expiration: i64::MAX,
+                wrong_vault: false,
+                protocol_fee: 0,
+                protocol_fees: (0, 0),
+                curve: (CURVE_CONSTANT_PRODUCT, 0),
+                now: 0,
+                transfer_fee: false,
+                referral_fee: None,
+                max_price_impact: 0,
+                allowlist: None,
+                oracle: None,
+            }
+        }
+    }
+
+    fn process_swap(args: SwapArgs) -> (InstructionResult, [Pubkey; 6]) {
+        let (mut mollusk, program_id) = shared::setup();
+        mollusk.sysvars.clock.unix_timestamp = args.now;
         let (token_program, token_program_account) =
             mollusk_svm_programs_token::token::keyed_account();
+        let (token_2022_program, token_2022_program_account) =
+            mollusk_svm_programs_token::token2022::keyed_account();
 
         let user = Pubkey::new_unique();
         let config = Pubkey::new_unique();
@@ -27,78 +75,164 @@ mod swap_tests {
         let user_y = Pubkey::new_unique();
         let vault_from = Pubkey::new_unique();
         let vault_to = Pubkey::new_unique();
+        let referrer = Pubkey::new_unique();
+        let price_feed = Pubkey::new_unique();
 
-        let swap_amount = 1_000_000u64;
-
-        let data = [
-            vec![3],                             // amount
-            1_000_000u64.to_le_bytes().to_vec(), // mint_x
-            1_000u64.to_le_bytes().to_vec(),     // expiration
-            i64::MIN.to_le_bytes().to_vec(),
+        let mut data = [
+            vec![3],
+            1_000_000u64.to_le_bytes().to_vec(),    // amount
+            args.min.to_le_bytes().to_vec(),        // min amount
+            args.expiration.to_le_bytes().to_vec(), // expiration
         ]
         .concat();
+        if let Some(referral_fee) = args.referral_fee {
+            data.extend(referral_fee.to_le_bytes()); // referral fee
+        }
+        let (root, proof) = shared::allowlist(user);
+        if args.allowlist == Some(true) {
+            data.extend(proof); // proof
+        }
+
+        let (token_program_x, mint_x_account, user_x_account, vault_from_account) = if args
+            .transfer_fee
+        {
+            (
+                token_2022_program,
+                shared::create_transfer_fee_mint_account(&mollusk, user, 6, 100, u64::MAX),
+                shared::create_transfer_fee_token_account(&mollusk, mint_x, user, 1_000_000_000),
+                shared::create_transfer_fee_token_account(
+                    &mollusk,
+                    mint_x,
+                    authority,
+                    1_000_000_000 + args.protocol_fees.0,
+                ),
+            )
+        } else {
+            (
+                token_program,
+                shared::create_mint_account(&mollusk, user, 0, 6, true, token_program),
+                shared::create_token_account(&mollusk, mint_x, user, 1_000_000_000, token_program),
+                shared::create_token_account(
+                    &mollusk,
+                    mint_x,
+                    authority,
+                    1_000_000_000 + args.protocol_fees.0,
+                    token_program,
+                ),
+            )
+        };
 
-        let user_x_account =
-            shared::create_token_account(&mollusk, mint_x, user, 1_000_000_000, token_program);
+        let mint_y_account = shared::create_mint_account(&mollusk, user, 0, 6, true, token_program);
 
         let user_y_account = shared::create_token_account(&mollusk, mint_y, user, 0, token_program);
 
-        let vault_from_account =
-            shared::create_token_account(&mollusk, mint_x, authority, 1_000_000_000, token_program);
-
-        let vault_to_account =
-            shared::create_token_account(&mollusk, mint_y, authority, 1_000_000_000, token_program);
+        let vault_to_account = shared::create_token_account(
+            &mollusk,
+            mint_y,
+            authority,
+            1_000_000_000 + args.protocol_fees.1,
+            token_program,
+        );
 
-        let config_account = shared::create_config(
+        let mut config_account = shared::create_config(
             &mollusk,
-            0,
+            args.status,
             authority,
             mint_x,
             mint_y,
             Pubkey::new_unique(),
-            vault_from,
+            if args.wrong_vault {
+                Pubkey::new_unique()
+            } else {
+                vault_from
+            },
             vault_to,
             1_000u16,
             bump,
             program_id,
         );
-
-        let instruction = Instruction::new_with_bytes(
-            program_id,
-            &data,
-            vec![
-                AccountMeta::new(user, true),
-                AccountMeta::new(authority, false),
-                /* AccountMeta::new(mint_lp, false), */
-                AccountMeta::new(user_x, false),
-                AccountMeta::new(user_y, false),
-                /* AccountMeta::new(user_lp, false), */
-                AccountMeta::new(vault_from, false),
-                AccountMeta::new(vault_to, false),
-                AccountMeta::new(config, false),
-                AccountMeta::new(token_program, false),
-            ],
+        shared::set_protocol_fees(
+            &mut config_account,
+            args.protocol_fee,
+            args.protocol_fees.0,
+            args.protocol_fees.1,
         );
+        shared::set_curve(&mut config_account, args.curve.0, args.curve.1);
+        if args.curve.0 == CURVE_WEIGHTED {
+            shared::set_weights(&mut config_account, args.curve.1 as u16, 2_000, 0, 1_000);
+        }
+        shared::set_reserves(&mut config_account, 1_000_000_000, 1_000_000_000);
+        shared::set_max_referral_fee(&mut config_account, 2_000);
+        shared::set_max_price_impact(&mut config_account, args.max_price_impact);
+        if args.allowlist.is_some() {
+            shared::set_allowlist_root(&mut config_account, root);
+        }
+        if args.oracle.is_some() {
+            shared::set_oracle_guard(&mut config_account, price_feed, 50, 100);
+        }
 
-        let result: mollusk_svm::result::InstructionResult = mollusk.process_instruction(
-            &instruction,
-            &vec![
-                (
-                    user,
-                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
-                ),
-                (
-                    authority,
-                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
-                ),
-                (user_x, user_x_account),
-                (user_y, user_y_account),
-                (vault_from, vault_from_account),
-                (vault_to, vault_to_account),
-                (config, config_account),
-                (token_program, token_program_account),
-            ],
-        );
+        let mut metas = vec![
+            AccountMeta::new(user, true),
+            AccountMeta::new(authority, false),
+            AccountMeta::new(user_x, false),
+            AccountMeta::new(user_y, false),
+            AccountMeta::new(vault_from, false),
+            AccountMeta::new(vault_to, false),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(mint_x, false),
+            AccountMeta::new_readonly(mint_y, false),
+            AccountMeta::new_readonly(token_program_x, false),
+            AccountMeta::new_readonly(token_program, false),
+        ];
+        let mut accounts = vec![
+            (
+                user,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                authority,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (user_x, user_x_account),
+            (user_y, user_y_account),
+            (vault_from, vault_from_account),
+            (vault_to, vault_to_account),
+            (config, config_account),
+            (mint_x, mint_x_account),
+            (mint_y, mint_y_account),
+            (token_program, token_program_account),
+            (token_2022_program, token_2022_program_account),
+        ];
+        if args.referral_fee.is_some() {
+            metas.push(AccountMeta::new(referrer, false));
+            accounts.push((
+                referrer,
+                shared::create_token_account(&mollusk, mint_y, referrer, 0, token_program),
+            ));
+        }
+        if let Some((price, exponent, confidence, publish_slot)) = args.oracle {
+            mollusk.sysvars.clock.slot = 1_000;
+            metas.push(AccountMeta::new_readonly(price_feed, false));
+            accounts.push((
+                price_feed,
+                shared::create_price_feed(&mollusk, price, exponent, confidence, publish_slot),
+            ));
+        }
+
+        let instruction = Instruction::new_with_bytes(program_id, &data, metas);
+
+        let result: InstructionResult = mollusk.process_instruction(&instruction, &accounts);
+
+        (
+            result,
+            [user_x, user_y, vault_from, vault_to, config, referrer],
+        )
+    }
+
+    #[test]
+    fn swap() {
+        let (result, [user_x, user_y, vault_from, vault_to, config, _]) =
+            process_swap(SwapArgs::default());
 
         assert!(!result.program_result.is_err());
 
@@ -106,5 +240,333 @@ mod swap_tests {
         shared::expect_token_balance(&result, user_y, 899100u64);
         shared::expect_token_balance(&result, vault_from, 1001000000u64);
         shared::expect_token_balance(&result, vault_to, 999100900u64);
+        assert_eq!(
+            shared::get_reserves(&result, config),
+            (1001000000u64, 999100900u64)
+        );
+    }
+
+    #[test]
+    fn swap_fails_when_locked() {
+        let (result, _) = process_swap(SwapArgs {
+            status: PAUSE_SWAPS,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::Locked);
+    }
+
+    #[test]
+    fn swap_fails_when_expired() {
+        let (result, _) = process_swap(SwapArgs {
+            expiration: i64::MIN,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::Expired);
+    }
+
+    #[test]
+    fn swap_fails_when_slippage_exceeded() {
+        let (result, _) = process_swap(SwapArgs {
+            min: 1_000_000,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::SlippageExceeded);
+    }
+
+    #[test]
+    fn swap_fails_with_invalid_vault() {
+        let (result, _) = process_swap(SwapArgs {
+            wrong_vault: true,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::InvalidVault);
+    }
+
+    #[test]
+    fn swap_accrues_protocol_fee() {
+        let (result, [_, user_y, _, vault_to, config, _]) = process_swap(SwapArgs {
+            protocol_fee: 5_000,
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        // 99_901 of Y paid in fees, half of it kept by the protocol
+        shared::expect_token_balance(&result, user_y, 899100u64);
+        shared::expect_token_balance(&result, vault_to, 999100900u64);
+        assert_eq!(shared::get_protocol_fees(&result, config), (0, 49_950));
+        // Only the LP share of the fee stays in the reserves
+        assert_eq!(
+            shared::get_reserves(&result, config),
+            (1001000000u64, 999100900u64 - 49_950)
+        );
+    }
+
+    #[test]
+    fn swap_excludes_protocol_fees_from_reserves() {
+        let (result, [_, user_y, _, vault_to, config, _]) = process_swap(SwapArgs {
+            protocol_fee: 5_000,
+            protocol_fees: (1_000_000_000, 1_000_000_000),
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        // Same quote as an empty-fee pool with 1e9 of each reserve
+        shared::expect_token_balance(&result, user_y, 899100u64);
+        shared::expect_token_balance(&result, vault_to, 1_999_100_900u64);
+        assert_eq!(
+            shared::get_protocol_fees(&result, config),
+            (1_000_000_000, 1_000_049_950)
+        );
+    }
+
+    #[test]
+    fn swap_stable() {
+        let (result, [user_x, user_y, vault_from, vault_to, ..]) = process_swap(SwapArgs {
+            curve: (CURVE_STABLE, 100),
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        // 999_995 out of the invariant for a balanced pool, minus the 10% fee
+        shared::expect_token_balance(&result, user_x, 999000000u64);
+        shared::expect_token_balance(&result, user_y, 899995u64);
+        shared::expect_token_balance(&result, vault_from, 1001000000u64);
+        shared::expect_token_balance(&result, vault_to, 999100005u64);
+    }
+
+    #[test]
+    fn swap_weighted() {
+        let (result, [_, user_y, _, vault_to, config, _]) = process_swap(SwapArgs {
+            curve: (CURVE_WEIGHTED, 8_000),
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        // 3_990_019 out of an 80/20 pool, minus the 10% fee
+        shared::expect_token_balance(&result, user_y, 3591017u64);
+        shared::expect_token_balance(&result, vault_to, 996408983u64);
+        assert_eq!(
+            shared::get_reserves(&result, config),
+            (1001000000u64, 996408983u64)
+        );
+    }
+
+    #[test]
+    fn swap_weighted_follows_schedule() {
+        // Halfway through the schedule the pool is 50/50, quoting as constant product
+        let (result, [_, user_y, ..]) = process_swap(SwapArgs {
+            curve: (CURVE_WEIGHTED, 8_000),
+            now: 500,
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_y, 899100u64);
+
+        // Past its end the schedule holds the end weights
+        let (result, [_, user_y, ..]) = process_swap(SwapArgs {
+            curve: (CURVE_WEIGHTED, 8_000),
+            now: 2_000,
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        // 249_843 out of a 20/80 pool, minus the 10% fee
+        shared::expect_token_balance(&result, user_y, 224858u64);
+    }
+
+    #[test]
+    fn swap_quotes_on_amount_received_with_transfer_fee() {
+        let (result, [user_x, user_y, vault_from, vault_to, config, _]) = process_swap(SwapArgs {
+            transfer_fee: true,
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        // 1% of the 1_000_000 X sent is withheld, the swap is quoted on 990_000
+        shared::expect_token_balance(&result, user_x, 999000000u64);
+        shared::expect_token_balance(&result, vault_from, 1000990000u64);
+        shared::expect_token_balance(&result, user_y, 890118u64);
+        shared::expect_token_balance(&result, vault_to, 999109882u64);
+        assert_eq!(
+            shared::get_reserves(&result, config),
+            (1000990000u64, 999109882u64)
+        );
+    }
+
+    #[test]
+    fn swap_pays_referrer() {
+        let (result, [_, user_y, _, vault_to, config, referrer]) = process_swap(SwapArgs {
+            protocol_fee: 5_000,
+            referral_fee: Some(2_000),
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        // 20% of the 99_901 fee to the referrer, half of the rest to the protocol
+        // and the remaining 39_961 to the LPs
+        shared::expect_token_balance(&result, user_y, 899100u64);
+        shared::expect_token_balance(&result, referrer, 19_980u64);
+        shared::expect_token_balance(&result, vault_to, 999100900u64 - 19_980);
+        assert_eq!(shared::get_protocol_fees(&result, config), (0, 39_960));
+        assert_eq!(
+            shared::get_reserves(&result, config),
+            (1001000000u64, 999100900u64 - 19_980 - 39_960)
+        );
+    }
+
+    #[test]
+    fn swap_fails_when_referral_fee_above_max() {
+        let (result, _) = process_swap(SwapArgs {
+            referral_fee: Some(2_001),
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::InvalidFee);
+    }
+
+    #[test]
+    fn swap_within_max_price_impact() {
+        // The price of X moves from 1 to 1.0019 Y, 19 bps
+        let (result, [_, user_y, ..]) = process_swap(SwapArgs {
+            max_price_impact: 19,
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_y, 899100u64);
+    }
+
+    #[test]
+    fn swap_fails_when_price_impact_exceeded() {
+        let (result, _) = process_swap(SwapArgs {
+            max_price_impact: 18,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::PriceImpactExceeded);
+    }
+
+    #[test]
+    fn swap_allowlisted() {
+        let (result, [_, user_y, ..]) = process_swap(SwapArgs {
+            allowlist: Some(true),
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_y, 899100u64);
+    }
+
+    #[test]
+    fn swap_allowlisted_with_referrer() {
+        // The proof follows the referral fee
+        let (result, [_, user_y, .., referrer]) = process_swap(SwapArgs {
+            referral_fee: Some(2_000),
+            allowlist: Some(true),
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_y, 899100u64);
+        shared::expect_token_balance(&result, referrer, 19_980u64);
+    }
+
+    #[test]
+    fn swap_fails_without_allowlist_proof() {
+        let (result, _) = process_swap(SwapArgs {
+            allowlist: Some(false),
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::NotAllowlisted);
+    }
+
+    #[test]
+    fn swap_within_oracle_deviation() {
+        // The price of X moves from 1 to 0.9981 Y, 19 bps from the feed
+        let (result, [_, user_y, ..]) = process_swap(SwapArgs {
+            oracle: Some((1, 0, 0, 950)),
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_y, 899100u64);
+    }
+
+    #[test]
+    fn swap_towards_oracle_price() {
+        // Still 185 bps away from 0.98 Y, but closer than the 204 bps before
+        let (result, [_, user_y, ..]) = process_swap(SwapArgs {
+            oracle: Some((980, -3, 0, 1_000)),
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_y, 899100u64);
+    }
+
+    #[test]
+    fn swap_with_oracle_guard_and_referrer() {
+        // The price feed is not taken for the referrer
+        let (result, [_, user_y, .., referrer]) = process_swap(SwapArgs {
+            referral_fee: Some(2_000),
+            oracle: Some((1, 0, 0, 1_000)),
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_y, 899100u64);
+        shared::expect_token_balance(&result, referrer, 19_980u64);
+    }
+
+    #[test]
+    fn swap_fails_when_oracle_deviation_exceeded() {
+        // Moves the price away from 1.01 Y, from 99 to 118 bps
+        let (result, _) = process_swap(SwapArgs {
+            oracle: Some((1_010, -3, 0, 1_000)),
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::OracleDeviation);
+    }
+
+    #[test]
+    fn swap_fails_with_stale_oracle() {
+        let (result, _) = process_swap(SwapArgs {
+            oracle: Some((1, 0, 0, 899)),
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::StaleOracle);
+    }
+
+    #[test]
+    fn swap_fails_with_uncertain_oracle() {
+        // A confidence of 60 bps is wider than the deviation allowed
+        let (result, _) = process_swap(SwapArgs {
+            oracle: Some((1_000, -3, 6, 1_000)),
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::InvalidOracle);
     }
 }
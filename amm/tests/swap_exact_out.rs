@@ -0,0 +1,218 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod swap_exact_out_tests {
+    use crate::shared::{self};
+    use amm::AmmError;
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::AccountSharedData,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    const AMOUNT_OUT: u64 = 899_100;
+    // ceil(1e18 / (1e9 - ceil(899_100 / 0.9))) - 1e9
+    const AMOUNT_IN: u64 = 999_999;
+
+    fn process_swap_exact_out(
+        max: u64,
+        y_to_x: bool,
+        max_price_impact: u16,
+        oracle: Option<(i64, i32, u64, u64)>,
+    ) -> (InstructionResult, [Pubkey; 4]) {
+        let (mut mollusk, program_id) = shared::setup();
+        let (token_program, token_program_account) =
+            mollusk_svm_programs_token::token::keyed_account();
+
+        let user = Pubkey::new_unique();
+        let config = Pubkey::new_unique();
+        let (authority, bump) = Pubkey::find_program_address(&[config.as_ref()], &program_id);
+        let mint_x = Pubkey::new_unique();
+        let mint_y = Pubkey::new_unique();
+        let user_x = Pubkey::new_unique();
+        let user_y = Pubkey::new_unique();
+        let vault_x = Pubkey::new_unique();
+        let vault_y = Pubkey::new_unique();
+
+        let data = [
+            vec![5],
+            AMOUNT_OUT.to_le_bytes().to_vec(), // amount out
+            max.to_le_bytes().to_vec(),        // max amount in
+            i64::MAX.to_le_bytes().to_vec(),   // expiration
+        ]
+        .concat();
+
+        let (user_x_amount, user_y_amount) = if y_to_x {
+            (0, 1_000_000_000)
+        } else {
+            (1_000_000_000, 0)
+        };
+
+        let mint_x_account = shared::create_mint_account(&mollusk, user, 0, 6, true, token_program);
+
+        let mint_y_account = shared::create_mint_account(&mollusk, user, 0, 6, true, token_program);
+
+        let user_x_account =
+            shared::create_token_account(&mollusk, mint_x, user, user_x_amount, token_program);
+
+        let user_y_account =
+            shared::create_token_account(&mollusk, mint_y, user, user_y_amount, token_program);
+
+        let vault_x_account =
+            shared::create_token_account(&mollusk, mint_x, authority, 1_000_000_000, token_program);
+
+        let vault_y_account =
+            shared::create_token_account(&mollusk, mint_y, authority, 1_000_000_000, token_program);
+
+        let mut config_account = shared::create_config(
+            &mollusk,
+            0,
+            authority,
+            mint_x,
+            mint_y,
+            Pubkey::new_unique(),
+            vault_x,
+            vault_y,
+            1_000u16,
+            bump,
+            program_id,
+        );
+        shared::set_reserves(&mut config_account, 1_000_000_000, 1_000_000_000);
+        shared::set_max_price_impact(&mut config_account, max_price_impact);
+        let price_feed = Pubkey::new_unique();
+        if oracle.is_some() {
+            shared::set_oracle_guard(&mut config_account, price_feed, 50, 100);
+        }
+
+        let (vault_from, vault_to) = if y_to_x {
+            (vault_y, vault_x)
+        } else {
+            (vault_x, vault_y)
+        };
+
+        let mut metas = vec![
+            AccountMeta::new(user, true),
+            AccountMeta::new(authority, false),
+            AccountMeta::new(user_x, false),
+            AccountMeta::new(user_y, false),
+            AccountMeta::new(vault_from, false),
+            AccountMeta::new(vault_to, false),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(mint_x, false),
+            AccountMeta::new_readonly(mint_y, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(token_program, false),
+        ];
+        let mut accounts = vec![
+            (
+                user,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                authority,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (user_x, user_x_account),
+            (user_y, user_y_account),
+            (vault_x, vault_x_account),
+            (vault_y, vault_y_account),
+            (config, config_account),
+            (mint_x, mint_x_account),
+            (mint_y, mint_y_account),
+            (token_program, token_program_account),
+        ];
+        if let Some((price, exponent, confidence, publish_slot)) = oracle {
+            mollusk.sysvars.clock.slot = 1_000;
+            metas.push(AccountMeta::new_readonly(price_feed, false));
+            accounts.push((
+                price_feed,
+                shared::create_price_feed(&mollusk, price, exponent, confidence, publish_slot),
+            ));
+        }
+
+        let instruction = Instruction::new_with_bytes(program_id, &data, metas);
+
+        let result: InstructionResult = mollusk.process_instruction(&instruction, &accounts);
+
+        (result, [user_x, user_y, vault_x, vault_y])
+    }
+
+    #[test]
+    fn swap_exact_out_x_to_y() {
+        let (result, [user_x, user_y, vault_x, vault_y]) =
+            process_swap_exact_out(AMOUNT_IN, false, 0, None);
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_x, 1_000_000_000 - AMOUNT_IN);
+        shared::expect_token_balance(&result, user_y, AMOUNT_OUT);
+        shared::expect_token_balance(&result, vault_x, 1_000_000_000 + AMOUNT_IN);
+        shared::expect_token_balance(&result, vault_y, 1_000_000_000 - AMOUNT_OUT);
+    }
+
+    #[test]
+    fn swap_exact_out_y_to_x() {
+        let (result, [user_x, user_y, vault_x, vault_y]) =
+            process_swap_exact_out(AMOUNT_IN, true, 0, None);
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_x, AMOUNT_OUT);
+        shared::expect_token_balance(&result, user_y, 1_000_000_000 - AMOUNT_IN);
+        shared::expect_token_balance(&result, vault_x, 1_000_000_000 - AMOUNT_OUT);
+        shared::expect_token_balance(&result, vault_y, 1_000_000_000 + AMOUNT_IN);
+    }
+
+    #[test]
+    fn swap_exact_out_fails_when_slippage_exceeded() {
+        let (result, _) = process_swap_exact_out(AMOUNT_IN - 1, false, 0, None);
+
+        shared::expect_error(&result, AmmError::SlippageExceeded);
+    }
+
+    #[test]
+    fn swap_exact_out_within_max_price_impact() {
+        // The price of X moves from 1 to 1.0019 Y, 19 bps
+        let (result, [_, user_y, ..]) = process_swap_exact_out(AMOUNT_IN, false, 19, None);
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_y, AMOUNT_OUT);
+    }
+
+    #[test]
+    fn swap_exact_out_fails_when_price_impact_exceeded() {
+        let (result, _) = process_swap_exact_out(AMOUNT_IN, false, 18, None);
+
+        shared::expect_error(&result, AmmError::PriceImpactExceeded);
+    }
+
+    #[test]
+    fn swap_exact_out_within_oracle_deviation() {
+        // The price of X moves from 1 to 0.9981 Y, 19 bps from the feed
+        let (result, [_, user_y, ..]) =
+            process_swap_exact_out(AMOUNT_IN, false, 0, Some((1, 0, 0, 950)));
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_y, AMOUNT_OUT);
+    }
+
+    #[test]
+    fn swap_exact_out_fails_when_oracle_deviation_exceeded() {
+        // Moves the price away from 1.01 Y, from 99 to 118 bps
+        let (result, _) = process_swap_exact_out(AMOUNT_IN, false, 0, Some((1_010, -3, 0, 1_000)));
+
+        shared::expect_error(&result, AmmError::OracleDeviation);
+    }
+
+    #[test]
+    fn swap_exact_out_fails_with_stale_oracle() {
+        let (result, _) = process_swap_exact_out(AMOUNT_IN, false, 0, Some((1, 0, 0, 899)));
+
+        shared::expect_error(&result, AmmError::StaleOracle);
+    }
+}
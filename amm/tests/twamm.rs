@@ -0,0 +1,667 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod twamm_tests {
+    use crate::shared::{self, Pool};
+    use amm::{AmmError, Order, Twamm, ORDER_SEED, TWAMM_BUCKETS, TWAMM_INTERVAL, TWAMM_SEED};
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    const RESERVE: u64 = 1_000_000_000;
+    // The order sells 1_000 X per slot from slot 9_000 to slot 27_000
+    const SALE_RATE: u64 = 1_000;
+    const START_SLOT: u64 = 9_000;
+    const END_SLOT: u64 = 27_000;
+
+    /// TWAMM of the pool and order of the user
+    fn twamm_addresses(pool: &Pool) -> (Pubkey, Pubkey) {
+        let (twamm, _) =
+            Pubkey::find_program_address(&[TWAMM_SEED, pool.config.as_ref()], &pool.program_id);
+        let (order, _) = Pubkey::find_program_address(
+            &[ORDER_SEED, twamm.as_ref(), pool.user.as_ref()],
+            &pool.program_id,
+        );
+        (twamm, order)
+    }
+
+    /// Sets up a pool with a 10% fee and even reserves, holding `unsold` X of
+    /// orders on top of the reserves
+    fn setup_pool(with_twamm: bool, unsold: u64) -> Pool {
+        let mut pool = shared::setup_pool(Pubkey::new_unique(), RESERVE);
+        pool.mollusk.sysvars.clock.slot = START_SLOT;
+
+        if with_twamm {
+            let (twamm, _) = twamm_addresses(&pool);
+            let config = pool.config;
+            shared::set_twamm(pool.account_mut(&config), twamm);
+        }
+        let vault_x = shared::create_token_account(
+            &pool.mollusk,
+            pool.mint_x,
+            pool.authority,
+            RESERVE + unsold,
+            pool.token_program,
+        );
+        pool.set_account(pool.vault_x, vault_x);
+
+        pool
+    }
+
+    /// Adds the TWAMM executed up to START_SLOT, selling SALE_RATE until
+    /// END_SLOT when `with_order`
+    fn add_twamm(pool: &mut Pool, with_order: bool) {
+        let (twamm, _) = twamm_addresses(pool);
+        let mut twamm_account = AccountSharedData::new(
+            pool.mollusk.sysvars.rent.minimum_balance(Twamm::LEN),
+            Twamm::LEN,
+            &pool.program_id,
+        );
+        let sale_rate = if with_order { SALE_RATE } else { 0 };
+        let mut data = [
+            pool.config.to_bytes().to_vec(),
+            sale_rate.to_le_bytes().to_vec(),  // sale rate
+            START_SLOT.to_le_bytes().to_vec(), // last slot
+            0u128.to_le_bytes().to_vec(),      // proceeds per sale rate
+        ]
+        .concat();
+        data.resize(Twamm::LEN, 0);
+        if with_order {
+            let bucket = [
+                END_SLOT.to_le_bytes().to_vec(),  // end slot
+                SALE_RATE.to_le_bytes().to_vec(), // sale rate ending
+                0u128.to_le_bytes().to_vec(),     // proceeds per sale rate
+                1u64.to_le_bytes().to_vec(),      // orders
+            ]
+            .concat();
+            let offset = bucket_offset(END_SLOT);
+            data[offset..offset + Twamm::BUCKET_LEN].copy_from_slice(&bucket);
+        }
+        twamm_account.set_data_from_slice(&data);
+        pool.accounts.push((twamm, twamm_account));
+    }
+
+    /// Adds the order of the user, placed at START_SLOT
+    fn add_order(pool: &mut Pool) {
+        let (twamm, order) = twamm_addresses(pool);
+        let mut order_account = AccountSharedData::new(
+            pool.mollusk.sysvars.rent.minimum_balance(Order::LEN),
+            Order::LEN,
+            &pool.program_id,
+        );
+        let data = [
+            twamm.to_bytes().to_vec(),
+            pool.user.to_bytes().to_vec(),
+            SALE_RATE.to_le_bytes().to_vec(), // sale rate
+            END_SLOT.to_le_bytes().to_vec(),  // end slot
+            0u128.to_le_bytes().to_vec(),     // proceeds per sale rate paid
+        ]
+        .concat();
+        order_account.set_data_from_slice(&data);
+        pool.accounts.push((order, order_account));
+    }
+
+    fn setup_order() -> Pool {
+        let mut pool = setup_pool(true, SALE_RATE * (END_SLOT - START_SLOT));
+        add_twamm(&mut pool, true);
+        add_order(&mut pool);
+        pool
+    }
+
+    fn bucket_offset(end_slot: u64) -> usize {
+        let index = (end_slot / TWAMM_INTERVAL) as usize % TWAMM_BUCKETS;
+        Twamm::HEADER_LEN + index * Twamm::BUCKET_LEN
+    }
+
+    fn get_twamm(result: &InstructionResult, twamm: Pubkey) -> (u64, u64, u128) {
+        let data = result.get_account(&twamm).unwrap().data();
+        (
+            u64::from_le_bytes(data[32..40].try_into().unwrap()),
+            u64::from_le_bytes(data[40..48].try_into().unwrap()),
+            u128::from_le_bytes(data[48..64].try_into().unwrap()),
+        )
+    }
+
+    fn get_bucket(
+        result: &InstructionResult,
+        twamm: Pubkey,
+        end_slot: u64,
+    ) -> (u64, u64, u128, u64) {
+        let data = result.get_account(&twamm).unwrap().data();
+        let offset = bucket_offset(end_slot);
+        (
+            u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap()),
+            u64::from_le_bytes(data[offset + 8..offset + 16].try_into().unwrap()),
+            u128::from_le_bytes(data[offset + 16..offset + 32].try_into().unwrap()),
+            u64::from_le_bytes(data[offset + 32..offset + 40].try_into().unwrap()),
+        )
+    }
+
+    #[test]
+    fn initialize_twamm() {
+        let mut pool = setup_pool(false, 0);
+        let (twamm, _) = twamm_addresses(&pool);
+        pool.accounts.push((twamm, AccountSharedData::default()));
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &[26],
+            vec![
+                AccountMeta::new(pool.update_authority, true),
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new(twamm, false),
+                AccountMeta::new_readonly(pool.system_program, false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        let config_data = result.get_account(&pool.config).unwrap().data();
+        assert_eq!(&config_data[376..408], twamm.as_ref());
+        let twamm_data = result.get_account(&twamm).unwrap().data();
+        assert_eq!(twamm_data.len(), Twamm::LEN);
+        assert_eq!(&twamm_data[..32], pool.config.as_ref());
+        assert_eq!(get_twamm(&result, twamm), (0, START_SLOT, 0));
+    }
+
+    #[test]
+    fn place_order() {
+        let mut pool = setup_pool(true, 0);
+        let (twamm, order) = twamm_addresses(&pool);
+        add_twamm(&mut pool, false);
+        pool.mollusk.sysvars.clock.slot = 10_000;
+
+        pool.accounts.push((order, AccountSharedData::default()));
+
+        let data = [
+            vec![27],
+            17_000_500u64.to_le_bytes().to_vec(), // amount
+            15_000u64.to_le_bytes().to_vec(),     // slots
+        ]
+        .concat();
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &data,
+            vec![
+                AccountMeta::new(pool.user, true),
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new(twamm, false),
+                AccountMeta::new(order, false),
+                AccountMeta::new(pool.user_x, false),
+                AccountMeta::new(pool.vault_x, false),
+                AccountMeta::new_readonly(pool.mint_x, false),
+                AccountMeta::new_readonly(pool.system_program, false),
+                AccountMeta::new_readonly(pool.token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        // Slot 25_000 is rounded up to the next interval, 17_000 slots away
+        let order_data = result.get_account(&order).unwrap().data();
+        assert_eq!(order_data.len(), Order::LEN);
+        assert_eq!(&order_data[..32], twamm.as_ref());
+        assert_eq!(&order_data[32..64], pool.user.as_ref());
+        assert_eq!(
+            u64::from_le_bytes(order_data[64..72].try_into().unwrap()),
+            SALE_RATE
+        );
+        assert_eq!(
+            u64::from_le_bytes(order_data[72..80].try_into().unwrap()),
+            END_SLOT
+        );
+
+        assert_eq!(get_twamm(&result, twamm), (SALE_RATE, 10_000, 0));
+        assert_eq!(
+            get_bucket(&result, twamm, END_SLOT),
+            (END_SLOT, SALE_RATE, 0, 1)
+        );
+        // Only the X sold by the rounded down sale rate is transferred
+        shared::expect_token_balance(&result, pool.user_x, RESERVE - 17_000_000);
+        shared::expect_token_balance(&result, pool.vault_x, RESERVE + 17_000_000);
+        // The order is not part of the reserves until sold
+        assert_eq!(
+            shared::get_reserves(&result, pool.config),
+            (RESERVE, RESERVE)
+        );
+    }
+
+    #[test]
+    fn place_order_fails_with_open_order() {
+        let pool = setup_order();
+        let (twamm, order) = twamm_addresses(&pool);
+
+        let data = [
+            vec![27],
+            17_000_000u64.to_le_bytes().to_vec(), // amount
+            15_000u64.to_le_bytes().to_vec(),     // slots
+        ]
+        .concat();
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &data,
+            vec![
+                AccountMeta::new(pool.user, true),
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new(twamm, false),
+                AccountMeta::new(order, false),
+                AccountMeta::new(pool.user_x, false),
+                AccountMeta::new(pool.vault_x, false),
+                AccountMeta::new_readonly(pool.mint_x, false),
+                AccountMeta::new_readonly(pool.system_program, false),
+                AccountMeta::new_readonly(pool.token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        shared::expect_error(&result, AmmError::InvalidOrder);
+    }
+
+    fn process_claim_order(slot: u64) -> (InstructionResult, Pool) {
+        let mut pool = setup_order();
+        let (twamm, order) = twamm_addresses(&pool);
+        pool.mollusk.sysvars.clock.slot = slot;
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &[28],
+            vec![
+                AccountMeta::new(pool.user, true),
+                AccountMeta::new_readonly(pool.authority, false),
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new(twamm, false),
+                AccountMeta::new(order, false),
+                AccountMeta::new(pool.user_y, false),
+                AccountMeta::new(pool.vault_y, false),
+                AccountMeta::new_readonly(pool.mint_y, false),
+                AccountMeta::new_readonly(pool.token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        (result, pool)
+    }
+
+    #[test]
+    fn claim_order() {
+        let (result, pool) = process_claim_order(18_000);
+        let (twamm, order) = twamm_addresses(&pool);
+
+        assert!(!result.program_result.is_err());
+
+        // 9_000_000 X sold at once into even reserves with a 10% fee
+        shared::expect_token_balance(&result, pool.user_y, 8_027_750);
+        shared::expect_token_balance(&result, pool.vault_y, RESERVE - 8_027_750);
+        assert_eq!(
+            shared::get_reserves(&result, pool.config),
+            (1_009_000_000, 991_972_250)
+        );
+        assert_eq!(
+            get_twamm(&result, twamm),
+            (SALE_RATE, 18_000, 8_027_750_000_000_000)
+        );
+
+        let order_data = result.get_account(&order).unwrap().data();
+        assert_eq!(
+            u128::from_le_bytes(order_data[80..96].try_into().unwrap()),
+            8_027_750_000_000_000
+        );
+    }
+
+    #[test]
+    fn claim_order_after_expiry() {
+        let (result, pool) = process_claim_order(30_000);
+        let (twamm, order) = twamm_addresses(&pool);
+
+        assert!(!result.program_result.is_err());
+
+        // Two intervals of 9_000_000 X, the second at the moved price
+        shared::expect_token_balance(&result, pool.user_y, 15_920_653);
+        assert_eq!(
+            shared::get_reserves(&result, pool.config),
+            (1_018_000_000, 984_079_347)
+        );
+        assert_eq!(
+            get_twamm(&result, twamm),
+            (0, 30_000, 15_920_653_000_000_000)
+        );
+        assert_eq!(
+            get_bucket(&result, twamm, END_SLOT),
+            (END_SLOT, SALE_RATE, 15_920_653_000_000_000, 0)
+        );
+
+        let order_account = result.get_account(&order).unwrap();
+        assert_eq!(order_account.lamports(), 0);
+        assert_eq!(order_account.data().len(), 0);
+    }
+
+    #[test]
+    fn claim_order_fails_with_invalid_owner() {
+        let mut pool = setup_order();
+        let (twamm, order) = twamm_addresses(&pool);
+        pool.user = Pubkey::new_unique();
+        pool.accounts.push((
+            pool.user,
+            AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+        ));
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &[28],
+            vec![
+                AccountMeta::new(pool.user, true),
+                AccountMeta::new_readonly(pool.authority, false),
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new(twamm, false),
+                AccountMeta::new(order, false),
+                AccountMeta::new(pool.user_y, false),
+                AccountMeta::new(pool.vault_y, false),
+                AccountMeta::new_readonly(pool.mint_y, false),
+                AccountMeta::new_readonly(pool.token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        shared::expect_error(&result, AmmError::InvalidOrder);
+    }
+
+    #[test]
+    fn cancel_order() {
+        let mut pool = setup_order();
+        let (twamm, order) = twamm_addresses(&pool);
+        pool.mollusk.sysvars.clock.slot = 18_000;
+
+        let instruction = Instruction::new_with_bytes(
+            pool.program_id,
+            &[29],
+            vec![
+                AccountMeta::new(pool.user, true),
+                AccountMeta::new_readonly(pool.authority, false),
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new(twamm, false),
+                AccountMeta::new(order, false),
+                AccountMeta::new(pool.user_x, false),
+                AccountMeta::new(pool.user_y, false),
+                AccountMeta::new(pool.vault_x, false),
+                AccountMeta::new(pool.vault_y, false),
+                AccountMeta::new_readonly(pool.mint_x, false),
+                AccountMeta::new_readonly(pool.mint_y, false),
+                AccountMeta::new_readonly(pool.token_program, false),
+                AccountMeta::new_readonly(pool.token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+
+        // Half sold, the other half returned
+        shared::expect_token_balance(&result, pool.user_y, 8_027_750);
+        shared::expect_token_balance(&result, pool.user_x, RESERVE + 9_000_000);
+        shared::expect_token_balance(&result, pool.vault_x, 1_009_000_000);
+        assert_eq!(
+            shared::get_reserves(&result, pool.config),
+            (1_009_000_000, 991_972_250)
+        );
+        assert_eq!(get_twamm(&result, twamm).0, 0);
+        assert_eq!(get_bucket(&result, twamm, END_SLOT), (END_SLOT, 0, 0, 0));
+        assert_eq!(result.get_account(&order).unwrap().lamports(), 0);
+    }
+
+    fn process_swap(with_twamm: bool) -> (InstructionResult, Pool) {
+        let mut pool = setup_order();
+        let (twamm, _) = twamm_addresses(&pool);
+        pool.mollusk.sysvars.clock.slot = 18_000;
+
+        let data = [
+            vec![3],
+            1_000_000u64.to_le_bytes().to_vec(), // amount
+            1u64.to_le_bytes().to_vec(),         // min amount
+            i64::MAX.to_le_bytes().to_vec(),     // expiration
+        ]
+        .concat();
+
+        let mut accounts = vec![
+            AccountMeta::new(pool.user, true),
+            AccountMeta::new(pool.authority, false),
+            AccountMeta::new(pool.user_x, false),
+            AccountMeta::new(pool.user_y, false),
+            AccountMeta::new(pool.vault_x, false),
+            AccountMeta::new(pool.vault_y, false),
+            AccountMeta::new(pool.config, false),
+            AccountMeta::new_readonly(pool.mint_x, false),
+            AccountMeta::new_readonly(pool.mint_y, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+        ];
+        if with_twamm {
+            accounts.push(AccountMeta::new(twamm, false));
+        }
+        let instruction = Instruction::new_with_bytes(pool.program_id, &data, accounts);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        (result, pool)
+    }
+
+    #[test]
+    fn swap_executes_orders_first() {
+        let (result, pool) = process_swap(true);
+        let (twamm, _) = twamm_addresses(&pool);
+
+        assert!(!result.program_result.is_err());
+
+        // Quoted against the reserves left by the order
+        shared::expect_token_balance(&result, pool.user_y, 883_935);
+        assert_eq!(
+            shared::get_reserves(&result, pool.config),
+            (1_010_000_000, 991_088_315)
+        );
+        assert_eq!(get_twamm(&result, twamm).1, 18_000);
+    }
+
+    #[test]
+    fn swap_fails_without_twamm() {
+        let (result, _) = process_swap(false);
+
+        shared::expect_error(&result, AmmError::InvalidTwamm);
+    }
+
+    /// Processes `data` with `accounts` on the order `pool` at slot 18_000, the
+    /// TWAMM appended, the user holding 1_000_000 LP
+    fn process_with_twamm(
+        pool: &mut Pool,
+        data: Vec<u8>,
+        mut accounts: Vec<AccountMeta>,
+    ) -> InstructionResult {
+        let (twamm, _) = twamm_addresses(pool);
+        pool.mollusk.sysvars.clock.slot = 18_000;
+        let user_lp = shared::create_token_account(
+            &pool.mollusk,
+            pool.mint_lp,
+            pool.user,
+            1_000_000,
+            pool.token_program,
+        );
+        pool.set_account(pool.user_lp, user_lp);
+
+        accounts.push(AccountMeta::new(twamm, false));
+        let instruction = Instruction::new_with_bytes(pool.program_id, &data, accounts);
+
+        let result: InstructionResult = pool
+            .mollusk
+            .process_instruction(&instruction, &pool.accounts);
+
+        assert!(!result.program_result.is_err());
+        assert_eq!(get_twamm(&result, twamm).1, 18_000);
+        result
+    }
+
+    #[test]
+    fn swap_exact_out_executes_orders_first() {
+        let mut pool = setup_order();
+
+        let data = [
+            vec![5],
+            1_000u64.to_le_bytes().to_vec(), // amount
+            u64::MAX.to_le_bytes().to_vec(), // max amount
+            i64::MAX.to_le_bytes().to_vec(), // expiration
+        ]
+        .concat();
+        let accounts = vec![
+            AccountMeta::new(pool.user, true),
+            AccountMeta::new(pool.authority, false),
+            AccountMeta::new(pool.user_x, false),
+            AccountMeta::new(pool.user_y, false),
+            AccountMeta::new(pool.vault_x, false),
+            AccountMeta::new(pool.vault_y, false),
+            AccountMeta::new(pool.config, false),
+            AccountMeta::new_readonly(pool.mint_x, false),
+            AccountMeta::new_readonly(pool.mint_y, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+        ];
+
+        let result = process_with_twamm(&mut pool, data, accounts);
+        shared::expect_token_balance(&result, pool.user_y, 1_000);
+    }
+
+    #[test]
+    fn route_swap_executes_orders_first() {
+        let mut pool = setup_order();
+
+        let data = [
+            vec![7],
+            1_000_000u64.to_le_bytes().to_vec(), // amount
+            1u64.to_le_bytes().to_vec(),         // min amount
+            i64::MAX.to_le_bytes().to_vec(),     // expiration
+        ]
+        .concat();
+        let accounts = vec![
+            AccountMeta::new(pool.user, true),
+            AccountMeta::new(pool.user_x, false),
+            AccountMeta::new(pool.user_y, false),
+            AccountMeta::new_readonly(pool.mint_x, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+            AccountMeta::new(pool.authority, false),
+            AccountMeta::new(pool.vault_x, false),
+            AccountMeta::new(pool.vault_y, false),
+            AccountMeta::new(pool.config, false),
+            AccountMeta::new_readonly(pool.mint_y, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+        ];
+
+        let result = process_with_twamm(&mut pool, data, accounts);
+        // Quoted against the reserves left by the order, as in Swap
+        shared::expect_token_balance(&result, pool.user_y, 883_935);
+    }
+
+    #[test]
+    fn withdraw_executes_orders_first() {
+        let mut pool = setup_order();
+
+        let data = [
+            vec![2],
+            1_000u64.to_le_bytes().to_vec(), // amount
+            0u64.to_le_bytes().to_vec(),     // min x
+            0u64.to_le_bytes().to_vec(),     // min y
+            i64::MAX.to_le_bytes().to_vec(), // expiration
+        ]
+        .concat();
+        let accounts = vec![
+            AccountMeta::new(pool.user, true),
+            AccountMeta::new(pool.authority, false),
+            AccountMeta::new(pool.mint_lp, false),
+            AccountMeta::new(pool.user_x, false),
+            AccountMeta::new(pool.user_y, false),
+            AccountMeta::new(pool.user_lp, false),
+            AccountMeta::new(pool.vault_x, false),
+            AccountMeta::new(pool.vault_y, false),
+            AccountMeta::new(pool.config, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+            AccountMeta::new_readonly(pool.mint_x, false),
+            AccountMeta::new_readonly(pool.mint_y, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+        ];
+
+        process_with_twamm(&mut pool, data, accounts);
+    }
+
+    /// Accounts of DepositSingle and WithdrawSingle in X
+    fn single_accounts(pool: &Pool) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(pool.user, true),
+            AccountMeta::new(pool.authority, false),
+            AccountMeta::new(pool.mint_lp, false),
+            AccountMeta::new(pool.user_x, false),
+            AccountMeta::new(pool.user_lp, false),
+            AccountMeta::new(pool.vault_x, false),
+            AccountMeta::new(pool.vault_y, false),
+            AccountMeta::new(pool.config, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+            AccountMeta::new_readonly(pool.mint_x, false),
+            AccountMeta::new_readonly(pool.token_program, false),
+        ]
+    }
+
+    #[test]
+    fn deposit_single_executes_orders_first() {
+        let mut pool = setup_order();
+
+        let data = [
+            vec![16],
+            1_000_000u64.to_le_bytes().to_vec(), // amount
+            1u64.to_le_bytes().to_vec(),         // min lp
+            i64::MAX.to_le_bytes().to_vec(),     // expiration
+        ]
+        .concat();
+
+        let accounts = single_accounts(&pool);
+        process_with_twamm(&mut pool, data, accounts);
+    }
+
+    #[test]
+    fn withdraw_single_executes_orders_first() {
+        let mut pool = setup_order();
+
+        let data = [
+            vec![17],
+            1_000u64.to_le_bytes().to_vec(), // amount
+            1u64.to_le_bytes().to_vec(),     // min amount
+            i64::MAX.to_le_bytes().to_vec(), // expiration
+        ]
+        .concat();
+
+        let accounts = single_accounts(&pool);
+        process_with_twamm(&mut pool, data, accounts);
+    }
+}
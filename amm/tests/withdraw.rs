@@ -0,0 +1,248 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod withdraw_tests {
+    use crate::shared::{self};
+    use amm::{
+        AmmError, EMERGENCY_MODE, MINIMUM_LIQUIDITY, PAUSE_DEPOSITS, PAUSE_SWAPS, PAUSE_WITHDRAWALS,
+    };
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::AccountSharedData,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    const POOL_AMOUNT: u64 = 1_000_000_000;
+    const WITHDRAW_AMOUNT: u64 = 250_000_000;
+
+    struct WithdrawArgs {
+        status: u8,
+        min_x: u64,
+        expiration: i64,
+        wrong_mint: bool,
+        wrong_vault: bool,
+    }
+
+    impl Default for WithdrawArgs {
+        fn default() -> Self {
+            Self {
+                status: 0,
+                min_x: WITHDRAW_AMOUNT,
+                expiration: i64::MAX,
+                wrong_mint: false,
+                wrong_vault: false,
+            }
+        }
+    }
+
+    fn process_withdraw(args: WithdrawArgs) -> (InstructionResult, [Pubkey; 4]) {
+        let (mollusk, program_id) = shared::setup();
+        let (token_program, token_program_account) =
+            mollusk_svm_programs_token::token::keyed_account();
+
+        let user = Pubkey::new_unique();
+        let config = Pubkey::new_unique();
+        let (authority, bump) = Pubkey::find_program_address(&[config.as_ref()], &program_id);
+        let mint_lp = Pubkey::new_unique();
+        let mint_x = Pubkey::new_unique();
+        let mint_y = Pubkey::new_unique();
+        let user_x = Pubkey::new_unique();
+        let user_y = Pubkey::new_unique();
+        let user_lp = Pubkey::new_unique();
+        let vault_x = Pubkey::new_unique();
+        let vault_y = Pubkey::new_unique();
+
+        let data = [
+            vec![2],
+            WITHDRAW_AMOUNT.to_le_bytes().to_vec(), // amount
+            args.min_x.to_le_bytes().to_vec(),      // min_x
+            WITHDRAW_AMOUNT.to_le_bytes().to_vec(), // min_y
+            args.expiration.to_le_bytes().to_vec(), // expiration
+        ]
+        .concat();
+
+        // The first deposit locked MINIMUM_LIQUIDITY of the supply
+        let mint_lp_account = shared::create_mint_account(
+            &mollusk,
+            authority,
+            POOL_AMOUNT - MINIMUM_LIQUIDITY,
+            6,
+            true,
+            token_program,
+        );
+
+        let mint_x_account = shared::create_mint_account(&mollusk, user, 0, 6, true, token_program);
+
+        let mint_y_account = shared::create_mint_account(&mollusk, user, 0, 6, true, token_program);
+
+        let user_x_account = shared::create_token_account(&mollusk, mint_x, user, 0, token_program);
+
+        let user_y_account = shared::create_token_account(&mollusk, mint_y, user, 0, token_program);
+
+        let vault_x_account =
+            shared::create_token_account(&mollusk, mint_x, authority, POOL_AMOUNT, token_program);
+
+        let vault_y_account =
+            shared::create_token_account(&mollusk, mint_y, authority, POOL_AMOUNT, token_program);
+
+        let user_lp_account = shared::create_token_account(
+            &mollusk,
+            mint_lp,
+            user,
+            POOL_AMOUNT - MINIMUM_LIQUIDITY,
+            token_program,
+        );
+
+        let mut config_account = shared::create_config(
+            &mollusk,
+            args.status,
+            authority,
+            mint_x,
+            mint_y,
+            if args.wrong_mint {
+                Pubkey::new_unique()
+            } else {
+                mint_lp
+            },
+            vault_x,
+            if args.wrong_vault {
+                Pubkey::new_unique()
+            } else {
+                vault_y
+            },
+            1_000u16,
+            bump,
+            program_id,
+        );
+        shared::set_reserves(&mut config_account, POOL_AMOUNT, POOL_AMOUNT);
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(user, true),
+                AccountMeta::new(authority, false),
+                AccountMeta::new(mint_lp, false),
+                AccountMeta::new(user_x, false),
+                AccountMeta::new(user_y, false),
+                AccountMeta::new(user_lp, false),
+                AccountMeta::new(vault_x, false),
+                AccountMeta::new(vault_y, false),
+                AccountMeta::new(config, false),
+                AccountMeta::new(token_program, false),
+                AccountMeta::new_readonly(mint_x, false),
+                AccountMeta::new_readonly(mint_y, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(token_program, false),
+            ],
+        );
+
+        let result: InstructionResult = mollusk.process_instruction(
+            &instruction,
+            &[
+                (
+                    user,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (
+                    authority,
+                    AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+                ),
+                (mint_lp, mint_lp_account),
+                (user_x, user_x_account),
+                (user_y, user_y_account),
+                (user_lp, user_lp_account),
+                (vault_x, vault_x_account),
+                (vault_y, vault_y_account),
+                (config, config_account),
+                (token_program, token_program_account),
+                (mint_x, mint_x_account),
+                (mint_y, mint_y_account),
+            ],
+        );
+
+        (result, [user_x, user_lp, vault_x, vault_y])
+    }
+
+    #[test]
+    fn withdraw() {
+        let (result, [user_x, user_lp, vault_x, vault_y]) =
+            process_withdraw(WithdrawArgs::default());
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_x, WITHDRAW_AMOUNT);
+        shared::expect_token_balance(&result, vault_x, POOL_AMOUNT - WITHDRAW_AMOUNT);
+        shared::expect_token_balance(&result, vault_y, POOL_AMOUNT - WITHDRAW_AMOUNT);
+        shared::expect_token_balance(
+            &result,
+            user_lp,
+            POOL_AMOUNT - MINIMUM_LIQUIDITY - WITHDRAW_AMOUNT,
+        );
+    }
+
+    #[test]
+    fn withdraw_fails_when_locked() {
+        let (result, _) = process_withdraw(WithdrawArgs {
+            status: PAUSE_WITHDRAWALS,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::Locked);
+    }
+
+    #[test]
+    fn withdraw_in_emergency_mode() {
+        let (result, [user_x, ..]) = process_withdraw(WithdrawArgs {
+            status: EMERGENCY_MODE | PAUSE_SWAPS | PAUSE_DEPOSITS,
+            ..Default::default()
+        });
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_x, WITHDRAW_AMOUNT);
+    }
+
+    #[test]
+    fn withdraw_fails_when_expired() {
+        let (result, _) = process_withdraw(WithdrawArgs {
+            expiration: i64::MIN,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::Expired);
+    }
+
+    #[test]
+    fn withdraw_fails_with_invalid_mint() {
+        let (result, _) = process_withdraw(WithdrawArgs {
+            wrong_mint: true,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::InvalidMint);
+    }
+
+    #[test]
+    fn withdraw_fails_with_invalid_vault() {
+        let (result, _) = process_withdraw(WithdrawArgs {
+            wrong_vault: true,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::InvalidVault);
+    }
+
+    #[test]
+    fn withdraw_fails_when_slippage_exceeded() {
+        let (result, _) = process_withdraw(WithdrawArgs {
+            min_x: WITHDRAW_AMOUNT + 1,
+            ..Default::default()
+        });
+
+        shared::expect_error(&result, AmmError::SlippageExceeded);
+    }
+}
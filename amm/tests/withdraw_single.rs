@@ -0,0 +1,182 @@
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(test)]
+mod withdraw_single_tests {
+    use crate::shared::{self};
+    use amm::{AmmError, EMERGENCY_MODE, MINIMUM_LIQUIDITY};
+    use mollusk_svm::result::InstructionResult;
+
+    use solana_sdk::{
+        account::AccountSharedData,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    };
+
+    const POOL: u64 = 1_000_000_000;
+    const AMOUNT: u64 = 10_000_000;
+    // 10_000_000 of X withdrawn, plus 10_000_000 of Y swapped for 8_910_000 of X
+    const AMOUNT_OUT: u64 = 18_910_000;
+
+    fn process_withdraw_single(
+        status: u8,
+        min: u64,
+        oracle: Option<(i64, i32, u64, u64)>,
+    ) -> (InstructionResult, [Pubkey; 5]) {
+        let (mut mollusk, program_id) = shared::setup();
+        let (token_program, token_program_account) =
+            mollusk_svm_programs_token::token::keyed_account();
+
+        let user = Pubkey::new_unique();
+        let config = Pubkey::new_unique();
+        let (authority, bump) = Pubkey::find_program_address(&[config.as_ref()], &program_id);
+        let mint_lp = Pubkey::new_unique();
+        let mint_x = Pubkey::new_unique();
+        let mint_y = Pubkey::new_unique();
+        let user_x = Pubkey::new_unique();
+        let user_lp = Pubkey::new_unique();
+        let vault_x = Pubkey::new_unique();
+        let vault_y = Pubkey::new_unique();
+
+        let data = [
+            vec![17],
+            AMOUNT.to_le_bytes().to_vec(),   // amount
+            min.to_le_bytes().to_vec(),      // min amount
+            i64::MAX.to_le_bytes().to_vec(), // expiration
+        ]
+        .concat();
+
+        let mut config_account = shared::create_config(
+            &mollusk, status, authority, mint_x, mint_y, mint_lp, vault_x, vault_y, 1_000u16, bump,
+            program_id,
+        );
+        shared::set_reserves(&mut config_account, POOL, POOL);
+        let price_feed = Pubkey::new_unique();
+        if oracle.is_some() {
+            shared::set_oracle_guard(&mut config_account, price_feed, 50, 100);
+        }
+
+        let mut metas = vec![
+            AccountMeta::new(user, true),
+            AccountMeta::new(authority, false),
+            AccountMeta::new(mint_lp, false),
+            AccountMeta::new(user_x, false),
+            AccountMeta::new(user_lp, false),
+            AccountMeta::new(vault_x, false),
+            AccountMeta::new(vault_y, false),
+            AccountMeta::new(config, false),
+            AccountMeta::new(token_program, false),
+            AccountMeta::new_readonly(mint_x, false),
+            AccountMeta::new_readonly(token_program, false),
+        ];
+        let mut accounts = vec![
+            (
+                user,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                authority,
+                AccountSharedData::new(1_000_000_000u64, 0, &Pubkey::default()),
+            ),
+            (
+                mint_lp,
+                shared::create_mint_account(
+                    &mollusk,
+                    authority,
+                    POOL - MINIMUM_LIQUIDITY,
+                    6,
+                    true,
+                    token_program,
+                ),
+            ),
+            (
+                user_x,
+                shared::create_token_account(&mollusk, mint_x, user, 0, token_program),
+            ),
+            (
+                user_lp,
+                shared::create_token_account(&mollusk, mint_lp, user, AMOUNT, token_program),
+            ),
+            (
+                vault_x,
+                shared::create_token_account(&mollusk, mint_x, authority, POOL, token_program),
+            ),
+            (
+                vault_y,
+                shared::create_token_account(&mollusk, mint_y, authority, POOL, token_program),
+            ),
+            (config, config_account),
+            (token_program, token_program_account),
+            (
+                mint_x,
+                shared::create_mint_account(&mollusk, user, 0, 6, true, token_program),
+            ),
+        ];
+        if let Some((price, exponent, confidence, publish_slot)) = oracle {
+            mollusk.sysvars.clock.slot = 1_000;
+            metas.push(AccountMeta::new_readonly(price_feed, false));
+            accounts.push((
+                price_feed,
+                shared::create_price_feed(&mollusk, price, exponent, confidence, publish_slot),
+            ));
+        }
+
+        let instruction = Instruction::new_with_bytes(program_id, &data, metas);
+
+        let result: InstructionResult = mollusk.process_instruction(&instruction, &accounts);
+
+        (result, [user_x, user_lp, vault_x, vault_y, config])
+    }
+
+    #[test]
+    fn withdraw_single() {
+        let (result, [user_x, user_lp, vault_x, vault_y, config]) =
+            process_withdraw_single(0, AMOUNT_OUT, None);
+
+        assert!(!result.program_result.is_err());
+
+        // The Y share never leaves its vault
+        shared::expect_token_balance(&result, user_x, AMOUNT_OUT);
+        shared::expect_token_balance(&result, user_lp, 0);
+        shared::expect_token_balance(&result, vault_x, POOL - AMOUNT_OUT);
+        shared::expect_token_balance(&result, vault_y, POOL);
+        assert_eq!(
+            shared::get_reserves(&result, config),
+            (POOL - AMOUNT_OUT, POOL)
+        );
+    }
+
+    #[test]
+    fn withdraw_single_fails_when_slippage_exceeded() {
+        let (result, _) = process_withdraw_single(0, AMOUNT_OUT + 1, None);
+
+        shared::expect_error(&result, AmmError::SlippageExceeded);
+    }
+
+    #[test]
+    fn withdraw_single_fails_in_emergency_mode() {
+        // Only proportional withdrawals are allowed, they do not move the price
+        let (result, _) = process_withdraw_single(EMERGENCY_MODE, AMOUNT_OUT, None);
+
+        shared::expect_error(&result, AmmError::Locked);
+    }
+
+    #[test]
+    fn withdraw_single_within_oracle_deviation() {
+        // The swap moves the price of X from 1 to 1.0193 Y, 7 bps from the feed
+        let (result, [user_x, ..]) =
+            process_withdraw_single(0, AMOUNT_OUT, Some((1_020, -3, 0, 1_000)));
+
+        assert!(!result.program_result.is_err());
+
+        shared::expect_token_balance(&result, user_x, AMOUNT_OUT);
+    }
+
+    #[test]
+    fn withdraw_single_fails_when_oracle_deviation_exceeded() {
+        // Moves the price away from 1 Y, from 0 to 193 bps
+        let (result, _) = process_withdraw_single(0, AMOUNT_OUT, Some((1, 0, 0, 1_000)));
+
+        shared::expect_error(&result, AmmError::OracleDeviation);
+    }
+}
@@ -1,3 +1,6 @@
+pub mod stable;
+pub mod weighted;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum CurveError {
     Overflow,
@@ -8,9 +11,9 @@ pub enum CurveError {
 pub fn k_from_xy(x: u64, y: u64) -> Result<u128, CurveError> {
     assert_ne!(x, 0);
     assert_ne!(y, 0);
-    Ok((x as u128)
+    (x as u128)
         .checked_mul(y as u128)
-        .ok_or(CurveError::Overflow)?)
+        .ok_or(CurveError::Overflow)
 }
 
 // Get spot price for a token in its opposing token
@@ -18,7 +21,7 @@ pub fn k_from_xy(x: u64, y: u64) -> Result<u128, CurveError> {
 pub fn spot_price_from_pair(x: u64, y: u64, precision: u32) -> Result<u64, CurveError> {
     assert_ne!(x, 0);
     assert_ne!(y, 0);
-    Ok(u64::try_from(
+    u64::try_from(
         (x as u128)
             .checked_mul(precision as u128)
             .ok_or(CurveError::Overflow)?
@@ -27,7 +30,14 @@ pub fn spot_price_from_pair(x: u64, y: u64, precision: u32) -> Result<u64, Curve
             .checked_div(precision as u128)
             .ok_or(CurveError::Overflow)?,
     )
-    .map_err(|_| CurveError::Overflow)?)
+    .map_err(|_| CurveError::Overflow)
+}
+
+// Get the liquidity token supply minted by the first deposit
+// L = √(X·Y)
+#[inline]
+pub fn l_from_xy(x: u64, y: u64) -> Result<u64, CurveError> {
+    u64::try_from((x as u128 * y as u128).isqrt()).map_err(|_| CurveError::Overflow)
 }
 
 // Get amount of X and Y to deposit from liquidity token amount
@@ -72,7 +82,7 @@ pub fn xy_withdraw_amounts_from_l(
     a: u64,
     precision: u32,
 ) -> Result<(u64, u64), CurveError> {
-    let ratio = ((l - a) as u128)
+    let ratio = (l.checked_sub(a).ok_or(CurveError::Overflow)? as u128)
         .checked_mul(precision as u128)
         .ok_or(CurveError::Overflow)?
         .checked_div(l as u128)
@@ -127,8 +137,8 @@ pub fn y2_from_x_swap_amount(x: u64, y: u64, a: u64) -> Result<u64, CurveError>
 // ΔX = X₁ - X₂
 #[inline]
 pub fn delta_x_from_y_swap_amount(x: u64, y: u64, a: u64) -> Result<u64, CurveError> {
-    Ok(x.checked_sub(x2_from_y_swap_amount(x, y, a)?)
-        .ok_or(CurveError::Overflow)?)
+    x.checked_sub(x2_from_y_swap_amount(x, y, a)?)
+        .ok_or(CurveError::Overflow)
 }
 
 // Calculate difference in Y from swapping in X
@@ -169,9 +179,96 @@ pub fn delta_y_from_x_swap_amount_with_fee(
     delta_x_from_y_swap_amount_with_fee(y, x, a, fee)
 }
 
+// Calculate the amount of Y to deposit in order to withdraw amount A of X
+// X₂ = X₁ - Amount
+// Y₂ = K / X₂ (rounded up in favor of the pool)
+// ΔY = Y₂ - Y₁
+#[inline]
+pub fn y_swap_amount_from_delta_x(x: u64, y: u64, a: u64) -> Result<u64, CurveError> {
+    let k = k_from_xy(x, y)?;
+    let x_new = (x as u128)
+        .checked_sub(a as u128)
+        .filter(|x_new| *x_new != 0)
+        .ok_or(CurveError::Overflow)?;
+    let y_new = k.div_ceil(x_new);
+    u64::try_from(y_new.checked_sub(y as u128).ok_or(CurveError::Overflow)?)
+        .map_err(|_| CurveError::Overflow)
+}
+
+// Calculate the amount of X to deposit in order to withdraw amount A of Y
+// ΔX = X₂ - X₁
+#[inline]
+pub fn x_swap_amount_from_delta_y(x: u64, y: u64, a: u64) -> Result<u64, CurveError> {
+    y_swap_amount_from_delta_x(y, x, a)
+}
+
+// Calculate the amount of Y to deposit in order to receive amount A of X after fees
+// The fee is charged on the output, so the pool must release ΔX = A / (1 - fee),
+// rounded up in favor of the pool. Returns the amount in and the fee in X.
+#[inline]
+pub fn y_swap_amount_from_delta_x_with_fee(
+    x: u64,
+    y: u64,
+    a: u64,
+    fee: u16,
+) -> Result<(u64, u64), CurveError> {
+    let raw_amount = u64::try_from(
+        (a as u128)
+            .checked_mul(10_000)
+            .ok_or(CurveError::Overflow)?
+            .div_ceil(
+                10_000u128
+                    .checked_sub(fee as u128)
+                    .filter(|rate| *rate != 0)
+                    .ok_or(CurveError::Overflow)?,
+            ),
+    )
+    .map_err(|_| CurveError::Overflow)?;
+    let amount = y_swap_amount_from_delta_x(x, y, raw_amount)?;
+    Ok((amount, raw_amount - a))
+}
+
+// Calculate the amount of X to deposit in order to receive amount A of Y after fees
+#[inline]
+pub fn x_swap_amount_from_delta_y_with_fee(
+    x: u64,
+    y: u64,
+    a: u64,
+    fee: u16,
+) -> Result<(u64, u64), CurveError> {
+    y_swap_amount_from_delta_x_with_fee(y, x, a, fee)
+}
+
+// Calculate the amount of X to swap for Y when depositing amount A of X alone, so that the
+// X left and the Y received are in the ratio of the reserves after the swap
+// With the fee F charged on the output, the swapped amount S solves:
+// S² + S·((2 - F)·X - F·A) - X·A = 0
+#[inline]
+pub fn x_swap_amount_from_single_deposit(x: u64, a: u64, fee: u16) -> Result<u64, CurveError> {
+    // B = ((2 - F)·X - F·A), in basis points then scaled down
+    let b = ((20_000 - fee as i128) * x as i128 - fee as i128 * a as i128) / 10_000;
+    let discriminant = b
+        .unsigned_abs()
+        .checked_mul(b.unsigned_abs())
+        .ok_or(CurveError::Overflow)?
+        .checked_add(
+            (x as u128)
+                .checked_mul(a as u128)
+                .ok_or(CurveError::Overflow)?
+                .checked_mul(4)
+                .ok_or(CurveError::Overflow)?,
+        )
+        .ok_or(CurveError::Overflow)?;
+    let swap_amount = (discriminant.isqrt() as i128 - b) / 2;
+    Ok((swap_amount.max(0) as u64).min(a))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::delta_y_from_x_swap_amount_with_fee;
+    use crate::{
+        delta_y_from_x_swap_amount_with_fee, x_swap_amount_from_delta_y,
+        x_swap_amount_from_delta_y_with_fee, x_swap_amount_from_single_deposit, CurveError,
+    };
     #[test]
     fn swap() {
         let (amount_out, fee) = delta_y_from_x_swap_amount_with_fee(20, 30, 5, 0).unwrap();
@@ -188,4 +285,48 @@ mod tests {
         assert_eq!(amount_out, 5);
         assert_eq!(fee, 1);
     }
+
+    #[test]
+    fn swap_exact_out() {
+        // 20 * 30 = 600, withdrawing 6 Y leaves 24 so X must reach 25
+        assert_eq!(x_swap_amount_from_delta_y(20, 30, 6).unwrap(), 5);
+        // 600 / 29 = 20.68, rounded up in favor of the pool
+        assert_eq!(x_swap_amount_from_delta_y(20, 30, 1).unwrap(), 1);
+        assert_eq!(
+            x_swap_amount_from_delta_y(20, 30, 30),
+            Err(CurveError::Overflow)
+        );
+    }
+
+    #[test]
+    fn swap_exact_out_with_fee() {
+        let (amount_in, fee) = x_swap_amount_from_delta_y_with_fee(20, 30, 5, 100).unwrap();
+        assert_eq!(amount_in, 5);
+        assert_eq!(fee, 1);
+
+        // Swapping the quoted amount in must yield at least the requested amount out
+        let (x, y, out) = (1_000_000_000, 2_000_000_000, 12_345_678);
+        let (amount_in, _) = x_swap_amount_from_delta_y_with_fee(x, y, out, 30).unwrap();
+        let (amount_out, _) = delta_y_from_x_swap_amount_with_fee(x, y, amount_in, 30).unwrap();
+        assert!(amount_out >= out);
+        let (amount_out, _) = delta_y_from_x_swap_amount_with_fee(x, y, amount_in - 1, 30).unwrap();
+        assert!(amount_out < out);
+    }
+
+    #[test]
+    fn single_deposit() {
+        // Without fee S = √(X² + X·A) - X
+        assert_eq!(
+            x_swap_amount_from_single_deposit(1_000_000, 21_000, 0).unwrap(),
+            10_445
+        );
+
+        // The X left and the Y received match the reserves after the swap
+        let (x, y, a, fee) = (1_000_000_000, 2_000_000_000, 10_000_000, 30);
+        let swap_amount = x_swap_amount_from_single_deposit(x, a, fee).unwrap();
+        let (amount_out, _) = delta_y_from_x_swap_amount_with_fee(x, y, swap_amount, fee).unwrap();
+        let left = (a - swap_amount) as u128 * (y - amount_out) as u128;
+        let received = amount_out as u128 * (x + swap_amount) as u128;
+        assert!(left.abs_diff(received) * 10_000 < left);
+    }
 }
@@ -0,0 +1,280 @@
+//! StableSwap invariant for two coin pools
+//!
+//! A·n^n·(x + y) + D = A·D·n^n + D^(n+1) / (n^n·x·y), with n = 2
+//!
+//! The amplification A flattens the curve around the 1:1 price so pegged
+//! pairs trade with little slippage, falling back to constant product
+//! pricing as the pool gets imbalanced. Swap functions follow the naming and
+//! argument order of the constant product ones with the amplification added.
+//!
+//! Deposits and withdrawals by LP amount are proportional to the reserves,
+//! which keeps D per LP token constant, so they share
+//! [`crate::xy_deposit_amounts_from_l`] and [`crate::xy_withdraw_amounts_from_l`].
+//! Only the initial supply differs, see [`l_from_xy`].
+
+use crate::CurveError;
+
+// Newton iterations before giving up on convergence
+const MAX_ITERATIONS: usize = 255;
+
+// Calculate the invariant D of the reserves
+#[inline]
+pub fn d_from_xy(x: u64, y: u64, amp: u64) -> Result<u128, CurveError> {
+    let (x, y) = (x as u128, y as u128);
+    let s = x + y;
+    if s == 0 {
+        return Ok(0);
+    }
+    if x == 0 || y == 0 {
+        return Err(CurveError::Overflow);
+    }
+    let ann = (amp as u128).checked_mul(4).ok_or(CurveError::Overflow)?;
+
+    let mut d = s;
+    for _ in 0..MAX_ITERATIONS {
+        // D_P = D³ / (4·x·y)
+        let d_p = d
+            .checked_mul(d)
+            .ok_or(CurveError::Overflow)?
+            .checked_div(x * 2)
+            .ok_or(CurveError::Overflow)?
+            .checked_mul(d)
+            .ok_or(CurveError::Overflow)?
+            .checked_div(y * 2)
+            .ok_or(CurveError::Overflow)?;
+        let d_prev = d;
+        // D = (Ann·S + 2·D_P)·D / ((Ann - 1)·D + 3·D_P)
+        let numerator = ann
+            .checked_mul(s)
+            .and_then(|ann_s| ann_s.checked_add(d_p.checked_mul(2)?))
+            .and_then(|sum| sum.checked_mul(d))
+            .ok_or(CurveError::Overflow)?;
+        let denominator = ann
+            .checked_sub(1)
+            .and_then(|ann| ann.checked_mul(d))
+            .and_then(|sum| sum.checked_add(d_p.checked_mul(3)?))
+            .ok_or(CurveError::Overflow)?;
+        d = numerator
+            .checked_div(denominator)
+            .ok_or(CurveError::Overflow)?;
+        if d.abs_diff(d_prev) <= 1 {
+            return Ok(d);
+        }
+    }
+    Err(CurveError::Overflow)
+}
+
+// Calculate the reserve of Y that keeps the invariant D for a reserve X
+// y² + (x + D/Ann - D)·y = D³ / (4·x·Ann), solved with Newton's method
+#[inline]
+pub fn y_from_x_and_d(x: u128, d: u128, amp: u64) -> Result<u128, CurveError> {
+    if x == 0 {
+        return Err(CurveError::Overflow);
+    }
+    let ann = (amp as u128).checked_mul(4).ok_or(CurveError::Overflow)?;
+
+    let c = d
+        .checked_mul(d)
+        .ok_or(CurveError::Overflow)?
+        .checked_div(x * 2)
+        .ok_or(CurveError::Overflow)?
+        .checked_mul(d)
+        .ok_or(CurveError::Overflow)?
+        .checked_div(ann.checked_mul(2).ok_or(CurveError::Overflow)?)
+        .ok_or(CurveError::Overflow)?;
+    let b = x
+        .checked_add(d.checked_div(ann).ok_or(CurveError::Overflow)?)
+        .ok_or(CurveError::Overflow)?;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        // y = (y² + c) / (2·y + b - D)
+        let numerator = y
+            .checked_mul(y)
+            .and_then(|y2| y2.checked_add(c))
+            .ok_or(CurveError::Overflow)?;
+        let denominator = y
+            .checked_mul(2)
+            .and_then(|y2| y2.checked_add(b))
+            .and_then(|sum| sum.checked_sub(d))
+            .filter(|denominator| *denominator != 0)
+            .ok_or(CurveError::Overflow)?;
+        y = numerator / denominator;
+        if y.abs_diff(y_prev) <= 1 {
+            return Ok(y);
+        }
+    }
+    Err(CurveError::Overflow)
+}
+
+// Get the LP supply minted by the first deposit, which is D itself
+#[inline]
+pub fn l_from_xy(x: u64, y: u64, amp: u64) -> Result<u64, CurveError> {
+    u64::try_from(d_from_xy(x, y, amp)?).map_err(|_| CurveError::Overflow)
+}
+
+// Calculate the withdraw amount of Y from swapping in X
+// Y₂ = y(X₁ + Amount, D)
+// ΔY = Y₁ - Y₂ - 1 (rounded down in favor of the pool)
+#[inline]
+pub fn delta_y_from_x_swap_amount(x: u64, y: u64, a: u64, amp: u64) -> Result<u64, CurveError> {
+    let d = d_from_xy(x, y, amp)?;
+    let y_new = y_from_x_and_d(
+        (x as u128)
+            .checked_add(a as u128)
+            .ok_or(CurveError::Overflow)?,
+        d,
+        amp,
+    )?;
+    Ok((y as u128).saturating_sub(y_new).saturating_sub(1) as u64)
+}
+
+// Calculate the withdraw amount of X from swapping in Y
+#[inline]
+pub fn delta_x_from_y_swap_amount(x: u64, y: u64, a: u64, amp: u64) -> Result<u64, CurveError> {
+    delta_y_from_x_swap_amount(y, x, a, amp)
+}
+
+// Calculate the withdraw amount of Y from swapping in X, net of the fee
+// Returns the amount out and the fee in Y
+#[inline]
+pub fn delta_y_from_x_swap_amount_with_fee(
+    x: u64,
+    y: u64,
+    a: u64,
+    amp: u64,
+    fee: u16,
+) -> Result<(u64, u64), CurveError> {
+    let raw_amount = delta_y_from_x_swap_amount(x, y, a, amp)?;
+    let amount = raw_amount
+        .checked_mul((10_000 - fee).into())
+        .ok_or(CurveError::Overflow)?
+        .saturating_div(10_000);
+    Ok((amount, raw_amount - amount))
+}
+
+// Calculate the withdraw amount of X from swapping in Y, net of the fee
+#[inline]
+pub fn delta_x_from_y_swap_amount_with_fee(
+    x: u64,
+    y: u64,
+    a: u64,
+    amp: u64,
+    fee: u16,
+) -> Result<(u64, u64), CurveError> {
+    delta_y_from_x_swap_amount_with_fee(y, x, a, amp, fee)
+}
+
+// Calculate the amount of Y to deposit in order to withdraw amount A of X
+// Y₂ = y(X₁ - Amount, D)
+// ΔY = Y₂ - Y₁ + 1 (rounded up in favor of the pool)
+#[inline]
+pub fn y_swap_amount_from_delta_x(x: u64, y: u64, a: u64, amp: u64) -> Result<u64, CurveError> {
+    let d = d_from_xy(x, y, amp)?;
+    let x_new = (x as u128)
+        .checked_sub(a as u128)
+        .filter(|x_new| *x_new != 0)
+        .ok_or(CurveError::Overflow)?;
+    let y_new = y_from_x_and_d(x_new, d, amp)?;
+    u64::try_from(
+        y_new
+            .checked_sub(y as u128)
+            .ok_or(CurveError::Overflow)?
+            .checked_add(1)
+            .ok_or(CurveError::Overflow)?,
+    )
+    .map_err(|_| CurveError::Overflow)
+}
+
+// Calculate the amount of X to deposit in order to withdraw amount A of Y
+#[inline]
+pub fn x_swap_amount_from_delta_y(x: u64, y: u64, a: u64, amp: u64) -> Result<u64, CurveError> {
+    y_swap_amount_from_delta_x(y, x, a, amp)
+}
+
+// Calculate the amount of Y to deposit in order to receive amount A of X after fees
+// Returns the amount in and the fee in X
+#[inline]
+pub fn y_swap_amount_from_delta_x_with_fee(
+    x: u64,
+    y: u64,
+    a: u64,
+    amp: u64,
+    fee: u16,
+) -> Result<(u64, u64), CurveError> {
+    let raw_amount = u64::try_from(
+        (a as u128)
+            .checked_mul(10_000)
+            .ok_or(CurveError::Overflow)?
+            .div_ceil(
+                10_000u128
+                    .checked_sub(fee as u128)
+                    .filter(|rate| *rate != 0)
+                    .ok_or(CurveError::Overflow)?,
+            ),
+    )
+    .map_err(|_| CurveError::Overflow)?;
+    let amount = y_swap_amount_from_delta_x(x, y, raw_amount, amp)?;
+    Ok((amount, raw_amount - a))
+}
+
+// Calculate the amount of X to deposit in order to receive amount A of Y after fees
+#[inline]
+pub fn x_swap_amount_from_delta_y_with_fee(
+    x: u64,
+    y: u64,
+    a: u64,
+    amp: u64,
+    fee: u16,
+) -> Result<(u64, u64), CurveError> {
+    y_swap_amount_from_delta_x_with_fee(y, x, a, amp, fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stable::{
+        d_from_xy, delta_y_from_x_swap_amount, delta_y_from_x_swap_amount_with_fee,
+        x_swap_amount_from_delta_y_with_fee,
+    };
+
+    #[test]
+    fn invariant() {
+        // A balanced pool has D = x + y whatever the amplification
+        assert_eq!(d_from_xy(1_000_000, 1_000_000, 100).unwrap(), 2_000_000);
+        let d = d_from_xy(1_500_000, 500_000, 100).unwrap();
+        assert!(d < 2_000_000 && d > 1_990_000);
+    }
+
+    #[test]
+    fn swap() {
+        let (x, y, a) = (1_000_000_000, 1_000_000_000, 1_000_000);
+        let stable = delta_y_from_x_swap_amount(x, y, a, 100).unwrap();
+        let constant_product = crate::delta_y_from_x_swap_amount(x, y, a).unwrap();
+        // Close to 1:1 and better than constant product
+        assert_eq!(stable, 999_995);
+        assert_eq!(constant_product, 999_001);
+    }
+
+    #[test]
+    fn swap_with_fee() {
+        let (amount_out, fee) =
+            delta_y_from_x_swap_amount_with_fee(1_000_000_000, 1_000_000_000, 1_000_000, 100, 30)
+                .unwrap();
+        assert_eq!(amount_out, 996_995);
+        assert_eq!(fee, 3_000);
+    }
+
+    #[test]
+    fn swap_exact_out_with_fee() {
+        // Swapping the quoted amount in must yield at least the requested amount out
+        let (x, y, out, amp) = (1_000_000_000, 1_200_000_000, 12_345_678, 200);
+        let (amount_in, _) = x_swap_amount_from_delta_y_with_fee(x, y, out, amp, 30).unwrap();
+        let (amount_out, _) =
+            delta_y_from_x_swap_amount_with_fee(x, y, amount_in, amp, 30).unwrap();
+        assert!(amount_out >= out);
+        let (amount_out, _) =
+            delta_y_from_x_swap_amount_with_fee(x, y, amount_in - 1, amp, 30).unwrap();
+        assert!(amount_out <= out);
+    }
+}
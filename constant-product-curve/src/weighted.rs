@@ -0,0 +1,318 @@
+//! Weighted product invariant for two coin pools
+//!
+//! X^Wx · Y^Wy = k
+//!
+//! With equal weights this is the constant product invariant. Unequal weights
+//! shift the value of the pool towards the heavier token, which liquidity
+//! bootstrapping pools move over time to walk the price down. Weights are
+//! only meaningful relative to each other, any scale can be used. Swap
+//! functions follow the naming and argument order of the constant product
+//! ones with the weights of X and Y added.
+//!
+//! Powers are computed in 18 decimals fixed point and rounded in favor of the
+//! pool by MAX_POW_RELATIVE_ERROR. Deposits and withdrawals by LP amount are
+//! proportional to the reserves and share [`crate::xy_deposit_amounts_from_l`]
+//! and [`crate::xy_withdraw_amounts_from_l`].
+
+use crate::CurveError;
+
+// Fixed point one, 18 decimals
+pub const ONE: u128 = 1_000_000_000_000_000_000;
+// ln(2) in fixed point
+const LN_2: u128 = 693_147_180_559_945_309;
+// Bound of the error of pow relative to its result, added to round it up
+const MAX_POW_RELATIVE_ERROR: u128 = 10_000;
+
+// Natural logarithm of a fixed point value of at least one
+// ln(v) = k·ln(2) + ln(m), v = m·2^k with 1 <= m < 2
+// ln(m) = 2·atanh(z) = 2·(z + z³/3 + z⁵/5 + ...), z = (m - 1) / (m + 1)
+#[inline]
+pub fn ln(v: u128) -> Result<u128, CurveError> {
+    if v < ONE {
+        return Err(CurveError::Overflow);
+    }
+    let k = (v / ONE).ilog2();
+    let m = v >> k;
+
+    let z = (m - ONE) * ONE / (m + ONE);
+    let z2 = z * z / ONE;
+    let (mut term, mut sum, mut n) = (z, z, 1);
+    while term != 0 {
+        term = term * z2 / ONE;
+        n += 2;
+        sum += term / n;
+    }
+    Ok(k as u128 * LN_2 + 2 * sum)
+}
+
+// Exponential of a non negative fixed point value
+// e^t = 2^k · e^r, t = k·ln(2) + r with 0 <= r < ln(2)
+#[inline]
+pub fn exp(t: u128) -> Result<u128, CurveError> {
+    let k = t / LN_2;
+    let r = t - k * LN_2;
+
+    let (mut term, mut sum, mut n) = (ONE, ONE, 0);
+    while term != 0 {
+        n += 1;
+        term = term * r / ONE / n;
+        sum += term;
+    }
+    // e^r < 2, so the shift overflows when sum·2^k needs more than 128 bits
+    if k >= (sum.leading_zeros() as u128) {
+        return Err(CurveError::Overflow);
+    }
+    Ok(sum << k)
+}
+
+// Fixed point power of a base by a fixed point exponent, without rounding
+// b^e = e^(e·ln(b)), or 1 / e^(e·ln(1/b)) below one
+#[inline]
+fn pow(base: u128, exponent: u128) -> Result<u128, CurveError> {
+    if base == 0 {
+        return Err(CurveError::Overflow);
+    }
+    if base >= ONE {
+        let power = ln(base)?
+            .checked_mul(exponent)
+            .ok_or(CurveError::Overflow)?
+            / ONE;
+        exp(power)
+    } else {
+        let power = ln(ONE * ONE / base)?
+            .checked_mul(exponent)
+            .ok_or(CurveError::Overflow)?
+            / ONE;
+        // Too small to be represented
+        Ok(exp(power).map_or(0, |inverse| ONE * ONE / inverse))
+    }
+}
+
+// Fixed point power rounded up
+#[inline]
+pub fn pow_up(base: u128, exponent: u128) -> Result<u128, CurveError> {
+    let power = pow(base, exponent)?;
+    power
+        .checked_add(
+            power / ONE * MAX_POW_RELATIVE_ERROR + power % ONE * MAX_POW_RELATIVE_ERROR / ONE + 1,
+        )
+        .ok_or(CurveError::Overflow)
+}
+
+// Calculate the withdraw amount of Y from swapping in X
+// ΔY = Y · (1 - (X / (X + Amount))^(Wx / Wy))
+// Rounded down in favor of the pool
+#[inline]
+pub fn delta_y_from_x_swap_amount(
+    x: u64,
+    y: u64,
+    a: u64,
+    weight_x: u64,
+    weight_y: u64,
+) -> Result<u64, CurveError> {
+    if x == 0 || weight_x == 0 || weight_y == 0 {
+        return Err(CurveError::Overflow);
+    }
+    let x_new = (x as u128)
+        .checked_add(a as u128)
+        .ok_or(CurveError::Overflow)?;
+    // A larger base and a smaller exponent leave more Y in the pool
+    let base = (x as u128 * ONE).div_ceil(x_new);
+    let exponent = weight_x as u128 * ONE / weight_y as u128;
+    let power = pow_up(base, exponent)?.min(ONE);
+    Ok((y as u128 * (ONE - power) / ONE) as u64)
+}
+
+// Calculate the withdraw amount of X from swapping in Y
+#[inline]
+pub fn delta_x_from_y_swap_amount(
+    x: u64,
+    y: u64,
+    a: u64,
+    weight_x: u64,
+    weight_y: u64,
+) -> Result<u64, CurveError> {
+    delta_y_from_x_swap_amount(y, x, a, weight_y, weight_x)
+}
+
+// Calculate the withdraw amount of Y from swapping in X, net of the fee
+// Returns the amount out and the fee in Y
+#[inline]
+pub fn delta_y_from_x_swap_amount_with_fee(
+    x: u64,
+    y: u64,
+    a: u64,
+    weight_x: u64,
+    weight_y: u64,
+    fee: u16,
+) -> Result<(u64, u64), CurveError> {
+    let raw_amount = delta_y_from_x_swap_amount(x, y, a, weight_x, weight_y)?;
+    let amount = raw_amount
+        .checked_mul((10_000 - fee).into())
+        .ok_or(CurveError::Overflow)?
+        .saturating_div(10_000);
+    Ok((amount, raw_amount - amount))
+}
+
+// Calculate the withdraw amount of X from swapping in Y, net of the fee
+#[inline]
+pub fn delta_x_from_y_swap_amount_with_fee(
+    x: u64,
+    y: u64,
+    a: u64,
+    weight_x: u64,
+    weight_y: u64,
+    fee: u16,
+) -> Result<(u64, u64), CurveError> {
+    delta_y_from_x_swap_amount_with_fee(y, x, a, weight_y, weight_x, fee)
+}
+
+// Calculate the amount of Y to deposit in order to withdraw amount A of X
+// ΔY = Y · ((X / (X - Amount))^(Wx / Wy) - 1)
+// Rounded up in favor of the pool
+#[inline]
+pub fn y_swap_amount_from_delta_x(
+    x: u64,
+    y: u64,
+    a: u64,
+    weight_x: u64,
+    weight_y: u64,
+) -> Result<u64, CurveError> {
+    if weight_x == 0 || weight_y == 0 {
+        return Err(CurveError::Overflow);
+    }
+    let x_new = (x as u128)
+        .checked_sub(a as u128)
+        .filter(|x_new| *x_new != 0)
+        .ok_or(CurveError::Overflow)?;
+    // A larger base and exponent ask for more Y
+    let base = (x as u128 * ONE).div_ceil(x_new);
+    let exponent = (weight_x as u128 * ONE).div_ceil(weight_y as u128);
+    let power = pow_up(base, exponent)?;
+    u64::try_from(
+        (y as u128)
+            .checked_mul(power - ONE)
+            .ok_or(CurveError::Overflow)?
+            .div_ceil(ONE),
+    )
+    .map_err(|_| CurveError::Overflow)
+}
+
+// Calculate the amount of X to deposit in order to withdraw amount A of Y
+#[inline]
+pub fn x_swap_amount_from_delta_y(
+    x: u64,
+    y: u64,
+    a: u64,
+    weight_x: u64,
+    weight_y: u64,
+) -> Result<u64, CurveError> {
+    y_swap_amount_from_delta_x(y, x, a, weight_y, weight_x)
+}
+
+// Calculate the amount of Y to deposit in order to receive amount A of X after fees
+// Returns the amount in and the fee in X
+#[inline]
+pub fn y_swap_amount_from_delta_x_with_fee(
+    x: u64,
+    y: u64,
+    a: u64,
+    weight_x: u64,
+    weight_y: u64,
+    fee: u16,
+) -> Result<(u64, u64), CurveError> {
+    let raw_amount = u64::try_from(
+        (a as u128)
+            .checked_mul(10_000)
+            .ok_or(CurveError::Overflow)?
+            .div_ceil(
+                10_000u128
+                    .checked_sub(fee as u128)
+                    .filter(|rate| *rate != 0)
+                    .ok_or(CurveError::Overflow)?,
+            ),
+    )
+    .map_err(|_| CurveError::Overflow)?;
+    let amount = y_swap_amount_from_delta_x(x, y, raw_amount, weight_x, weight_y)?;
+    Ok((amount, raw_amount - a))
+}
+
+// Calculate the amount of X to deposit in order to receive amount A of Y after fees
+#[inline]
+pub fn x_swap_amount_from_delta_y_with_fee(
+    x: u64,
+    y: u64,
+    a: u64,
+    weight_x: u64,
+    weight_y: u64,
+    fee: u16,
+) -> Result<(u64, u64), CurveError> {
+    y_swap_amount_from_delta_x_with_fee(y, x, a, weight_y, weight_x, fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::weighted::{
+        delta_y_from_x_swap_amount, delta_y_from_x_swap_amount_with_fee, exp, ln,
+        x_swap_amount_from_delta_y_with_fee, ONE,
+    };
+
+    #[test]
+    fn ln_exp() {
+        assert_eq!(ln(ONE).unwrap(), 0);
+        assert_eq!(ln(2 * ONE).unwrap(), 693_147_180_559_945_309);
+        assert_eq!(exp(0).unwrap(), ONE);
+        assert!(exp(ONE).unwrap().abs_diff(2_718_281_828_459_045_235) < 100);
+        assert!(exp(ln(123 * ONE).unwrap()).unwrap().abs_diff(123 * ONE) < 10_000);
+        assert!(exp(100 * ONE).is_err());
+    }
+
+    #[test]
+    fn swap() {
+        let (x, y, a) = (1_000_000_000, 1_000_000_000, 1_000_000);
+        // Equal weights quote as constant product, rounded down
+        assert_eq!(
+            delta_y_from_x_swap_amount(x, y, a, 5_000, 5_000).unwrap(),
+            999_000
+        );
+        // Y · (1 - (X / (X + A))^4) = 3_990_019.96
+        assert_eq!(
+            delta_y_from_x_swap_amount(x, y, a, 8_000, 2_000).unwrap(),
+            3_990_019
+        );
+        // Y · (1 - (X / (X + A))^0.25) = 249_843.86
+        assert_eq!(
+            delta_y_from_x_swap_amount(x, y, a, 2_000, 8_000).unwrap(),
+            249_843
+        );
+    }
+
+    #[test]
+    fn swap_with_fee() {
+        let (amount_out, fee) = delta_y_from_x_swap_amount_with_fee(
+            1_000_000_000,
+            1_000_000_000,
+            1_000_000,
+            8_000,
+            2_000,
+            1_000,
+        )
+        .unwrap();
+        assert_eq!(amount_out, 3_591_017);
+        assert_eq!(fee, 399_002);
+    }
+
+    #[test]
+    fn swap_exact_out_with_fee() {
+        // Swapping the quoted amount in must yield at least the requested amount out
+        let (x, y, out) = (1_000_000_000, 1_200_000_000, 12_345_678);
+        let (amount_in, _) =
+            x_swap_amount_from_delta_y_with_fee(x, y, out, 3_000, 7_000, 30).unwrap();
+        let (amount_out, _) =
+            delta_y_from_x_swap_amount_with_fee(x, y, amount_in, 3_000, 7_000, 30).unwrap();
+        assert!(amount_out >= out);
+        let (amount_out, _) =
+            delta_y_from_x_swap_amount_with_fee(x, y, amount_in - 1, 3_000, 7_000, 30).unwrap();
+        assert!(amount_out <= out);
+    }
+}